@@ -0,0 +1,121 @@
+use crate::model::{Model, Topic, AttributeValueType, ATTRIBUTE_ORDER};
+use std::collections::BTreeSet;
+
+#[derive(Debug)]
+pub(crate) struct AttributeTable {
+    title: String,
+    columns: Vec<AttributeColumn>,
+    rows: Vec<AttributeRow>,
+}
+
+#[derive(Debug)]
+pub(crate) struct AttributeColumn {
+    name: String,
+    value_type: AttributeValueType,
+}
+
+#[derive(Debug)]
+pub(crate) struct AttributeRow {
+    topic_name: String,
+    // The primary category comes first.
+    categories: Vec<String>,
+    // One entry per column, each holding the topic's values for that attribute in their
+    // canonical form, so dates look like "2022-01-03" and sort correctly in a spreadsheet.
+    values: Vec<Vec<String>>,
+}
+
+impl AttributeTable {
+    pub(crate) fn new(model: &Model, category: Option<&str>) -> Self {
+        // If a category is given, only the topics in that category are included. A column is
+        // included only if at least one of the included topics has that attribute, otherwise
+        // the table for a small category would be almost entirely empty columns.
+        let title = category.map_or(super::FILE_NAME_ALL_TOPICS.to_string(), |category| category.to_string());
+        let mut topics = model.get_topics().values()
+            .filter(|topic| model.topic_is_in_audience(topic))
+            .filter(|topic| category.map_or(true, |category| topic.has_category(category)))
+            .collect::<Vec<_>>();
+        topics.sort_by_cached_key(|topic| topic.get_name().to_lowercase());
+        let columns = Self::make_columns(model, &topics);
+        let rows = topics.iter()
+            .map(|topic| {
+                let values = columns.iter()
+                    .map(|column| topic.get_attributes().get(&column.name).map_or(vec![], |attribute| attribute.get_values().clone()))
+                    .collect::<Vec<_>>();
+                AttributeRow {
+                    topic_name: topic.get_name().to_string(),
+                    categories: topic.get_categories().clone(),
+                    values,
+                }
+            })
+            .collect();
+        Self {
+            title,
+            columns,
+            rows,
+        }
+    }
+
+    fn make_columns(model: &Model, topics: &Vec<&Topic>) -> Vec<AttributeColumn> {
+        // Columns follow ATTRIBUTE_ORDER, the same order as the attribute table at the top of
+        // each topic. Any attribute type that's not in that list goes at the end alphabetically.
+        let mut names = ATTRIBUTE_ORDER.iter()
+            .map(|name| name.to_string())
+            .filter(|name| topics.iter().any(|topic| topic.get_attributes().contains_key(name)))
+            .collect::<Vec<_>>();
+        let other_names = topics.iter()
+            .flat_map(|topic| topic.get_attributes().keys())
+            .filter(|name| !ATTRIBUTE_ORDER.contains(&name.as_str()))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        names.extend(other_names.into_iter());
+        names.iter()
+            .map(|name| {
+                let value_type = model.get_attribute_type(name).map_or(AttributeValueType::String, |attribute_type| attribute_type.get_value_type().clone());
+                AttributeColumn {
+                    name: name.clone(),
+                    value_type,
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    pub(crate) fn get_columns(&self) -> &Vec<AttributeColumn> {
+        &self.columns
+    }
+
+    pub(crate) fn get_rows(&self) -> &Vec<AttributeRow> {
+        &self.rows
+    }
+}
+
+impl AttributeColumn {
+    pub(crate) fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn get_value_type(&self) -> &AttributeValueType {
+        &self.value_type
+    }
+}
+
+impl AttributeRow {
+    pub(crate) fn get_topic_name(&self) -> &str {
+        &self.topic_name
+    }
+
+    pub(crate) fn get_category(&self) -> Option<&String> {
+        self.categories.first()
+    }
+
+    pub(crate) fn get_categories(&self) -> &Vec<String> {
+        &self.categories
+    }
+
+    pub(crate) fn get_values(&self) -> &Vec<Vec<String>> {
+        &self.values
+    }
+}
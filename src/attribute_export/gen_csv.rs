@@ -0,0 +1,43 @@
+use crate::model::{AttributeType, AttributeValueType};
+use super::*;
+
+pub(crate) fn gen_csv(table: &AttributeTable) -> String {
+    // Like:
+    //   Topic,Category,Categories,Title,Author,Year
+    //   Programming Rust,Books,Books; Rust,Programming Rust,"Jim Blandy; Jason Orendorff",2017
+    // Dates stay in the sortable "2022-01-03" form rather than the display form used in the wiki.
+    let mut lines = vec![];
+    let mut header = vec![quote(COLUMN_NAME_TOPIC), quote(COLUMN_NAME_CATEGORY), quote(COLUMN_NAME_CATEGORIES)];
+    header.extend(table.get_columns().iter().map(|column| quote(column.get_name())));
+    lines.push(header.join(","));
+    for row in table.get_rows().iter() {
+        let mut cells = vec![quote(row.get_topic_name()), quote(&row.get_category().cloned().unwrap_or_default()), quote(&row.get_categories().join(DELIM_MULTIPLE_VALUES))];
+        for (column, values) in table.get_columns().iter().zip(row.get_values().iter()) {
+            let cell = values.iter()
+                .map(|value| export_value(column.get_value_type(), value))
+                .collect::<Vec<_>>()
+                .join(DELIM_MULTIPLE_VALUES);
+            cells.push(quote(&cell));
+        }
+        lines.push(cells.join(","));
+    }
+    // Spreadsheets expect CRLF line endings in CSV files.
+    format!("{}\r\n", lines.join("\r\n"))
+}
+
+fn export_value(value_type: &AttributeValueType, value: &str) -> String {
+    // Years are stored with leading zeros so that they sort as text.
+    match value_type {
+        AttributeValueType::Year => AttributeType::value_to_display_string(value_type, value),
+        _ => value.to_string(),
+    }
+}
+
+fn quote(text: &str) -> String {
+    // Quote only when the spreadsheet would otherwise split or misread the cell.
+    if text.contains(",") || text.contains("\"") || text.contains("\n") || text.contains("\r") {
+        format!("\"{}\"", text.replace("\"", "\"\""))
+    } else {
+        text.to_string()
+    }
+}
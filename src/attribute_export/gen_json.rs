@@ -0,0 +1,54 @@
+use crate::model::AttributeValueType;
+use crate::model::json::{json_quote, json_field, json_object, json_array, json_array_lines};
+use super::*;
+
+pub(crate) fn gen_json(table: &AttributeTable) -> String {
+    // An array with one object per topic, like:
+    //   [
+    //     {"Topic": "Programming Rust", "Category": "Books", "Categories": ["Books", "Rust"], "Author": ["Jim Blandy", "Jason Orendorff"], "Year": [2017]}
+    //   ]
+    // Each attribute is an array even when the topic has only one value, so that a column has the
+    // same type in every row. Attributes the topic doesn't have are left out of its object.
+    let items = table.get_rows().iter()
+        .map(|row| {
+            let mut fields = vec![json_field(COLUMN_NAME_TOPIC, &json_quote(row.get_topic_name()))];
+            if let Some(category) = row.get_category() {
+                fields.push(json_field(COLUMN_NAME_CATEGORY, &json_quote(category)));
+                fields.push(json_field(COLUMN_NAME_CATEGORIES, &json_array(&row.get_categories().iter().map(|category| json_quote(category)).collect::<Vec<_>>())));
+            }
+            for (column, values) in table.get_columns().iter().zip(row.get_values().iter()) {
+                let values = values.iter()
+                    .map(|value| export_value(column.get_value_type(), value))
+                    .collect::<Vec<_>>();
+                if !values.is_empty() {
+                    fields.push(json_field(column.get_name(), &json_array(&values)));
+                }
+            }
+            json_object(&fields)
+        })
+        .collect::<Vec<_>>();
+    json_array_lines(&items)
+}
+
+fn export_value(value_type: &AttributeValueType, value: &str) -> String {
+    // Numbers and years become JSON numbers and Yes/No values become booleans. Anything that
+    // doesn't parse as expected stays a string rather than being lost.
+    match value_type {
+        AttributeValueType::Boolean => match util::bool::string_to_bool(value) {
+            Ok(value) => value.to_string(),
+            Err(_) => json_quote(value),
+        },
+        AttributeValueType::Number | AttributeValueType::Year => {
+            if let Ok(value) = value.parse::<i64>() {
+                value.to_string()
+            } else {
+                match value.parse::<f64>() {
+                    // JSON has no way to write infinity or NaN.
+                    Ok(value) if value.is_finite() => value.to_string(),
+                    _ => json_quote(value),
+                }
+            }
+        },
+        _ => json_quote(value),
+    }
+}
@@ -0,0 +1,49 @@
+use std::fs;
+use crate::model::Model;
+use crate::dokuwiki::gen_tools_wiki::PROJECT_NAME;
+use super::*;
+use super::gen_csv::gen_csv;
+use super::gen_json::gen_json;
+
+pub fn export_attributes(category: Option<&str>, filter_audience: Option<String>) {
+    // Write one table as both a CSV file and a JSON file, either for a single category like
+    // "Books" or for every topic in the wiki.
+    let model = build_model_from_dokuwiki(filter_audience);
+    if let Some(category) = category {
+        assert!(model.get_categories().iter().any(|model_category| model_category.eq(category)), "Category not found: \"{}\".", category);
+    }
+    export_table(&AttributeTable::new(&model, category));
+}
+
+pub fn export_attributes_all_categories(filter_audience: Option<String>) {
+    // One pair of files per category plus one pair for the whole wiki.
+    println!("\nAttribute export: Start.");
+
+    let model = build_model_from_dokuwiki(filter_audience);
+    export_table(&AttributeTable::new(&model, None));
+    for category in model.get_categories().iter() {
+        let table = AttributeTable::new(&model, Some(category));
+        if !table.get_rows().is_empty() {
+            export_table(&table);
+        }
+    }
+
+    println!("\nAttribute export: Done.");
+}
+
+fn export_table(table: &AttributeTable) {
+    util::file::path_create_if_necessary_r(FOLDER_ATTRIBUTE_EXPORT.to_string()).unwrap();
+    let file_name = crate::dokuwiki::legal_file_name(table.get_title());
+    let path_csv = format!("{}/{}{}", FOLDER_ATTRIBUTE_EXPORT, file_name, FILE_EXTENSION_CSV);
+    fs::write(&path_csv, gen_csv(table)).unwrap();
+    let path_json = format!("{}/{}{}", FOLDER_ATTRIBUTE_EXPORT, file_name, FILE_EXTENSION_JSON);
+    fs::write(&path_json, gen_json(table)).unwrap();
+    println!("{}: {} topics, {} attributes, written to \"{}\" and \"{}\".", table.get_title(), table.get_rows().len(), table.get_columns().len(), path_csv, path_json);
+}
+
+fn build_model_from_dokuwiki(filter_audience: Option<String>) -> Model {
+    // Nothing is written back to the DokuWiki pages, so there's no need for the file monitor.
+    let (mut model, _build_process) = crate::dokuwiki::to_model::build_model(PROJECT_NAME, &PROJECT_NAME.to_lowercase(), true, filter_audience, None, None, None, None);
+    crate::dokuwiki::to_model::complete_model(&mut model);
+    model
+}
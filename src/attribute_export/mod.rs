@@ -0,0 +1,23 @@
+pub(crate) mod attribute_table;
+pub(crate) use attribute_table::*;
+
+pub(crate) mod gen_csv;
+
+pub(crate) mod gen_json;
+
+pub mod gen_tools_wiki;
+
+// Tables of the topics' attributes, one row per topic and one column per attribute type, for
+// analyzing things like the book list in a spreadsheet.
+pub(crate) const FOLDER_ATTRIBUTE_EXPORT: &str = "C:/Wiki Gen Backup/Attributes";
+pub(crate) const FILE_EXTENSION_CSV: &str = ".csv";
+pub(crate) const FILE_EXTENSION_JSON: &str = ".json";
+pub(crate) const FILE_NAME_ALL_TOPICS: &str = "All Topics";
+
+pub(crate) const COLUMN_NAME_TOPIC: &str = "Topic";
+// The primary category, followed by all of the topic's categories with the primary one first.
+pub(crate) const COLUMN_NAME_CATEGORY: &str = "Category";
+pub(crate) const COLUMN_NAME_CATEGORIES: &str = "Categories";
+// A topic can have several values for one attribute, like two authors. In a CSV cell these are
+// joined with this delimiter, while in JSON they become an array.
+pub(crate) const DELIM_MULTIPLE_VALUES: &str = "; ";
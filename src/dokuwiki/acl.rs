@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+
+use super::*;
+use crate::model::{Model, Topic, TopicKey, ATTRIBUTE_VALUE_PUBLIC, ATTRIBUTE_VALUE_PRIVATE, ATTRIBUTE_VALUE_UNKNOWN};
+
+// Access control for serving the whole wiki from one DokuWiki instance instead of building a
+// separate public copy. Each topic's Visibility attribute says which audiences may read it:
+// Public becomes DokuWiki's @ALL group and any other audience becomes a group of the same name,
+// so "Client Acme" becomes @client_acme. A topic without a known Visibility takes the audiences of
+// its primary category, and failing that it's readable only by the admin, who bypasses the ACL.
+//
+// DokuWiki checks the rules for a page, then for each enclosing namespace, then for the root, and
+// stops at the first level where any rule matches one of the user's groups. So each namespace
+// gets a default rule for the audiences most of its topics share, and only the pages that differ
+// get rules of their own. Every level includes an @ALL rule so that it always stops the search.
+
+pub(crate) const ACL_GROUP_ALL: &str = "@ALL";
+pub(crate) const ACL_PERMISSION_NONE: u8 = 0;
+pub(crate) const ACL_PERMISSION_READ: u8 = 1;
+const ACL_NAMESPACE_WILDCARD: &str = "*";
+const ACL_FILE_HEADER: &str = "# acl.auth.php\n# <?php exit()?>\n";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct AclRule {
+    // A page ID like "tools:rust" or a namespace like "tools:*".
+    scope: String,
+    // A group like "@ALL" or "@team".
+    group: String,
+    permission: u8,
+}
+
+pub(crate) struct AclPlan {
+    // For each namespace, the groups that can read it by default along with the number of
+    // topics, and the pages whose groups are different.
+    namespaces: BTreeMap<String, AclNamespacePlan>,
+    // The groups allowed to read each topic page.
+    page_groups: BTreeMap<String, Vec<String>>,
+}
+
+struct AclNamespacePlan {
+    default_groups: Vec<String>,
+    topic_count: usize,
+    exceptions: Vec<String>,
+}
+
+impl AclRule {
+    pub(crate) fn new(scope: &str, group: &str, permission: u8) -> Self {
+        Self {
+            scope: scope.to_string(),
+            group: group.to_string(),
+            permission,
+        }
+    }
+
+    fn to_line(&self) -> String {
+        // Spaces in a group or page name are written as "%20" in the file.
+        format!("{}\t{}\t{}", self.scope.replace(' ', "%20"), self.group.replace(' ', "%20"), self.permission)
+    }
+}
+
+pub(crate) fn audience_to_group(audience: &str) -> String {
+    if audience.eq_ignore_ascii_case(ATTRIBUTE_VALUE_PUBLIC) {
+        ACL_GROUP_ALL.to_string()
+    } else {
+        format!("@{}", audience.trim().to_lowercase().replace(' ', "_"))
+    }
+}
+
+fn topic_groups(model: &Model, topic: &Topic) -> Vec<String> {
+    // The groups that can read the topic. Public makes any other audience redundant.
+    let is_known = |audiences: &Vec<String>| audiences.iter().any(|audience| !audience.eq_ignore_ascii_case(ATTRIBUTE_VALUE_UNKNOWN));
+    let mut audiences = topic.get_audiences();
+    if !is_known(&audiences) {
+        if let Some(category_topic) = topic.get_category().and_then(|category| model.get_topics().get(&TopicKey::new(model.get_main_namespace(), &category))) {
+            audiences = category_topic.get_audiences();
+        }
+    }
+    let mut groups = audiences.iter()
+        .filter(|audience| !audience.eq_ignore_ascii_case(ATTRIBUTE_VALUE_PRIVATE) && !audience.eq_ignore_ascii_case(ATTRIBUTE_VALUE_UNKNOWN))
+        .map(|audience| audience_to_group(audience))
+        .collect::<Vec<_>>();
+    if groups.iter().any(|group| group.eq(ACL_GROUP_ALL)) {
+        groups = vec![ACL_GROUP_ALL.to_string()];
+    }
+    groups.sort();
+    groups.dedup();
+    groups
+}
+
+fn page_id(topic_key: &TopicKey) -> String {
+    format!("{}{}{}", topic_key.get_namespace(), DELIM_NAMESPACE, legal_file_name(topic_key.get_topic_name()))
+}
+
+fn namespace_scope(namespace: &str) -> String {
+    format!("{}{}{}", namespace.trim_start_matches(DELIM_NAMESPACE), DELIM_NAMESPACE, ACL_NAMESPACE_WILDCARD)
+}
+
+fn rules_for_groups(scope: &str, groups: &Vec<String>) -> Vec<AclRule> {
+    // The @ALL rule comes first and is always there so that this level of the search is final.
+    let mut rules = vec![AclRule::new(scope, ACL_GROUP_ALL, if groups.iter().any(|group| group.eq(ACL_GROUP_ALL)) { ACL_PERMISSION_READ } else { ACL_PERMISSION_NONE })];
+    rules.extend(groups.iter().filter(|group| !group.eq(&ACL_GROUP_ALL)).map(|group| AclRule::new(scope, group, ACL_PERMISSION_READ)));
+    rules
+}
+
+impl AclPlan {
+    pub(crate) fn new(model: &Model) -> Self {
+        let mut page_groups = BTreeMap::new();
+        let mut namespace_pages: BTreeMap<String, Vec<(String, Vec<String>)>> = BTreeMap::new();
+        for topic in model.get_topics().values() {
+            let topic_key = topic.get_topic_key();
+            let groups = topic_groups(model, topic);
+            let page_id = page_id(&topic_key);
+            namespace_pages.entry(topic_key.get_namespace().to_string()).or_insert_with(Vec::new).push((page_id.clone(), groups.clone()));
+            page_groups.insert(page_id, groups);
+        }
+        let mut namespaces = BTreeMap::new();
+        for (namespace, pages) in namespace_pages.iter() {
+            // The most common set of groups becomes the default. On a tie, take the set with the
+            // fewest groups so that the default is the more restrictive one.
+            let mut counts: BTreeMap<&Vec<String>, usize> = BTreeMap::new();
+            for (_, groups) in pages.iter() {
+                *counts.entry(groups).or_insert(0) += 1;
+            }
+            let default_groups = counts.iter()
+                .max_by(|(groups_a, count_a), (groups_b, count_b)| count_a.cmp(count_b).then(groups_b.len().cmp(&groups_a.len())))
+                .map(|(groups, _)| (*groups).clone())
+                .unwrap();
+            let exceptions = pages.iter()
+                .filter(|(_, groups)| !groups.eq(&default_groups))
+                .map(|(page_id, _)| page_id.clone())
+                .collect();
+            namespaces.insert(namespace.clone(), AclNamespacePlan { default_groups, topic_count: pages.len(), exceptions });
+        }
+        // The navigation pages like Recent Topics and the attribute indexes list every topic, so
+        // they're left to the admin.
+        namespaces.insert(model.namespace_navigation(), AclNamespacePlan { default_groups: vec![], topic_count: 0, exceptions: vec![] });
+        Self {
+            namespaces,
+            page_groups,
+        }
+    }
+
+    pub(crate) fn get_rules(&self) -> Vec<AclRule> {
+        let mut rules = vec![];
+        for (namespace, namespace_plan) in self.namespaces.iter() {
+            rules.append(&mut rules_for_groups(&namespace_scope(namespace), &namespace_plan.default_groups));
+            for page_id in namespace_plan.exceptions.iter() {
+                rules.append(&mut rules_for_groups(page_id, &self.page_groups[page_id]));
+            }
+        }
+        rules
+    }
+
+    pub(crate) fn get_file_text(&self) -> String {
+        // A fragment to paste into conf/acl.auth.php, with the per-namespace plan as comments.
+        let mut text = ACL_FILE_HEADER.to_string();
+        for line in self.get_plan_lines().iter() {
+            text.push_str(&format!("# {}\n", line));
+        }
+        for rule in self.get_rules().iter() {
+            text.push_str(&rule.to_line());
+            text.push('\n');
+        }
+        text
+    }
+
+    pub(crate) fn get_plan_lines(&self) -> Vec<String> {
+        self.namespaces.iter()
+            .map(|(namespace, namespace_plan)| {
+                let default_groups = if namespace_plan.default_groups.is_empty() { "admin only".to_string() } else { namespace_plan.default_groups.join(", ") };
+                format!("{}: {} by default; {} topics, {} with their own rules.", namespace, default_groups, namespace_plan.topic_count, namespace_plan.exceptions.len())
+            })
+            .collect()
+    }
+
+    pub(crate) fn check(&self, rules_on_disk: &Vec<AclRule>) -> Vec<String> {
+        // Compare who can read each topic page under the rules on disk with who should. Only read
+        // access matters, so a group that's also allowed to edit a page is fine.
+        let mut groups = self.page_groups.values().flatten().cloned().collect::<Vec<_>>();
+        groups.push(ACL_GROUP_ALL.to_string());
+        groups.sort();
+        groups.dedup();
+        let mut problems = vec![];
+        for (page_id, page_groups) in self.page_groups.iter() {
+            for group in groups.iter() {
+                let should_read = page_groups.contains(group) || page_groups.iter().any(|page_group| page_group.eq(ACL_GROUP_ALL));
+                let can_read = effective_permission(rules_on_disk, page_id, group) >= ACL_PERMISSION_READ;
+                if can_read != should_read {
+                    problems.push(format!("{}: {} {} read it on disk but {} per the model.", page_id, group, if can_read { "can" } else { "can't" }, if should_read { "should" } else { "shouldn't" }));
+                }
+            }
+        }
+        problems
+    }
+}
+
+pub(crate) fn effective_permission(rules: &Vec<AclRule>, page_id: &str, group: &str) -> u8 {
+    // The permission for a member of the group, who is also in @ALL, following DokuWiki's search
+    // from the page up through its namespaces to the root.
+    let matches_group = |rule: &&AclRule| rule.group.eq_ignore_ascii_case(group) || rule.group.eq(ACL_GROUP_ALL);
+    let mut scopes = vec![page_id.to_string()];
+    let mut namespace = page_id.to_string();
+    while let Some(index) = namespace.rfind(DELIM_NAMESPACE) {
+        namespace.truncate(index);
+        scopes.push(namespace_scope(&namespace));
+    }
+    scopes.push(ACL_NAMESPACE_WILDCARD.to_string());
+    for scope in scopes.iter() {
+        if let Some(permission) = rules.iter().filter(|rule| rule.scope.eq(scope)).filter(matches_group).map(|rule| rule.permission).max() {
+            return permission;
+        }
+    }
+    ACL_PERMISSION_NONE
+}
+
+pub(crate) fn read_acl_file(file_name: &str) -> Result<Vec<AclRule>, String> {
+    // Each line in acl.auth.php is a scope, a user or group, and a permission separated by
+    // whitespace. Comments start with "#". Rules for single users are read but never match a
+    // group.
+    let mut rules = vec![];
+    for line in util::file::read_file_as_lines_r(file_name).map_err(|err| format!("Unable to read \"{}\": {:?}", file_name, err))?.iter() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let splits = line.split_whitespace().collect::<Vec<_>>();
+        if splits.len() != 3 {
+            return Err(format!("Expected three fields in \"{}\" in \"{}\".", line, file_name));
+        }
+        let permission = splits[2].parse::<u8>().map_err(|_| format!("Bad permission in \"{}\" in \"{}\".", line, file_name))?;
+        rules.push(AclRule::new(&splits[0].replace("%20", " "), &splits[1].replace("%20", " "), permission));
+    }
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_rule_overrides_namespace_rule() {
+        let rules = vec![
+            AclRule::new("*", ACL_GROUP_ALL, ACL_PERMISSION_NONE),
+            AclRule::new("tools:*", ACL_GROUP_ALL, ACL_PERMISSION_READ),
+            AclRule::new("tools:handoff", ACL_GROUP_ALL, ACL_PERMISSION_NONE),
+            AclRule::new("tools:handoff", "@client_acme", ACL_PERMISSION_READ),
+        ];
+        assert_eq!(effective_permission(&rules, "tools:rust", "@team"), ACL_PERMISSION_READ);
+        assert_eq!(effective_permission(&rules, "tools:handoff", "@team"), ACL_PERMISSION_NONE);
+        assert_eq!(effective_permission(&rules, "tools:handoff", "@client_acme"), ACL_PERMISSION_READ);
+        assert_eq!(effective_permission(&rules, "book:chapter", "@team"), ACL_PERMISSION_NONE);
+    }
+
+    #[test]
+    fn audiences_become_groups() {
+        assert_eq!(audience_to_group("Public"), ACL_GROUP_ALL);
+        assert_eq!(audience_to_group("Client Acme"), "@client_acme");
+    }
+}
@@ -0,0 +1,116 @@
+use crate::*;
+use super::*;
+use crate::model::{List, Table};
+
+pub(crate) fn format_page(content: &str) -> String {
+    // Put a hand-edited page into the form GenFromModel would write without building a model.
+    // Headers, lists, and tables are rewritten through the same markup functions the generator
+    // uses, trailing spaces and extra blank lines are removed, and everything else is left alone.
+    // Nothing between a marker like "<code>" or "<WRAP>" and its closing marker is touched.
+    let content = content.split(DELIM_LINEFEED)
+        .map(|line| line.trim_end())
+        .join(DELIM_LINEFEED);
+    let content = util::format::remove_repeated_n(&content, DELIM_LINEFEED, 2);
+    let mut marker_exit_string: Option<String> = None;
+    let mut paragraphs = vec![];
+    for paragraph in content.split(DELIM_PARAGRAPH) {
+        if let Some(exit_string) = &marker_exit_string {
+            if paragraph.trim().eq(exit_string) {
+                marker_exit_string = None;
+            }
+            paragraphs.push(paragraph.to_string());
+            continue;
+        }
+        if let Ok(Some((_, exit_string))) = parse_marker_optional(paragraph) {
+            marker_exit_string = Some(exit_string);
+            paragraphs.push(paragraph.trim().to_string());
+            continue;
+        }
+        paragraphs.push(format_paragraph(paragraph));
+    }
+    util::parse::trim_linefeeds(&paragraphs.join(DELIM_PARAGRAPH)).to_string()
+}
+
+fn format_paragraph(paragraph: &str) -> String {
+    // A paragraph that doesn't parse cleanly as a header, list, or table is kept as it is, since
+    // the round trip will report it anyway.
+    if let Some(markup) = format_header_optional(paragraph) {
+        return markup;
+    }
+    if let Ok(Some(list)) = parse_list_optional(paragraph) {
+        return format_list(&list);
+    }
+    if let Ok(Some(table)) = parse_table_optional(paragraph) {
+        return format_table(&table);
+    }
+    paragraph.to_string()
+}
+
+fn format_header_optional(paragraph: &str) -> Option<String> {
+    // Unlike parse_header_optional(), a header whose closing "=" count doesn't match the opening
+    // one is accepted here and the opening count wins, as in "===Name====" becoming "===Name===".
+    let text = paragraph.trim();
+    if !text.starts_with(DELIM_HEADER) || text.contains(DELIM_LINEFEED) {
+        return None;
+    }
+    let opening_count = text.chars().take_while(|c| DELIM_HEADER.starts_with(*c)).count();
+    let closing_count = text.chars().rev().take_while(|c| DELIM_HEADER.starts_with(*c)).count();
+    // Six "=" is the page title, which is left as it is.
+    if opening_count >= 6 || closing_count == 0 || opening_count + closing_count >= text.len() {
+        return None;
+    }
+    let name = text[opening_count..text.len() - closing_count].trim();
+    Some(headline_markup(name, 6 - opening_count))
+}
+
+fn format_list(list: &List) -> String {
+    let mut lines = vec![];
+    if let Some(header) = list.get_header() {
+        lines.push(header.get_unresolved_text().trim().to_string());
+    }
+    for list_item in list.get_items().iter() {
+        lines.push(list_item_markup(list_item.get_depth(), list_item.is_ordered(), list_item.get_text_block().get_unresolved_text().trim()));
+    }
+    lines.join(DELIM_LINEFEED)
+}
+
+fn format_table(table: &Table) -> String {
+    table.get_rows().iter().enumerate()
+        .map(|(row_index, cells)| {
+            let cells = cells.iter()
+                .map(|cell| cell.get_text_block().get_unresolved_text())
+                .collect::<Vec<_>>();
+            table_row_markup(table, row_index, &cells)
+        })
+        .join(DELIM_LINEFEED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "======Rust======   \n\n===Tools====\n\nSome text.  \n\n\n\nTools:\n  * Cargo\n    * Clippy\n\n^ Name ^ Use ^\n| Cargo | Build |\n\n<code>\n\n===Not a header====\n\n</code>\n\n<WRAP round box>\n\n==Also not a header=\n\n</WRAP>\n";
+
+    #[test]
+    fn format_is_idempotent() {
+        let formatted = format_page(PAGE);
+        assert_eq!(format_page(&formatted), formatted);
+        assert!(formatted.starts_with("======Rust======\n\n===Tools===\n\nSome text.\n\nTools:\n"));
+    }
+
+    #[test]
+    fn text_inside_markers_is_left_alone() {
+        let formatted = format_page(PAGE);
+        assert!(formatted.contains("<code>\n\n===Not a header====\n\n</code>"));
+        assert!(formatted.contains("<WRAP round box>\n\n==Also not a header=\n\n</WRAP>"));
+    }
+
+    #[test]
+    fn header_levels() {
+        assert_eq!(format_header_optional("==Name==="), Some("==Name==".to_string()));
+        assert_eq!(format_header_optional("=====Name====="), Some("=====Name=====".to_string()));
+        assert_eq!(format_header_optional("======Title======"), None);
+        assert_eq!(format_header_optional("=======Name======="), None);
+        assert_eq!(format_header_optional("======"), None);
+    }
+}
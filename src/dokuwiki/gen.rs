@@ -4,7 +4,7 @@ use std::fs;
 use crate::*;
 use std::hash::{Hasher, Hash};
 use super::*;
-use crate::model::TopicKey;
+use crate::model::{TopicKey, Table, HorizontalAlignment};
 
 #[allow(dead_code)]
 pub(crate) enum WikiImageSize {
@@ -168,6 +168,48 @@ pub(crate) fn add_headline(page_text: &mut String, text: &str, level: usize) {
     page_text.push_str(&format!("{}{}{}\n\n", markers, text, markers));
 }
 
+pub(crate) fn headline_markup(text: &str, level: usize) -> String {
+    // Like "=====Categories=====" where a level 1 (top) headline has five equal signs and the
+    // page title at level 0 has six.
+    debug_assert!(level <= 5);
+    let markers = DELIM_HEADER.repeat(6 - level);
+    format!("{}{}{}", markers, text, markers)
+}
+
+pub(crate) fn list_item_markup(depth: usize, is_ordered: bool, text: &str) -> String {
+    // Like "    * Rust" for an unordered item at depth 2.
+    let delimiter = if is_ordered { DELIM_LIST_ITEM_ORDERED } else { DELIM_LIST_ITEM_UNORDERED };
+    format!("{}{} {}", DELIM_LIST_ITEM_DEPTH.repeat(depth), delimiter, text)
+}
+
+pub(crate) fn table_row_markup(table: &Table, row_index: usize, cells: &Vec<String>) -> String {
+    // A table header row should look something like:
+    //   ^ Color ^ Blue ^
+    // A regular table row should look something like:
+    //   | Color | Blue |
+    // Extra spaces on one or both sides of the text set the alignment.
+    let last_delimiter = if table.has_header() && row_index == 0 { DELIM_TABLE_CELL_BOLD } else { DELIM_TABLE_CELL };
+    format!("{}{}", cells.iter().enumerate()
+        .map(|(col_index, cell_text)| {
+            let cell_info = table.get_cell(row_index, col_index);
+            let delimiter = if cell_info.is_bold() { DELIM_TABLE_CELL_BOLD } else { DELIM_TABLE_CELL };
+            match cell_info.get_horizontal() {
+                HorizontalAlignment::Center => {
+                    format!("{}  {}  ", delimiter, cell_text.trim())
+                },
+                HorizontalAlignment::Left => {
+                    format!("{} {} ", delimiter, cell_text.trim())
+                },
+                HorizontalAlignment::Right => {
+                    format!("{}  {} ", delimiter, cell_text.trim())
+                },
+            }
+        })
+        .join(""),
+        last_delimiter
+    )
+}
+
 /*
 #[allow(dead_code)]
 pub(crate) fn add_image_internal_link(page_text: &mut String, page_namespace: &str, page_name: &str, image_namespace: &str, image_file_name: &str, image_size: &WikiImageSize) {
@@ -293,6 +335,12 @@ pub(crate) fn page_link(namespace: &str, page_name: &str, label: Option<&str>) -
     format!("[[{}{}{}{}]]", namespace_prefix(namespace), legal_file_name(page_name), DELIM_LINK_LABEL, label.unwrap_or(page_name))
 }
 
+pub(crate) fn redirect_marker(namespace: &str, page_name: &str) -> String {
+    // Like "~~REDIRECT>tools:kubernetes~~". This has to be the first line of the page.
+    TopicKey::assert_legal_namespace(namespace);
+    format!("{}{}{}{}", MARKER_REDIRECT_START, namespace_prefix(namespace), legal_file_name(page_name), MARKER_REDIRECT_END)
+}
+
 pub(crate) fn page_link_from_string_label(namespace: &str, page_name: &str, label: &Option<String>) -> String {
     TopicKey::assert_legal_namespace(namespace);
     // format!("[[{}{}{}]]", namespace_prefix(namespace), legal_file_name(page_name), label.map_or("".to_string(), |x| format!("|{}", x)))
@@ -0,0 +1,174 @@
+use chrono::NaiveDate;
+
+// Atom and RSS 2.0 feeds of recently added or edited topics, so that someone following the wiki
+// can pick up changes in a feed reader rather than checking the Recent Topics page.
+
+#[derive(Debug)]
+pub(crate) struct FeedEntry {
+    title: String,
+    url: String,
+    date: NaiveDate,
+    summary: Option<String>,
+    category: Option<String>,
+}
+
+impl FeedEntry {
+    pub(crate) fn new(title: &str, url: &str, date: NaiveDate, summary: Option<String>, category: Option<String>) -> Self {
+        Self {
+            title: title.to_string(),
+            url: url.to_string(),
+            date,
+            summary,
+            category,
+        }
+    }
+}
+
+pub(crate) fn gen_atom(title: &str, author: &str, url_site: &str, url_feed: &str, entries: &Vec<FeedEntry>) -> String {
+    // The feed's updated date is that of the most recent entry, so regenerating the wiki without
+    // any topic changes produces the same file. An empty feed uses today's date. The entries
+    // don't have authors of their own, so the feed-level author covers them.
+    let mut xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <title>{}</title>\n", escape(title)));
+    xml.push_str(&format!("  <link href=\"{}\"/>\n", escape(url_site)));
+    xml.push_str(&format!("  <link rel=\"self\" href=\"{}\"/>\n", escape(url_feed)));
+    xml.push_str(&format!("  <id>{}</id>\n", escape(url_feed)));
+    let updated = entries.first().map_or_else(|| chrono::Local::today().naive_local(), |entry| entry.date);
+    xml.push_str(&format!("  <updated>{}</updated>\n", date_atom(&updated)));
+    xml.push_str(&format!("  <author>\n    <name>{}</name>\n  </author>\n", escape(author)));
+    for entry in entries.iter() {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape(&entry.title)));
+        xml.push_str(&format!("    <link href=\"{}\"/>\n", escape(&entry.url)));
+        xml.push_str(&format!("    <id>{}</id>\n", escape(&entry.url)));
+        xml.push_str(&format!("    <updated>{}</updated>\n", date_atom(&entry.date)));
+        if let Some(category) = &entry.category {
+            xml.push_str(&format!("    <category term=\"{}\"/>\n", escape(category)));
+        }
+        if let Some(summary) = &entry.summary {
+            xml.push_str(&format!("    <summary>{}</summary>\n", escape(summary)));
+        }
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+pub(crate) fn gen_rss(title: &str, url_site: &str, description: &str, entries: &Vec<FeedEntry>) -> String {
+    let mut xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+    xml.push_str("<rss version=\"2.0\">\n");
+    xml.push_str("  <channel>\n");
+    xml.push_str(&format!("    <title>{}</title>\n", escape(title)));
+    xml.push_str(&format!("    <link>{}</link>\n", escape(url_site)));
+    xml.push_str(&format!("    <description>{}</description>\n", escape(description)));
+    if let Some(entry) = entries.first() {
+        xml.push_str(&format!("    <lastBuildDate>{}</lastBuildDate>\n", date_rss(&entry.date)));
+    }
+    for entry in entries.iter() {
+        xml.push_str("    <item>\n");
+        xml.push_str(&format!("      <title>{}</title>\n", escape(&entry.title)));
+        xml.push_str(&format!("      <link>{}</link>\n", escape(&entry.url)));
+        xml.push_str(&format!("      <guid>{}</guid>\n", escape(&entry.url)));
+        xml.push_str(&format!("      <pubDate>{}</pubDate>\n", date_rss(&entry.date)));
+        if let Some(category) = &entry.category {
+            xml.push_str(&format!("      <category>{}</category>\n", escape(category)));
+        }
+        if let Some(summary) = &entry.summary {
+            xml.push_str(&format!("      <description>{}</description>\n", escape(summary)));
+        }
+        xml.push_str("    </item>\n");
+    }
+    xml.push_str("  </channel>\n");
+    xml.push_str("</rss>\n");
+    xml
+}
+
+pub(crate) fn feed_summary(text: &str, max_length: usize) -> Option<String> {
+    // Take the display text of a paragraph, drop the DokuWiki formatting, and cut it off at a
+    // word boundary. The "//" in a URL is not italics so it's protected while the rest of the
+    // markup is removed.
+    let temp_url = "~temp url~";
+    let summary = text.replace("://", temp_url);
+    let summary = summary.replace(super::DELIM_BOLD, "")
+        .replace(super::DELIM_ITALIC, "")
+        .replace(super::DELIM_MONOSPACE, "")
+        .replace(super::DELIM_NO_FORMAT, "")
+        .replace(super::DELIM_LINE_BREAK, " ");
+    let summary = summary.replace(temp_url, "://");
+    let summary = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+    if summary.is_empty() {
+        return None;
+    }
+    if summary.chars().count() <= max_length {
+        return Some(summary);
+    }
+    let mut short = String::new();
+    for word in summary.split(' ') {
+        if short.chars().count() + word.chars().count() + 1 > max_length {
+            break;
+        }
+        if !short.is_empty() {
+            short.push(' ');
+        }
+        short.push_str(word);
+    }
+    Some(format!("{}...", short))
+}
+
+fn date_atom(date: &NaiveDate) -> String {
+    // Like "2021-04-07T00:00:00Z".
+    format!("{}T00:00:00Z", date.format("%Y-%m-%d"))
+}
+
+fn date_rss(date: &NaiveDate) -> String {
+    // RFC 822, like "Wed, 07 Apr 2021 00:00:00 GMT".
+    format!("{} 00:00:00 GMT", date.format("%a, %d %b %Y"))
+}
+
+fn escape(text: &str) -> String {
+    text.replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+        .replace("\"", "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<FeedEntry> {
+        vec![
+            FeedEntry::new("Rust & Cargo", "http://localhost/doku.php?id=tools:rust", NaiveDate::from_ymd(2021, 4, 7), Some("A language.".to_string()), Some("Languages".to_string())),
+            FeedEntry::new("Git", "http://localhost/doku.php?id=tools:git", NaiveDate::from_ymd(2021, 3, 1), None, None),
+        ]
+    }
+
+    #[test]
+    fn atom_feed_has_author_updated_and_entries() {
+        let xml = gen_atom("Tools", "Tools", "http://localhost/site", "http://localhost/feed.xml", &entries());
+        assert!(xml.contains("  <updated>2021-04-07T00:00:00Z</updated>\n  <author>\n    <name>Tools</name>\n  </author>\n"));
+        assert!(xml.contains("    <title>Rust &amp; Cargo</title>\n"));
+        assert!(xml.contains("    <category term=\"Languages\"/>\n    <summary>A language.</summary>\n"));
+        assert!(xml.contains("    <updated>2021-03-01T00:00:00Z</updated>\n"));
+        assert_eq!(xml.matches("<entry>").count(), 2);
+        assert!(xml.ends_with("</feed>\n"));
+    }
+
+    #[test]
+    fn empty_atom_feed_still_has_author_and_updated() {
+        let xml = gen_atom("Tools", "Tools", "http://localhost/site", "http://localhost/feed.xml", &vec![]);
+        assert_eq!(xml.matches("<updated>").count(), 1);
+        assert!(xml.contains("<author>"));
+        assert!(!xml.contains("<entry>"));
+    }
+
+    #[test]
+    fn rss_feed_has_items() {
+        let xml = gen_rss("Tools", "http://localhost/site", "Recent topics.", &entries());
+        assert!(xml.contains("    <lastBuildDate>Wed, 07 Apr 2021 00:00:00 GMT</lastBuildDate>\n"));
+        assert!(xml.contains("      <pubDate>Mon, 01 Mar 2021 00:00:00 GMT</pubDate>\n"));
+        assert!(xml.contains("      <category>Languages</category>\n      <description>A language.</description>\n"));
+        assert_eq!(xml.matches("<item>").count(), 2);
+    }
+}
@@ -5,10 +5,14 @@ use std::rc::Rc;
 use std::cell::{RefCell, Ref};
 use crate::model::{AttributeValueType, TopicKey, Topic, TableCell, LinkRc, links_to_topic_keys, ATTRIBUTE_NAME_EDITED, ATTRIBUTE_NAME_ADDED, TopicTreeNode, ATTRIBUTE_VALUE_UNKNOWN, ATTRIBUTE_NAME_VISIBILITY, Model};
 use std::collections::BTreeMap;
-use crate::dokuwiki::{PAGE_NAME_ATTR_VALUE, WikiAttributeTable, PAGE_NAME_ATTR_DATE, PAGE_NAME_ATTR_YEAR, DELIM_TABLE_CELL_BOLD, DELIM_TABLE_CELL, WikiGenPage, HEADLINE_LINKS, RECENT_TOPICS_THRESHOLD, legal_file_name, image_ref_from_file_name, PAGE_NAME_TERMS, PAGE_NAME_CLOUD_TERMS, PAGE_NAME_PROFISEE_TERMS, PAGE_NAME_SOC_SVC_TERMS};
+use crate::dokuwiki::{DELIM_MONOSPACE, DELIM_NO_FORMAT, PAGE_NAME_ATTR_VALUE, WikiAttributeTable, PAGE_NAME_ATTR_DATE, PAGE_NAME_ATTR_YEAR, WikiGenPage, HEADLINE_LINKS, RECENT_TOPICS_THRESHOLD, legal_file_name, image_ref_from_file_name, PAGE_NAME_TERMS, PAGE_NAME_CLOUD_TERMS, PAGE_NAME_PROFISEE_TERMS, PAGE_NAME_SOC_SVC_TERMS};
 use crate::tree::TreeNode;
 use crate::dokuwiki::to_model::{make_topic_file_key, TopicFile};
 use crate::model::glossary::Glossary;
+use crate::model::lint::LintResult;
+use crate::model::redaction::RedactionHit;
+use crate::dokuwiki::media::MediaReport;
+use crate::model::date::{AddedDateEstimate, AddedDateSource};
 
 //const SUBCATEGORY_TREE_MAX_SIZE: usize = 30;
 
@@ -66,6 +70,51 @@ impl <'a> GenFromModel<'a> {
         page.write(&self.path_pages);
     }
 
+    pub(crate) fn gen_recent_topics_feed(&self, project_name: &str, path: &str, topic_count: usize, include_rss: bool) {
+        // Same dates as the Recent Topics page, but with a summary of each topic. If the model is
+        // filtered to an audience, topics the audience can't see never make it into the feed even
+        // if they weren't removed from the model.
+        let mut topics = self.model.get_topics().values()
+            .filter(|topic| self.model.topic_is_in_audience(topic))
+            .filter_map(|topic| {
+                topic.get_attribute_date(ATTRIBUTE_NAME_EDITED).or(topic.get_attribute_date(ATTRIBUTE_NAME_ADDED))
+                    .map(|date| (date, topic))
+            })
+            .collect::<Vec<_>>();
+        // Most recent first, then alphabetical within a date.
+        topics.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.get_name().to_lowercase().cmp(&b.1.get_name().to_lowercase())));
+        let entries = topics.iter()
+            .take(topic_count)
+            .map(|(date, topic)| {
+                let url = self.page_url(&topic.get_topic_key());
+                wiki::FeedEntry::new(topic.get_name(), &url, *date, Self::topic_summary(topic), topic.get_category())
+            })
+            .collect::<Vec<_>>();
+        let title = format!("{}: {}", project_name, wiki::PAGE_NAME_RECENT_TOPICS);
+        let url_site = format!("{}{}:{}", wiki::URL_WIKI_PAGE_PREFIX, self.model.namespace_navigation(), legal_file_name(wiki::PAGE_NAME_RECENT_TOPICS));
+        let url_feed = format!("{}{}:{}", wiki::URL_WIKI_MEDIA_PREFIX, self.model.get_main_namespace(), wiki::FILE_NAME_FEED_ATOM);
+        util::file::path_create_if_necessary_r(path.to_string()).unwrap();
+        std::fs::write(format!("{}/{}", path, wiki::FILE_NAME_FEED_ATOM), wiki::gen_atom(&title, project_name, &url_site, &url_feed, &entries)).unwrap();
+        if include_rss {
+            let description = format!("The {} most recently added or edited topics.", entries.len());
+            std::fs::write(format!("{}/{}", path, wiki::FILE_NAME_FEED_RSS), wiki::gen_rss(&title, &url_site, &description, &entries)).unwrap();
+        }
+    }
+
+    fn topic_summary(topic: &Topic) -> Option<String> {
+        // The first text paragraph that has something left after the markup is removed.
+        topic.get_paragraphs().iter()
+            .filter_map(|paragraph| match paragraph {
+                model::Paragraph::Text { text_block } => wiki::feed_summary(&text_block.get_display_text(), wiki::FEED_SUMMARY_LENGTH),
+                _ => None,
+            })
+            .next()
+    }
+
+    fn page_url(&self, topic_key: &model::TopicKey) -> String {
+        format!("{}{}:{}", wiki::URL_WIKI_PAGE_PREFIX, self.model.qualify_namespace(topic_key.get_namespace()), legal_file_name(topic_key.get_topic_name()))
+    }
+
     pub(crate) fn gen_all_topics_page(&mut self) {
         let namespace = &self.model.qualify_namespace(&self.model.namespace_navigation());
         let mut page = wiki::WikiGenPage::new(namespace, wiki::PAGE_NAME_ALL_TOPICS,None);
@@ -78,8 +127,12 @@ impl <'a> GenFromModel<'a> {
             page.add_headline(section_name, 1);
             self.gen_topic_first_letter_links(&mut page, 9);
             for topic_key in topic_keys {
-                let link = Self::page_link(topic_key);
-                page.add_line_with_break(&link);
+                // An alias points to the real topic, like "K8s, see Kubernetes".
+                let line = match self.model.get_aliases().get(topic_key) {
+                    Some(real_topic_key) => format!("{}, see {}", topic_key.get_topic_name(), Self::page_link(real_topic_key)),
+                    None => Self::page_link(topic_key),
+                };
+                page.add_line_with_break(&line);
             }
         }
         page.write(&self.path_pages);
@@ -237,12 +290,121 @@ impl <'a> GenFromModel<'a> {
     #[allow(dead_code)]
     pub(crate) fn gen_reports_page(&self) {
         let mut page = wiki::WikiGenPage::new(&self.model.namespace_navigation(), wiki::PAGE_NAME_REPORTS,None);
-        if !self.model.filter_is_public() {
+        if self.model.filter_audience().is_none() {
             // self.gen_reports_page_public_topics_by_category(&mut page);
             // self.gen_reports_page_public_ref_to_private(&mut page);
-            // self.gen_reports_page_redactions(&mut page);
             self.gen_report_privacy_unknown(&mut page);
         }
+        // Maintenance reports, which don't belong in a published build.
+        if !self.model.is_filtered() {
+            self.gen_report_broken_section_links(&mut page);
+            self.gen_report_unlinked_mentions(&mut page);
+        }
+        page.write(&self.path_pages);
+    }
+
+    pub(crate) fn gen_lint_page(&self, results: &Vec<LintResult>) {
+        // One section per severity, most severe first, listing the topics and what was found in
+        // each. The same results are written as JSON by write_lint_results().
+        let mut page = wiki::WikiGenPage::new(&self.model.namespace_navigation(), wiki::PAGE_NAME_LINT, None);
+        let mut map = BTreeMap::new();
+        for result in results.iter() {
+            let entry = map.entry(result.get_severity()).or_insert(BTreeMap::new())
+                .entry(result.get_topic_key().clone()).or_insert(vec![]);
+            entry.push(result);
+        }
+        for (severity, topic_map) in map.iter().rev() {
+            let count = topic_map.values().map(|results| results.len()).sum::<usize>();
+            let severity_name = severity.to_string();
+            let headline = format!("{}{} ({})", severity_name[..1].to_uppercase(), &severity_name[1..], util::format::format_count(count));
+            page.add_headline(&headline, 1);
+            for (topic_key, results) in topic_map.iter() {
+                let link = self.page_link_qualified(topic_key);
+                page.add_list_item_unordered(1, &link);
+                for result in results.iter() {
+                    page.add_list_item_unordered(2, &format!("{}{}{} ({}{}{})", DELIM_NO_FORMAT, result.get_message(), DELIM_NO_FORMAT, DELIM_MONOSPACE, result.get_lint_name(), DELIM_MONOSPACE));
+                }
+            }
+            page.add_linefeed();
+        }
+        if map.is_empty() {
+            page.add_paragraph("No problems found.");
+        }
+        page.write(&self.path_pages);
+    }
+
+    pub(crate) fn gen_redactions_page(&self, hits: &Vec<RedactionHit>) {
+        // Everything a public build would redact, by topic, with the rule behind each one. The
+        // model comes from a filtered build but this page is meant for the private wiki, since it
+        // shows exactly what's being hidden. The same hits are written as JSON by
+        // write_redaction_results().
+        let mut page = wiki::WikiGenPage::new(&self.model.namespace_navigation(), wiki::PAGE_NAME_REDACTIONS, None);
+        let mut map = BTreeMap::new();
+        for hit in hits.iter() {
+            let entry = map.entry(hit.get_topic_key().clone()).or_insert(vec![]);
+            entry.push(hit);
+        }
+        let no_format = |text: &str| if text.is_empty() { "".to_string() } else { format!("{}{}{}", DELIM_NO_FORMAT, text, DELIM_NO_FORMAT) };
+        page.add_paragraph(&format!("{} redactions in {} topics.", util::format::format_count(hits.len()), util::format::format_count(map.len())));
+        for (topic_key, hits) in map.iter() {
+            let link = self.page_link_qualified(topic_key);
+            page.add_list_item_unordered(1, &link);
+            for hit in hits.iter() {
+                let line = format!("Line {}: {}{}{}{}{} ({}{}{}, {})",
+                    hit.get_line_number(), no_format(hit.get_before()), wiki::DELIM_BOLD, no_format(hit.get_matched_text()), wiki::DELIM_BOLD, no_format(hit.get_after()),
+                    DELIM_MONOSPACE, hit.get_rule_description(), DELIM_MONOSPACE, no_format(&hit.get_source().description()));
+                page.add_list_item_unordered(2, &line);
+            }
+        }
+        if !map.is_empty() {
+            page.add_linefeed();
+        }
+        page.write(&self.path_pages);
+    }
+
+    pub(crate) fn gen_media_page(&self, report: &MediaReport) {
+        // Files in the media folder that no topic links to, links to files that aren't there, and
+        // files with the same content under different names.
+        let mut page = wiki::WikiGenPage::new(&self.model.namespace_navigation(), wiki::PAGE_NAME_MEDIA, None);
+        let media_link = |media_id: &str| wiki::gen::image_part(media_id, &wiki::gen::WikiImageLinkType::LinkOnly, &wiki::gen::WikiImageSize::Original);
+        page.add_headline(&format!("Unreferenced ({})", util::format::format_count(report.get_unreferenced().len())), 1);
+        for media_id in report.get_unreferenced().iter() {
+            page.add_list_item_unordered(1, &media_link(media_id));
+        }
+        page.add_linefeed();
+        page.add_headline(&format!("Missing ({})", util::format::format_count(report.get_missing().len())), 1);
+        for (media_id, topic_keys) in report.get_missing().iter() {
+            page.add_list_item_unordered(1, &format!("{}{}{}", DELIM_MONOSPACE, media_id, DELIM_MONOSPACE));
+            for topic_key in topic_keys.iter() {
+                page.add_list_item_unordered(2, &self.page_link_qualified(topic_key));
+            }
+        }
+        page.add_linefeed();
+        page.add_headline(&format!("Duplicates ({})", util::format::format_count(report.get_duplicates().len())), 1);
+        for media_ids in report.get_duplicates().iter() {
+            page.add_list_item_unordered(1, &media_ids.iter().map(|media_id| media_link(media_id)).join(", "));
+        }
+        page.add_linefeed();
+        page.write(&self.path_pages);
+    }
+
+    pub(crate) fn gen_added_dates_page(&self, estimates: &Vec<AddedDateEstimate>) {
+        // The Added dates that would be filled in for topics that don't have one, with where each
+        // date came from. Dates taken from links in later rounds are built on earlier estimates,
+        // so they're the ones most worth checking.
+        let mut page = wiki::WikiGenPage::new(&self.model.namespace_navigation(), wiki::PAGE_NAME_ADDED_DATES, None);
+        page.add_paragraph(&format!("{} estimated Added dates.", util::format::format_count(estimates.len())));
+        for estimate in estimates.iter() {
+            let source = match estimate.get_source() {
+                AddedDateSource::OtherDate { attr_type_name } => format!("from {}", attr_type_name),
+                AddedDateSource::InboundLink { topic_key } => format!("linked from {}, round {}", self.page_link_qualified(topic_key), estimate.get_round()),
+            };
+            let line = format!("{}: {} ({})", self.page_link_qualified(estimate.get_topic_key()), model::AttributeType::date_to_display_string(&estimate.get_date()), source);
+            page.add_list_item_unordered(1, &line);
+        }
+        if !estimates.is_empty() {
+            page.add_linefeed();
+        }
         page.write(&self.path_pages);
     }
 
@@ -269,12 +431,12 @@ impl <'a> GenFromModel<'a> {
 
         page.add_headline("Acronyms and Abbreviations", 1);
 
-        let table = base_glossary.make_table(false, true, false, &included_tags, &excluded_tags, self.model.filter_is_public());
+        let table = base_glossary.make_table(false, true, false, &included_tags, &excluded_tags, self.model.filter_audience().is_some());
         self.add_table(&mut page, &table);
 
         page.add_headline("Terms", 1);
 
-        let table = base_glossary.make_table(true, false, false, &included_tags, &excluded_tags, self.model.filter_is_public());
+        let table = base_glossary.make_table(true, false, false, &included_tags, &excluded_tags, self.model.filter_audience().is_some());
         self.add_table(&mut page, &table);
 
         page.write(&self.path_pages);
@@ -321,14 +483,6 @@ impl <'a> GenFromModel<'a> {
         }
     }
 
-    #[allow(dead_code)]
-    fn gen_reports_page_redactions(&self, _page: &mut WikiGenPage) {
-        // While redactions should be used only for public renders, the report of redactions is
-        // private. So in this case we did the redactions just to get the report.
-        assert!(!self.model.is_filtered());
-        //let redaction_record = self.model.get_redaction_record().unwrap();
-    }
-
     #[allow(dead_code)]
     fn gen_report_privacy_unknown(&self, page: &mut WikiGenPage) {
         page.add_headline("Privacy Unknown", 1);
@@ -344,13 +498,64 @@ impl <'a> GenFromModel<'a> {
         }
     }
 
+    fn gen_report_broken_section_links(&self, page: &mut WikiGenPage) {
+        page.add_headline("Broken Section Links", 1);
+        let mut map = BTreeMap::new();
+        for (topic_key, section_key, suggestion) in self.model.find_broken_section_links().into_iter() {
+            let entry = map.entry(topic_key).or_insert(vec![]);
+            entry.push((section_key, suggestion));
+        }
+        for (topic_key, broken_links) in map.iter() {
+            let link = self.page_link_qualified(topic_key);
+            page.add_list_item_unordered(1, &link);
+            for (section_key, suggestion) in broken_links.iter() {
+                let line = match suggestion {
+                    Some(section_name) => {
+                        let link = wiki::section_link(&self.model.qualify_namespace(section_key.get_namespace()), section_key.get_topic_name(), section_name, None);
+                        format!("{}{}{}: did you mean {}?", DELIM_NO_FORMAT, section_key.get_display_text(), DELIM_NO_FORMAT, link)
+                    },
+                    None => format!("{}{}{}: no similar section found.", DELIM_NO_FORMAT, section_key.get_display_text(), DELIM_NO_FORMAT),
+                };
+                page.add_list_item_unordered(2, &line);
+            }
+        }
+        if !map.is_empty() {
+            page.add_linefeed();
+        }
+    }
+
+    fn gen_report_unlinked_mentions(&self, page: &mut WikiGenPage) {
+        // Topics whose text names another topic without linking to it. These can be turned into
+        // links with dokuwiki_link_unlinked_mentions().
+        page.add_headline("Unlinked Mentions", 1);
+        let mut map = BTreeMap::new();
+        for mention in self.model.find_unlinked_mentions().into_iter() {
+            let entry = map.entry(mention.get_topic_key().clone()).or_insert(vec![]);
+            entry.push(mention);
+        }
+        let mut count = 0;
+        for (topic_key, mentions) in map.iter() {
+            let link = self.page_link_qualified(topic_key);
+            page.add_list_item_unordered(1, &link);
+            for mention in mentions.iter() {
+                let link = self.page_link_qualified(mention.get_mentioned_topic_key());
+                let times = if mention.get_count() == 1 { "once".to_string() } else { format!("{} times", mention.get_count()) };
+                page.add_list_item_unordered(2, &format!("{} as \"{}\", {}", link, mention.get_first_text(), times));
+            }
+            count += 1;
+        }
+        if count > 0 {
+            page.add_linefeed();
+        }
+    }
+
     #[allow(dead_code)]
     fn add_public_topics_by_category(&self, map: &mut BTreeMap<String, Vec<TopicKey>>, node_rc: &Rc<RefCell<TopicTreeNode>>, category_label: String) {
         let node: Ref<TreeNode<TopicKey>> = RefCell::borrow(node_rc);
         let category_name = node.item.get_topic_name();
         let category_label = if category_label.is_empty() { category_name.to_string() } else { format!("{} - {}", category_label, category_name) };
         for topic in self.model.get_topics().values()
-                .filter(|topic| topic.is_public() && topic.get_category().map_or(false, |cat| cat.eq(category_name))) {
+                .filter(|topic| topic.is_public() && topic.has_category(category_name)) {
             let entry = map.entry(category_label.clone()).or_insert(vec![]);
             entry.push(topic.get_topic_key());
         }
@@ -468,6 +673,24 @@ impl <'a> GenFromModel<'a> {
             // page.write_if_changed(&self.path_pages, self.model.get_original_pages());
             //f debug { dbg!(&page); panic!(); }
         }
+        self.current_topic_key = None;
+        for (topic_key_from, topic_key_to) in self.model.get_redirects().iter() {
+            // A stub page left behind by a rename or merge. The redirect plugin reads the marker
+            // on the first line, and the sentence below it is for anyone viewing the page without
+            // the plugin.
+            if !self.model.has_topic(topic_key_to) {
+                continue;
+            }
+            let mut page = wiki::WikiGenPage::new(&self.model.qualify_namespace(topic_key_from.get_namespace()), topic_key_from.get_topic_name(), None);
+            page.add_paragraph(&format!("This topic has moved to {}.", Self::page_link(topic_key_to)));
+            let redirect_marker = wiki::redirect_marker(&self.model.qualify_namespace(topic_key_to.get_namespace()), topic_key_to.get_topic_name());
+            page.content.insert_str(0, &format!("{}\n\n", redirect_marker));
+            page.fix_content_before_write();
+            let topic_file_name = legal_file_name(topic_key_from.get_topic_name());
+            let topic_file_key = make_topic_file_key(topic_key_from.get_namespace(), &topic_file_name);
+            let topic_file = TopicFile::new(topic_key_from.get_namespace(), &topic_file_name, topic_key_from.get_topic_name(), page.content);
+            map.insert(topic_file_key, topic_file);
+        }
         self.errors.print(Some("GenFromModel::gen()"));
         map
     }
@@ -533,8 +756,8 @@ impl <'a> GenFromModel<'a> {
     }
 
     fn add_category_optional(&mut self, page: &mut wiki::WikiGenPage, topic: &model::Topic) {
-        if let Some(category) = topic.get_category() {
-            page.add_category(&self.model.get_main_namespace(),&category);
+        if !topic.get_categories().is_empty() {
+            page.add_category(&self.model.get_main_namespace(),topic.get_categories());
         }
         // if page.topic_name.contains("10,000") { //bg!(&page.content); }
     }
@@ -706,7 +929,12 @@ impl <'a> GenFromModel<'a> {
      */
 
     fn add_subcategory_tree(&self, page: &mut wiki::WikiGenPage, topic: &model::Topic) {
-        let node_rc = topic.get_category_tree_node().as_ref().unwrap();
+        // A category that only has topics through their secondary categories may not be in the
+        // category tree at all.
+        let node_rc = match topic.get_category_tree_node() {
+            Some(node_rc) => node_rc,
+            None => return,
+        };
         let node = b!(&node_rc);
         if node.height() > 2 {
             // let filter_func = |node: Ref<TopicTreeNode>| node.height() > 1;
@@ -739,12 +967,15 @@ impl <'a> GenFromModel<'a> {
             for node_rc in nodes.iter() {
                 // for node_rc in nodes.iter().sorted_by_key(|node| b!(node).item.get_topic_name().to_lowercase()) {
                 let node = b!(node_rc);
-                let use_this_node = if is_category { !node.is_leaf() } else { true };
+                // A category whose topics are all there through secondary categories is a leaf in
+                // the tree, so ask the topic rather than the node.
+                let category_topic = if is_category { self.model.get_topics().get(&node.item).filter(|topic| topic.is_category()) } else { None };
+                let use_this_node = if is_category { category_topic.is_some() } else { true };
                 if use_this_node {
                     let depth = (node.depth() - base_depth) + 1;
                     let link = self.page_link_simple(&node.item);
-                    let topic_count_label = if is_category {
-                        let topic_count = node.subtree_leaf_count();
+                    let topic_count_label = if let Some(category_topic) = category_topic {
+                        let topic_count = category_topic.indirect_topics_in_category().len();
                         format!(" ({})", util::format::format_count(topic_count))
                     } else {
                         "".to_string()
@@ -785,7 +1016,7 @@ impl <'a> GenFromModel<'a> {
 
     fn add_glossary(&mut self, page: &mut wiki::WikiGenPage, glossaries: &BTreeMap<String, Glossary>, glossary_name: &str) {
         let glossary = glossaries.get(glossary_name).unwrap();
-        let table = glossary.make_table(true, true, true, &None, &None, self.model.filter_is_public());
+        let table = glossary.make_table(true, true, true, &None, &None, self.model.filter_audience().is_some());
         self.add_table(page, &table);
     }
 
@@ -822,31 +1053,7 @@ impl <'a> GenFromModel<'a> {
     }
 
     pub(crate) fn add_table_row(&mut self, page: &mut wiki::WikiGenPage, table: &model::Table, row_index: usize, cells: &Vec<String>) {
-        // A table header row should look something like:
-        //   ^ Color ^ Blue ^
-        // A regular table row should look something like:
-        //   | Color | Blue |
-        let last_delimiter = if table.has_header() && row_index == 0 { DELIM_TABLE_CELL_BOLD } else { DELIM_TABLE_CELL };
-        let markup = format!("{}{}\n", cells.iter().enumerate()
-            .map(|(col_index, cell_text)| {
-                let cell_info = table.get_cell(row_index, col_index);
-                let delimiter = if cell_info.is_bold() { DELIM_TABLE_CELL_BOLD } else { DELIM_TABLE_CELL };
-                match cell_info.get_horizontal() {
-                    model::HorizontalAlignment::Center => {
-                        format!("{}  {}  ", delimiter, cell_text.trim())
-                    },
-                    model::HorizontalAlignment::Left => {
-                        format!("{} {} ", delimiter, cell_text.trim())
-                    },
-                    model::HorizontalAlignment::Right => {
-                        format!("{}  {} ", delimiter, cell_text.trim())
-                    },
-                }
-            })
-            .join(""),
-                             last_delimiter
-        );
-        page.add_text(&markup);
+        page.add_line(&wiki::table_row_markup(table, row_index, cells));
     }
 
     fn link_to_markup(&mut self, link: &model::LinkRc) -> String {
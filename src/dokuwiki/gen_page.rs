@@ -26,16 +26,22 @@ impl WikiGenPage {
     }
 
     pub(crate) fn add_headline(&mut self, text: &str, level: usize) {
-        // Like "----- Categories -----" where a level 1 (top) headline has five hyphens.
-        debug_assert!(level <= 5);
-        let equal_signs = "=".repeat(6 - level);
-        self.content.push_str(&format!("{}{}{}\n\n", equal_signs, text, equal_signs));
+        self.content.push_str(&format!("{}\n\n", headline_markup(text, level)));
     }
 
-    pub(crate) fn add_category(&mut self, qualified_namespace: &str, category_name: &str) {
-        // Like "Category: [[APIs]]".
+    pub(crate) fn add_category(&mut self, qualified_namespace: &str, category_names: &Vec<String>) {
+        // Like "Category: [[APIs]]", or if the topic is in more than one category, like
+        // "Category: [[Rust Crates]] (primary), [[Serialization]]" with the primary one first.
         TopicKey::assert_legal_namespace(qualified_namespace);
-        self.content.push_str(&format!("{}{}\n\n", PREFIX_CATEGORY, page_link(qualified_namespace, category_name, None)));
+        debug_assert!(!category_names.is_empty());
+        let links = category_names.iter()
+            .enumerate()
+            .map(|(index, category_name)| {
+                let link = page_link(qualified_namespace, category_name, None);
+                if index == 0 && category_names.len() > 1 { format!("{} {}", link, MARKER_PRIMARY_CATEGORY) } else { link }
+            })
+            .join(", ");
+        self.content.push_str(&format!("{}{}\n\n", PREFIX_CATEGORY, links));
     }
 
     /*
@@ -130,9 +136,7 @@ impl WikiGenPage {
 
     pub(crate) fn add_list_item(&mut self, depth: usize, is_ordered: bool, text: &str) {
         //bg!(depth, is_ordered, &text);
-        let delimiter = if is_ordered { DELIM_LIST_ITEM_ORDERED } else { DELIM_LIST_ITEM_UNORDERED };
-        let prefix = format!("{}{}", DELIM_LIST_ITEM_DEPTH.repeat(depth), delimiter);
-        self.content.push_str(&format!("{} {}\n", prefix, text));
+        self.content.push_str(&format!("{}\n", list_item_markup(depth, is_ordered, text)));
     }
 
     pub(crate) fn add_paragraph(&mut self, text: &str) {
@@ -1,27 +1,257 @@
 use crate::dokuwiki as wiki;
 use crate::model;
-use crate::model::{FOLDER_PREFIX_WIKI_GEN_BACKUP, FOLDER_WIKI_GEN_BACKUP, FOLDER_WIKI_COMPARE_OLD, FOLDER_WIKI_COMPARE_NEW};
+use crate::model::DateSource;
+use crate::model::{FOLDER_PREFIX_WIKI_GEN_BACKUP, FOLDER_WIKI_GEN_BACKUP, FOLDER_WIKI_COMPARE_OLD, FOLDER_WIKI_COMPARE_NEW, FILE_NAME_LINT_RESULTS, FILE_NAME_REDACTION_RESULTS};
+use crate::model::lint::{LintConfig, LintResult, LintSeverity, lint_results_to_json};
+use crate::model::redaction::redaction_hits_to_json;
+use crate::dokuwiki::leak_scan::LeakScan;
+use crate::dokuwiki::acl::{AclPlan, read_acl_file};
+use crate::dokuwiki::media::{MediaReport, copy_referenced_media};
 use crate::dokuwiki::gen_from_model::GenFromModel;
-use crate::dokuwiki::{PATH_MEDIA, PATH_PAGES, FILE_MONITOR_PROJECT_NAME_DOKUWIKI, FILE_MONITOR_SCAN_MINUTES};
+use crate::dokuwiki::{PATH_MEDIA, PATH_PAGES, FILE_MONITOR_PROJECT_NAME_DOKUWIKI, FILE_MONITOR_SCAN_MINUTES, FILE_NAME_ACL, FILE_NAME_ACL_GEN};
 use file_monitor::model::Marker as FileMonitorMarker;
 use crate::dokuwiki::to_model::BuildProcess;
 
 pub(crate) const PROJECT_NAME: &str = "Tools";
 
-pub fn dokuwiki_round_trip(mut compare_only: bool, filter_is_public: bool, filter_main_topic_ref: Option<String>) {
+pub fn dokuwiki_round_trip(mut compare_only: bool, filter_audience: Option<String>, filter_main_topic_ref: Option<String>, date_source: DateSource) {
+    // A filtered build is either for one audience, such as "Public" or "Team", meaning the topics
+    // whose Visibility includes that audience, or for the topics under one root topic.
     println!("\nDokuWiki round trip test: Start.");
 
-    if filter_is_public || filter_main_topic_ref.is_some() {
+    if filter_audience.is_some() || filter_main_topic_ref.is_some() {
         compare_only = true;
     }
 
-    let (model, build_process) = prep_round_trip(compare_only, filter_is_public, filter_main_topic_ref);
+    let (model, build_process) = prep_round_trip(compare_only, filter_audience, filter_main_topic_ref, date_source);
     complete_round_trip(model, build_process);
 
     println!("\nDokuWiki round trip test: Done.");
 }
 
-pub(crate) fn prep_round_trip(compare_only: bool, filter_is_public: bool, filter_main_topic_ref: Option<String>) -> (model::Model, BuildProcess) {
+pub fn dokuwiki_round_trip_with_markdown_import(compare_only: bool, path_markdown: &str) {
+    // Merge a folder of Markdown notes into the wiki, then regenerate the DokuWiki pages so the
+    // notes become regular topics.
+    println!("\nDokuWiki round trip with Markdown import: Start.");
+
+    let (mut model, build_process) = prep_round_trip(compare_only, None, None, DateSource::FileMonitor);
+    crate::markdown::to_model::import_folder(&mut model, path_markdown);
+    complete_round_trip(model, build_process);
+
+    println!("\nDokuWiki round trip with Markdown import: Done.");
+}
+
+pub fn dokuwiki_rename_topic(compare_only: bool, topic_name_old: &str, topic_name_new: &str, leave_redirect: bool) {
+    // Rename a topic in the main namespace, rewriting every link to it, then regenerate the wiki.
+    // The old name can be given as the title like "Functional Programming" or in its link form.
+    println!("\nDokuWiki rename topic: Start.");
+
+    let (mut model, mut build_process) = prep_round_trip(compare_only, None, None, DateSource::FileMonitor);
+    let topic_key_old = model::Model::get_corrected_topic_key(model.get_topic_refs(), model.get_main_namespace(), topic_name_old).unwrap();
+    let topic_key_new = model.rename_topic(&topic_key_old, topic_name_new).unwrap();
+
+    // The file monitor tracks files by path, so the moved file starts a new history there and the
+    // next build would take its Added date from the day of the rename. Carry the dates recorded
+    // for the old file over to the renamed topic's attribute table, where they take precedence.
+    model.carry_over_attributes_from_file_monitor(&topic_key_old, &topic_key_new);
+
+    // A redirect page under the old name keeps bookmarks and links from outside the wiki working.
+    if leave_redirect {
+        model.add_redirect(&topic_key_old, &topic_key_new).unwrap();
+    }
+
+    build_process.rename_topic_file(&topic_key_old, &topic_key_new);
+    complete_round_trip(model, build_process);
+
+    println!("\nDokuWiki rename topic: Done.");
+}
+
+pub fn dokuwiki_merge_topics(compare_only: bool, topic_name_keep: &str, topic_name_merge: &str, leave_redirect: bool) {
+    // Merge the second topic into the first, like "rust-analyzer" into "Rust Analyzer", then
+    // regenerate the wiki. The merged topic's page goes away.
+    println!("\nDokuWiki merge topics: Start.");
+
+    let (mut model, mut build_process) = prep_round_trip(compare_only, None, None, DateSource::FileMonitor);
+    let topic_key_keep = model::Model::get_corrected_topic_key(model.get_topic_refs(), model.get_main_namespace(), topic_name_keep).unwrap();
+    let topic_key_merge = model::Model::get_corrected_topic_key(model.get_topic_refs(), model.get_main_namespace(), topic_name_merge).unwrap();
+    let conflicts = model.merge_topics(&topic_key_keep, &topic_key_merge).unwrap();
+    conflicts.print_without_panic(Some("Conflicts to resolve by hand"));
+    if leave_redirect {
+        model.add_redirect(&topic_key_merge, &topic_key_keep).unwrap();
+    }
+
+    build_process.remove_topic_file(&topic_key_merge);
+    complete_round_trip(model, build_process);
+
+    println!("\nDokuWiki merge topics: Done.");
+}
+
+pub fn dokuwiki_split_topic(compare_only: bool, topic_name: &str, section_names: &[&str]) {
+    // Move the given sections of a topic into new subtopics, then regenerate the wiki. The
+    // original page lists the new topics in its Subtopics section.
+    println!("\nDokuWiki split topic: Start.");
+
+    let (mut model, build_process) = prep_round_trip(compare_only, None, None, DateSource::FileMonitor);
+    let topic_key = model::Model::get_corrected_topic_key(model.get_topic_refs(), model.get_main_namespace(), topic_name).unwrap();
+    let topic_keys_new = model.split_topic(&topic_key, &section_names.to_vec()).unwrap();
+    for topic_key_new in topic_keys_new.iter() {
+        println!("\t{}", topic_key_new);
+    }
+    complete_round_trip(model, build_process);
+
+    println!("\nDokuWiki split topic: Done.");
+}
+
+pub fn dokuwiki_link_unlinked_mentions(compare_only: bool) {
+    // Turn the first unlinked mention of each topic on a page into a link, then regenerate the
+    // wiki. The same mentions are listed on the Reports page, so it's worth looking them over
+    // there first, or running this with compare_only.
+    println!("\nDokuWiki link unlinked mentions: Start.");
+
+    let (mut model, build_process) = prep_round_trip(compare_only, None, None, DateSource::FileMonitor);
+    let mentions = model.link_unlinked_mentions();
+    for mention in mentions.iter() {
+        println!("\t{}: {} (\"{}\")", mention.get_topic_key(), mention.get_mentioned_topic_key(), mention.get_first_text());
+    }
+    complete_round_trip(model, build_process);
+
+    println!("\nDokuWiki link unlinked mentions: Done.");
+}
+
+pub fn dokuwiki_added_date_preview(date_source: DateSource) {
+    // List the Added dates that dokuwiki_estimate_added_dates() would fill in, without changing
+    // any topic pages. The list goes to the Estimated Added Dates page of the private wiki.
+    println!("\nDokuWiki added date preview: Start.");
+
+    // Read the page dates first so that the dates already known aren't shown as estimates.
+    let project = file_monitor::model::set_up_project(FILE_MONITOR_PROJECT_NAME_DOKUWIKI, FILE_MONITOR_SCAN_MINUTES);
+    let (model, _build_process) = super::to_model::build_model(PROJECT_NAME, &PROJECT_NAME.to_lowercase(), true, None, None, Some(date_source), None, Some(project));
+    let estimates = model.estimate_added_dates();
+    for estimate in estimates.iter() {
+        println!("\t{}: {} ({})", estimate.get_topic_key(), estimate.get_date(), estimate.describe_source());
+    }
+    let gen = GenFromModel::new(&model, PATH_PAGES);
+    gen.gen_added_dates_page(&estimates);
+    println!("Added dates: {} estimated.", estimates.len());
+
+    println!("\nDokuWiki added date preview: Done.");
+}
+
+pub fn dokuwiki_estimate_added_dates(compare_only: bool, date_source: DateSource) {
+    // Give each topic that has no Added date an estimated one, flagged with an Added Estimated
+    // attribute, then regenerate the wiki. Run dokuwiki_added_date_preview() first to look over
+    // the dates.
+    println!("\nDokuWiki estimate added dates: Start.");
+
+    let (mut model, build_process) = prep_round_trip(compare_only, None, None, date_source);
+    let estimates = model.estimate_added_dates();
+    model.apply_added_date_estimates(&estimates);
+    println!("Added dates: {} estimated.", estimates.len());
+    complete_round_trip(model, build_process);
+
+    println!("\nDokuWiki estimate added dates: Done.");
+}
+
+pub fn dokuwiki_fmt(topic_names: &[&str], check_only: bool) {
+    // Rewrite the given pages in the main namespace into the canonical form the round trip would
+    // produce, without building the model or touching any other page. With check_only, nothing is
+    // written and the run fails if any of the pages would change.
+    println!("\nDokuWiki fmt: Start.");
+
+    assert!(!topic_names.is_empty());
+    let path_namespace = format!("{}/{}", PATH_PAGES, wiki::namespace_to_path(&PROJECT_NAME.to_lowercase()));
+    let mut changed_pages = vec![];
+    for topic_name in topic_names.iter() {
+        let path_page = format!("{}/{}.txt", path_namespace, wiki::legal_file_name(topic_name));
+        let content = std::fs::read_to_string(&path_page).expect(&format!("Page not found for \"{}\": \"{}\".", topic_name, path_page));
+        let content_formatted = wiki::fmt::format_page(&content);
+        if content_formatted.ne(&content) {
+            println!("\t{}", path_page);
+            changed_pages.push((path_page, content_formatted));
+        }
+    }
+
+    if check_only {
+        assert!(changed_pages.is_empty(), "{} of {} pages are not formatted.", changed_pages.len(), topic_names.len());
+    } else if !changed_pages.is_empty() {
+        // Formatting isn't an edit, so keep the file monitor from counting it as one.
+        let project = file_monitor::model::set_up_project(FILE_MONITOR_PROJECT_NAME_DOKUWIKI, FILE_MONITOR_SCAN_MINUTES);
+        project.set_marker(&FileMonitorMarker::Pause);
+        for (path_page, content_formatted) in changed_pages.iter() {
+            std::fs::write(path_page, content_formatted).unwrap();
+        }
+        project.clear_marker(&FileMonitorMarker::Pause);
+        println!("Formatted {} of {} pages.", changed_pages.len(), topic_names.len());
+    }
+
+    println!("\nDokuWiki fmt: Done.");
+}
+
+pub fn dokuwiki_lint() {
+    // Check the topics against the lint rules and write the results as JSON without changing any
+    // pages. A full round trip also writes them to the Lint page.
+    println!("\nDokuWiki lint: Start.");
+
+    let (mut model, _build_process) = super::to_model::build_model(PROJECT_NAME, &PROJECT_NAME.to_lowercase(), true, None, None, None, None, None);
+    super::to_model::complete_model(&mut model);
+    let results = model.lint(&LintConfig::read(&model).unwrap());
+    for result in results.iter() {
+        println!("\t{} {}: {} ({})", result.get_severity(), result.get_topic_key(), result.get_message(), result.get_lint_name());
+    }
+    write_lint_results(&results);
+
+    println!("\nDokuWiki lint: Done.");
+}
+
+pub fn dokuwiki_redaction_report(filter_audience: Option<String>, filter_main_topic_ref: Option<String>) {
+    // Preview what a filtered build would redact without generating any public pages. The report
+    // goes to the Redactions page of the private wiki and to a JSON file.
+    println!("\nDokuWiki redaction report: Start.");
+
+    let (model, _build_process) = super::to_model::build_model(PROJECT_NAME, &PROJECT_NAME.to_lowercase(), true, filter_audience, filter_main_topic_ref, None, None, None);
+    assert!(model.is_filtered());
+    let hits = model.get_redaction_hits();
+    let gen = GenFromModel::new(&model, PATH_PAGES);
+    gen.gen_redactions_page(hits);
+    std::fs::write(FILE_NAME_REDACTION_RESULTS, redaction_hits_to_json(hits)).unwrap();
+    println!("Redactions: {} hits, written to \"{}\".", hits.len(), FILE_NAME_REDACTION_RESULTS);
+
+    println!("\nDokuWiki redaction report: Done.");
+}
+
+pub fn dokuwiki_acl() {
+    // For serving the private wiki with access control instead of building a public copy. Write
+    // the ACL rules that follow from each topic's Visibility to a fragment for acl.auth.php, then
+    // report any topic page whose access under the current acl.auth.php is different.
+    println!("\nDokuWiki ACL: Start.");
+
+    let (mut model, _build_process) = super::to_model::build_model(PROJECT_NAME, &PROJECT_NAME.to_lowercase(), true, None, None, None, None, None);
+    super::to_model::complete_model(&mut model);
+    let plan = AclPlan::new(&model);
+    for line in plan.get_plan_lines().iter() {
+        println!("\t{}", line);
+    }
+    std::fs::write(FILE_NAME_ACL_GEN, plan.get_file_text()).unwrap();
+    println!("ACL: {} rules, written to \"{}\".", plan.get_rules().len(), FILE_NAME_ACL_GEN);
+
+    if util::file::path_exists(FILE_NAME_ACL) {
+        let problems = plan.check(&read_acl_file(FILE_NAME_ACL).unwrap());
+        for problem in problems.iter() {
+            println!("\t{}", problem);
+        }
+        println!("ACL: {} differences between \"{}\" and the model.", problems.len(), FILE_NAME_ACL);
+    }
+
+    println!("\nDokuWiki ACL: Done.");
+}
+
+fn write_lint_results(results: &Vec<LintResult>) {
+    std::fs::write(FILE_NAME_LINT_RESULTS, lint_results_to_json(results)).unwrap();
+    let error_count = results.iter().filter(|result| result.get_severity() == LintSeverity::Error).count();
+    println!("Lint: {} problems including {} errors, written to \"{}\".", results.len(), error_count, FILE_NAME_LINT_RESULTS);
+}
+
+pub(crate) fn prep_round_trip(compare_only: bool, filter_audience: Option<String>, filter_main_topic_ref: Option<String>, date_source: DateSource) -> (model::Model, BuildProcess) {
     println!("\ndokuwiki::gen_tools_wiki::prep_round_trip(): Start.");
 
     let project = file_monitor::model::set_up_project(FILE_MONITOR_PROJECT_NAME_DOKUWIKI, FILE_MONITOR_SCAN_MINUTES);
@@ -31,7 +261,7 @@ pub(crate) fn prep_round_trip(compare_only: bool, filter_is_public: bool, filter
     }
 
     // Create a model from the DokuWiki pages.
-    let (model, build_process) = super::to_model::build_model(PROJECT_NAME, &PROJECT_NAME.to_lowercase(), compare_only, filter_is_public, filter_main_topic_ref, None, Some(project));
+    let (model, build_process) = super::to_model::build_model(PROJECT_NAME, &PROJECT_NAME.to_lowercase(), compare_only, filter_audience, filter_main_topic_ref, Some(date_source), None, Some(project));
 
     // Back up the DokuWiki pages.
     let backup_folder_old = util::file::back_up_folder_next_number_r(PATH_PAGES, FOLDER_WIKI_GEN_BACKUP, FOLDER_PREFIX_WIKI_GEN_BACKUP, 4).unwrap();
@@ -60,6 +290,15 @@ pub(crate) fn complete_round_trip(mut model: model::Model, mut build_process: Bu
     //     clean_up_tools_dokuwiki_files(&build_process.gen_path_pages, false);
     // }
 
+    if model.is_filtered() {
+        // Pages like the lint and redaction reports are only generated in a full build, so clear
+        // out any left from an earlier build before the leak scan sees them.
+        let path_nav = format!("{}/nav", path_pages_project(&build_process.gen_path_pages));
+        if util::file::path_exists(&path_nav) {
+            util::file::remove_files_r(&path_nav).unwrap();
+        }
+    }
+
     gen_tools_project_from_model(&model, &mut build_process);
 
     // At this point the standard generated files like [start.txt] and everything in the nav folder
@@ -69,6 +308,14 @@ pub(crate) fn complete_round_trip(mut model: model::Model, mut build_process: Bu
 
     build_process.write_main_topic_files();
 
+    if model.is_filtered() {
+        // Copy only the media that the included topics link to, rather than whole folders that
+        // might hold screenshots from private topics.
+        let media_ids = copy_referenced_media(&model, PATH_MEDIA, wiki::FOLDER_MEDIA_COMPARE);
+        println!("Copied {} media files to \"{}\".", media_ids.len(), wiki::FOLDER_MEDIA_COMPARE);
+        scan_for_leaks(&model, &build_process.gen_path_pages);
+    }
+
     if !build_process.compare_only {
         // let path_pages_project = path_pages_project(PATH_PAGES);
         let backup_folder_new = util::file::back_up_folder_next_number_r(PATH_PAGES, FOLDER_WIKI_GEN_BACKUP, FOLDER_PREFIX_WIKI_GEN_BACKUP, 4).unwrap();
@@ -92,6 +339,19 @@ pub(crate) fn complete_round_trip(mut model: model::Model, mut build_process: Bu
     println!("\ndokuwiki::gen_tools_wiki::complete_round_trip(): Done.");
 }
 
+fn scan_for_leaks(model: &model::Model, path_pages: &str) {
+    // Read back everything the filtered build wrote, including the feeds and copied media, and
+    // fail if anything that should have been redacted got through.
+    println!("\nScanning filtered build for leaks{}: Start.", model.filter_audience().map_or("".to_string(), |audience| format!(" for audience \"{}\"", audience)));
+    let mut leak_scan = LeakScan::new(model.get_redactor());
+    leak_scan.scan_folder(path_pages, "", false);
+    leak_scan.scan_folder(&path_pages_project(path_pages), &PROJECT_NAME.to_lowercase(), true);
+    leak_scan.scan_folder(wiki::FOLDER_FEEDS_COMPARE, &PROJECT_NAME.to_lowercase(), false);
+    leak_scan.scan_folder(wiki::FOLDER_MEDIA_COMPARE, "", true);
+    leak_scan.assert_no_leaks();
+    println!("\nScanning filtered build for leaks: Done.");
+}
+
 fn path_pages_project(path_pages: &str) -> String {
     format!("{}/{}", path_pages, PROJECT_NAME.to_lowercase())
 }
@@ -144,6 +404,10 @@ fn gen_tools_project_from_model(model: &model::Model, build_process: &mut BuildP
     gen_sidebar_page(model, &mut gen);
     gen_start_page(model, &gen);
     gen.gen_recent_topics_page();
+    // The live feeds go in the media folder. When only comparing, write them off to the side so
+    // they don't show up as differences in the pages.
+    let path_feeds = if build_process.compare_only { wiki::FOLDER_FEEDS_COMPARE.to_string() } else { path_media_project() };
+    gen.gen_recent_topics_feed(PROJECT_NAME, &path_feeds, wiki::FEED_TOPIC_COUNT, true);
     gen.gen_all_topics_page();
     gen.gen_categories_page();
     gen.gen_subtopics_page();
@@ -152,6 +416,14 @@ fn gen_tools_project_from_model(model: &model::Model, build_process: &mut BuildP
     gen.gen_attr_pages();
     gen.gen_attr_value_page();
     gen.gen_reports_page();
+    if !model.is_filtered() {
+        let lint_results = model.lint(&LintConfig::read(model).unwrap());
+        gen.gen_lint_page(&lint_results);
+        write_lint_results(&lint_results);
+        let media_report = MediaReport::new(model, PATH_MEDIA);
+        gen.gen_media_page(&media_report);
+        media_report.print();
+    }
     gen.gen_glossary_pages(&model);
     // gen_terms_page();
     build_process.topic_dest_files = gen.gen();
@@ -212,6 +484,7 @@ fn add_main_page_links(page: &mut wiki::WikiGenPage, model: &model::Model, use_l
     ]);
     if !model.is_filtered() {
         links.push(wiki::page_link(&namespace_nav, wiki::PAGE_NAME_REPORTS, None));
+        links.push(wiki::page_link(&namespace_nav, wiki::PAGE_NAME_LINT, None));
         links.push(wiki::page_link(&namespace_main, wiki::PAGE_NAME_DOKUWIKI_MARKUP, None));
     }
     links.push(wiki::page_link(&namespace_main, wiki::PAGE_NAME_TERMS, None));
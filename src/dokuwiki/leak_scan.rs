@@ -0,0 +1,104 @@
+use std::fs;
+
+use super::*;
+use crate::model::redaction::Redactor;
+
+// The last line of defense for a filtered build. Redaction works on the source pages, but a
+// private name could still reach a public page some other way, such as through a generated
+// navigation page, a glossary row, or a link label. So once everything has been written, read it
+// all back and look for anything the redactor would have hidden.
+
+pub(crate) struct LeakScan<'a> {
+    redactor: &'a Redactor,
+    leaks: Vec<String>,
+}
+
+impl <'a> LeakScan<'a> {
+    pub(crate) fn new(redactor: &'a Redactor) -> Self {
+        Self {
+            redactor,
+            leaks: vec![],
+        }
+    }
+
+    pub(crate) fn scan_folder(&mut self, path: &str, namespace: &str, recursive: bool) {
+        // Check the name and content of each text file in the folder. Other files, like images,
+        // are only checked by name. A subfolder is a namespace inside this one.
+        if !util::file::path_exists(path) {
+            return;
+        }
+        for dir_entry in fs::read_dir(path).unwrap().map(|dir_entry| dir_entry.unwrap()) {
+            let file_name = util::file::dir_entry_to_file_name(&dir_entry);
+            let path_file = format!("{}/{}", path, file_name);
+            if dir_entry.path().is_dir() {
+                if recursive {
+                    let namespace_sub = if namespace.is_empty() { file_name.clone() } else { format!("{}{}{}", namespace, DELIM_NAMESPACE, file_name) };
+                    self.scan_folder(&path_file, &namespace_sub, recursive);
+                }
+                continue;
+            }
+            self.scan_name(&file_name, namespace, &path_file);
+            if file_name.ends_with(".txt") || file_name.ends_with(".xml") {
+                let content = fs::read_to_string(&path_file).unwrap();
+                self.scan_text(&content, namespace, &path_file);
+            }
+        }
+    }
+
+    pub(crate) fn scan_name(&mut self, name: &str, namespace: &str, context: &str) {
+        for found in self.redactor.find_matches(name, namespace).iter() {
+            self.leaks.push(format!("{}: file name has \"{}\", rule {}.", context, &name[found.get_start()..found.get_end()], self.redactor.describe_match(found)));
+        }
+    }
+
+    fn scan_text(&mut self, text: &str, namespace: &str, context: &str) {
+        for found in self.redactor.find_matches(text, namespace).iter() {
+            let line_number = text[..found.get_start()].matches(DELIM_LINEFEED).count() + 1;
+            self.leaks.push(format!("{}, line {}: \"{}\", rule {}.", context, line_number, &text[found.get_start()..found.get_end()], self.redactor.describe_match(found)));
+        }
+    }
+
+    pub(crate) fn assert_no_leaks(&self) {
+        // Fail the build rather than let a private name out.
+        if !self.leaks.is_empty() {
+            println!("\nLeaks found in the filtered build:");
+            for leak in self.leaks.iter() {
+                println!("\t{}", leak);
+            }
+            panic!("{} leaks found in the filtered build.", self.leaks.len());
+        }
+        println!("Leak scan: no leaks found.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::redaction::{parse_rule_line, RuleLine};
+
+    fn redactor(phrase: &str) -> Redactor {
+        match parse_rule_line(phrase).unwrap() {
+            RuleLine::Rule(rule) => Redactor::new(vec![rule], vec![]),
+            RuleLine::Allow(_) => panic!("Expected a rule."),
+        }
+    }
+
+    #[test]
+    fn private_name_is_a_leak() {
+        let redactor = redactor("Acme");
+        let mut scan = LeakScan::new(&redactor);
+        scan.scan_text("A public page.\nWritten for Acme.", "tools", "tools/rust.txt");
+        assert_eq!(scan.leaks.len(), 1);
+        assert!(scan.leaks[0].starts_with("tools/rust.txt, line 2: \"Acme\", rule "));
+    }
+
+    #[test]
+    fn public_text_is_not_a_leak() {
+        let redactor = redactor("Acme");
+        let mut scan = LeakScan::new(&redactor);
+        scan.scan_text("A public page about Acmeworks.", "tools", "tools/rust.txt");
+        scan.scan_name("rust.txt", "tools", "tools/rust.txt");
+        assert!(scan.leaks.is_empty());
+        scan.assert_no_leaks();
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+
+use crate::*;
+use super::*;
+use crate::model::{Model, TopicKey, LinkType, ImageSource};
+
+// The media files (images and other files like PDFs) that the topics actually refer to. A filtered
+// build copies only the files referenced by the topics it includes, so a screenshot that belongs
+// to a private topic can't end up in the public copy just because it's in the same folder. The
+// same references drive a report on the media folder as a whole.
+
+pub(crate) struct MediaReport {
+    // Media IDs like "tools:rust_analyzer_settings.png".
+    unreferenced: Vec<String>,
+    // Media IDs that a topic refers to but that aren't in the media folder, with the topics.
+    missing: Vec<(String, Vec<TopicKey>)>,
+    // Groups of media IDs whose files have the same content.
+    duplicates: Vec<Vec<String>>,
+}
+
+pub(crate) fn referenced_media(model: &Model) -> BTreeMap<String, Vec<TopicKey>> {
+    // Every internal image or file link in the model, keyed by media ID, with the topics that
+    // link to it. External images aren't ours to copy.
+    let mut map = BTreeMap::new();
+    for topic in model.get_topics().values() {
+        for link_rc in topic.get_links(false, false).iter() {
+            let media_id = match b!(link_rc).get_type() {
+                LinkType::Image { source: ImageSource::Internal { namespace, file_name }, .. } => format!("{}{}{}", namespace, DELIM_NAMESPACE, file_name),
+                LinkType::File { file_ref } => file_ref.clone(),
+                _ => continue,
+            };
+            let media_id = media_id.trim_start_matches(DELIM_NAMESPACE).to_lowercase();
+            let topic_keys = map.entry(media_id).or_insert(vec![]);
+            if !topic_keys.contains(&topic.get_topic_key()) {
+                topic_keys.push(topic.get_topic_key());
+            }
+        }
+    }
+    map
+}
+
+pub(crate) fn media_id_to_path(path_media: &str, media_id: &str) -> String {
+    format!("{}/{}", path_media, media_id.replace(DELIM_NAMESPACE, "/"))
+}
+
+pub(crate) fn copy_referenced_media(model: &Model, path_from: &str, path_to: &str) -> Vec<String> {
+    // Replace whatever is in path_to with the referenced files from path_from, keeping the
+    // namespace folders. Returns the media IDs of the files copied. A reference to a missing file
+    // is skipped here and shows up in the media report.
+    if util::file::path_exists(path_to) {
+        fs::remove_dir_all(path_to).unwrap();
+    }
+    let mut copied = vec![];
+    for media_id in referenced_media(model).keys() {
+        let path_file_from = media_id_to_path(path_from, media_id);
+        if !util::file::path_exists(&path_file_from) {
+            continue;
+        }
+        let path_file_to = media_id_to_path(path_to, media_id);
+        let path_folder_to = path_file_to[..path_file_to.rfind('/').unwrap()].to_string();
+        util::file::path_create_if_necessary_r(path_folder_to).unwrap();
+        fs::copy(&path_file_from, &path_file_to).unwrap();
+        copied.push(media_id.clone());
+    }
+    copied
+}
+
+fn media_files(path_media: &str, namespace: &str, media_ids: &mut Vec<String>) {
+    // The media IDs of the files under the folder. The generated feeds live in the media folder
+    // too but they're not media anyone links to.
+    for dir_entry in fs::read_dir(path_media).unwrap().map(|dir_entry| dir_entry.unwrap()) {
+        let file_name = util::file::dir_entry_to_file_name(&dir_entry);
+        let media_id = if namespace.is_empty() { file_name.clone() } else { format!("{}{}{}", namespace, DELIM_NAMESPACE, file_name) };
+        if dir_entry.path().is_dir() {
+            media_files(&format!("{}/{}", path_media, file_name), &media_id, media_ids);
+        } else if !file_name.eq(FILE_NAME_FEED_ATOM) && !file_name.eq(FILE_NAME_FEED_RSS) {
+            media_ids.push(media_id.to_lowercase());
+        }
+    }
+}
+
+impl MediaReport {
+    pub(crate) fn new(model: &Model, path_media: &str) -> Self {
+        let referenced = referenced_media(model);
+        let mut on_disk = vec![];
+        media_files(path_media, "", &mut on_disk);
+        on_disk.sort();
+        let unreferenced = on_disk.iter()
+            .filter(|media_id| !referenced.contains_key(*media_id))
+            .cloned()
+            .collect();
+        let missing = referenced.iter()
+            .filter(|(media_id, _)| on_disk.binary_search(media_id).is_err())
+            .map(|(media_id, topic_keys)| (media_id.clone(), topic_keys.clone()))
+            .collect();
+        // Group the files by size and a hash of their content.
+        let mut content_map: BTreeMap<(u64, u64), Vec<String>> = BTreeMap::new();
+        for media_id in on_disk.iter() {
+            let content = fs::read(media_id_to_path(path_media, media_id)).unwrap();
+            let mut hasher = DefaultHasher::new();
+            content.hash(&mut hasher);
+            content_map.entry((content.len() as u64, hasher.finish())).or_insert(vec![]).push(media_id.clone());
+        }
+        let duplicates = content_map.into_iter()
+            .map(|(_, media_ids)| media_ids)
+            .filter(|media_ids| media_ids.len() > 1)
+            .collect();
+        Self {
+            unreferenced,
+            missing,
+            duplicates,
+        }
+    }
+
+    pub(crate) fn get_unreferenced(&self) -> &Vec<String> {
+        &self.unreferenced
+    }
+
+    pub(crate) fn get_missing(&self) -> &Vec<(String, Vec<TopicKey>)> {
+        &self.missing
+    }
+
+    pub(crate) fn get_duplicates(&self) -> &Vec<Vec<String>> {
+        &self.duplicates
+    }
+
+    pub(crate) fn print(&self) {
+        println!("Media: {} unreferenced, {} missing, {} groups of duplicates.", self.unreferenced.len(), self.missing.len(), self.duplicates.len());
+    }
+}
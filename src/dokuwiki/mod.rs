@@ -1,13 +1,24 @@
+pub(crate) mod fmt;
+
 pub(crate) mod gen;
 pub(crate) use gen::*;
 
+pub(crate) mod gen_feed;
+pub(crate) use gen_feed::*;
+
 pub(crate) mod gen_from_model;
 
 pub(crate) mod gen_page;
 pub(crate) use gen_page::*;
 
+pub(crate) mod acl;
+
 pub mod gen_tools_wiki;
 
+pub(crate) mod media;
+
+pub(crate) mod leak_scan;
+
 pub(crate) mod parse;
 pub(crate) use parse::*;
 
@@ -19,17 +30,40 @@ pub mod to_model;
 pub(crate) const PATH_PAGES: &str = "C:/Doku/DokuWikiStick/dokuwiki/data/pages";
 pub(crate) const PATH_MEDIA: &str = "C:/Doku/DokuWikiStick/dokuwiki/data/media";
 pub(crate) const PATH_TEMP_SOURCE: &str = "C:/Wiki Gen Backup/Temp_Source";
+pub(crate) const FILE_NAME_ACL: &str = "C:/Doku/DokuWikiStick/dokuwiki/conf/acl.auth.php";
+pub(crate) const FILE_NAME_ACL_GEN: &str = "C:/Wiki Gen Backup/acl.auth.php";
 
 pub(crate) const FILE_MONITOR_PROJECT_NAME_DOKUWIKI: &str = "DokuWiki";
 pub(crate) const FILE_MONITOR_SCAN_MINUTES: f32 = 1.0;
 
+// When a build reads the page dates from git, commits by these authors (name or email) are
+// ignored, so commit the pages under one of them after a round trip.
+pub(crate) const GIT_AUTHORS_GEN: [&str; 1] = ["Wiki Gen"];
+
 pub(crate) const RECENT_TOPICS_THRESHOLD: usize = 100;
 
+// The Atom feed (and optionally an RSS 2.0 feed) of recent topics is written to the project's
+// media folder so that DokuWiki serves it. Change the URL prefix when the wiki is hosted
+// somewhere other than DokuWikiStick.
+pub(crate) const URL_WIKI_PAGE_PREFIX: &str = "http://localhost:8800/doku.php?id=";
+pub(crate) const URL_WIKI_MEDIA_PREFIX: &str = "http://localhost:8800/lib/exe/fetch.php?media=";
+pub(crate) const FOLDER_FEEDS_COMPARE: &str = "C:/Wiki Gen Backup/Feeds";
+// In a filtered build, the media files the included topics refer to are copied here.
+pub(crate) const FOLDER_MEDIA_COMPARE: &str = "C:/Wiki Gen Backup/Media";
+pub(crate) const FILE_NAME_FEED_ATOM: &str = "recent_topics.atom.xml";
+pub(crate) const FILE_NAME_FEED_RSS: &str = "recent_topics.rss.xml";
+pub(crate) const FEED_TOPIC_COUNT: usize = 25;
+pub(crate) const FEED_SUMMARY_LENGTH: usize = 300;
+
 pub(crate) const PAGE_NAME_SIDEBAR:         &str = "Sidebar";
 pub(crate) const PAGE_NAME_MAIN:            &str = "Main";
 pub(crate) const PAGE_NAME_START:           &str = "Start";
 pub(crate) const PAGE_NAME_RECENT_TOPICS:   &str = "Recent Topics";
 pub(crate) const PAGE_NAME_REPORTS:         &str = "Reports";
+pub(crate) const PAGE_NAME_LINT:            &str = "Lint";
+pub(crate) const PAGE_NAME_REDACTIONS:      &str = "Redactions";
+pub(crate) const PAGE_NAME_MEDIA:           &str = "Media";
+pub(crate) const PAGE_NAME_ADDED_DATES:     &str = "Estimated Added Dates";
 pub(crate) const PAGE_NAME_DOKUWIKI_MARKUP: &str = "DokuWiki Markup";
 pub(crate) const PAGE_NAME_ALL_TOPICS:      &str = "All Topics";
 pub(crate) const PAGE_NAME_CATEGORIES:      &str = "Categories";
@@ -51,8 +85,10 @@ pub(crate) const DELIM_PARAGRAPH: &str = "\n\n";
 pub(crate) const DELIM_BREADCRUMB_RIGHT: &str = "=>";
 pub(crate) const DELIM_BREADCRUMB_LEFT: &str = "<=";
 pub(crate) const DELIM_BOLD: &str = "**";
-#[allow(dead_code)]
 pub(crate) const DELIM_ITALIC: &str = "//";
+pub(crate) const DELIM_MONOSPACE: &str = "''";
+pub(crate) const DELIM_NO_FORMAT: &str = "%%";
+pub(crate) const DELIM_LINE_BREAK: &str = "\\\\";
 pub(crate) const DELIM_LINK_START: &str = "[[";
 pub(crate) const DELIM_LINK_END: &str = "]]";
 pub(crate) const DELIM_LINK_LABEL: &str = "|";
@@ -70,24 +106,31 @@ pub(crate) const DELIM_LIST_ITEM_DEPTH: &str = "  ";
 pub(crate) const MARKER_LINE_START: &str = "<";
 pub(crate) const MARKER_LINE_START_CLOSE: &str = "</";
 pub(crate) const MARKER_LINE_END: &str = ">";
-// pub(crate) const MARKER_QUOTE_START: &str = "<WRAP round box>";
+pub(crate) const MARKER_QUOTE_START: &str = "<WRAP round box>";
 pub(crate) const MARKER_QUOTE_START_PREFIX: &str = "<WRAP";
-// pub(crate) const MARKER_QUOTE_END: &str = "</WRAP>";
+pub(crate) const MARKER_QUOTE_END: &str = "</WRAP>";
 pub(crate) const MARKER_CODE_START_PREFIX: &str = "<code";
 pub(crate) const MARKER_CODE_END: &str = "</code>";
+// Wraps one or more paragraphs of an otherwise shareable page that must never go into a filtered
+// build.
+pub(crate) const MARKER_PRIVATE_START: &str = "<private>";
+pub(crate) const MARKER_PRIVATE_END: &str = "</private>";
 // Change the symbols if this causes a parsing issue.
 pub(crate) const MARKER_REDACTION: &str = "~~~redacted~~~";
 pub(crate) const MARKER_REDACTION_FINAL: &str = "%%*****%%";
-#[allow(dead_code)]
-pub(crate) const MARKER_PUBLIC_IN_TEXT_FILE: &str = "^ Visibility | Public |";
 pub(crate) const MARKER_DELETE_THIS_FILE: &str = "{{{delete}}}";
 pub(crate) const MARKER_TERMS_ADD_DEFINITION: &str = "(//add definition//)";
+// The syntax of the DokuWiki pageredirect plugin, like "~~REDIRECT>tools:kubernetes~~".
+pub(crate) const MARKER_REDIRECT_START: &str = "~~REDIRECT>";
+pub(crate) const MARKER_REDIRECT_END: &str = "~~";
 
 pub(crate) const TEMP_DELIM_IMG_START: &str = "[[{{";
 pub(crate) const TEMP_DELIM_IMG_END: &str = "}}]]";
 pub(crate) const TEMP_COMMA: &str = "~temp comma~";
 
 pub(crate) const PREFIX_CATEGORY: &str = "Category: ";
+// Follows the primary category when a topic is in more than one.
+pub(crate) const MARKER_PRIMARY_CATEGORY: &str = "(primary)";
 
 /*
 pub(crate) fn back_up_from_live() {
@@ -135,10 +135,11 @@ pub(crate) fn parse_breadcrumb_optional(text: &str, context: &str, is_filtered:
 
 pub(crate) fn parse_marker_optional(text: &str) -> Result<Option<(String, String)>, String> {
     // A marker will be a one-line paragraph with something like "<WRAP round box>", "</WRAP>",
-    // "<code>", "</code>", "<code Rust>", "<file>", "<html>", or "<php>".
+    // "<code>", "</code>", "<code Rust>", "<file>", "<html>", "<php>", or "<private>".
     let text = text.trim();
-    // For now we handle only "<code...>" (with or without a language) and "<WRAP...>".
-    if text.starts_with(MARKER_QUOTE_START_PREFIX) || text.starts_with(MARKER_CODE_START_PREFIX) {
+    // For now we handle only "<code...>" (with or without a language), "<WRAP...>" and
+    // "<private>".
+    if text.starts_with(MARKER_QUOTE_START_PREFIX) || text.starts_with(MARKER_CODE_START_PREFIX) || text.eq(MARKER_PRIVATE_START) {
     // if text.starts_with(MARKER_LINE_START) {
         // We can assume this is a marker.
         if !text.ends_with(MARKER_LINE_END) {
@@ -300,6 +301,27 @@ pub(crate) fn text_or_topic_link_label(text: &str) -> Result<String, String> {
     Ok(label.to_string())
 }
 
+pub(crate) fn parse_attribute_values(text: &str, context: &str) -> Result<Vec<String>, String> {
+    // The values cell of a row in an attribute table, like:
+    //   [[tools:nav:attribute_values#Android|Android]], [[tools:nav:attribute_values#Windows|Windows]]
+    // We want to split the attribute values using commas, but commas might be part of a quoted
+    // string or inside a link, and in those cases we want to avoid them during the split.
+    // Replace any commas inside a quoted string with a placeholder.
+    let text = util::parse::replace_within_delimiters_rc(&text, "\"", "\"", ",", TEMP_COMMA, true, context).unwrap();
+    // Replace any commas inside a link with a placeholder.
+    let text = util::parse::replace_within_delimiters_rc(&text, DELIM_LINK_START, DELIM_LINK_END, ",", TEMP_COMMA, true, context).unwrap();
+    // Split the attribute values using the remaining commas, if any.
+    let cell_items = util::parse::split_trim(&text, ",");
+    // Put the commas back inside the quoted strings and links.
+    let cell_items = cell_items.iter().map(|item| item.replace(TEMP_COMMA, ",")).collect::<Vec<_>>();
+    let mut values = vec![];
+    for cell_item in cell_items.iter() {
+        let value = text_or_topic_link_label(cell_item)?.trim().to_string();
+        values.push(value);
+    }
+    Ok(values)
+}
+
 pub(crate) fn parse_list_optional(text: &str) -> Result<Option<model::List>, String> {
     //et debug = text.contains("on_data_structures_and_algorithms_with_rust|Hands-On Data Structures and Algorithms with Rust]]");
     let debug = false;
@@ -407,3 +429,20 @@ pub(crate) fn parse_list_item_optional(line: &str, _debug: bool) -> Result<Optio
 
     Ok(Some(list_item))
 }
+
+pub(crate) fn replace_delimiter_pairs(text: &str, delim: &str, start: &str, end: &str) -> String {
+    // Replace each pair of delimiters like "**" or "~~" with a start and end marker, but only if
+    // the delimiters are balanced. Shared by the Markdown and MediaWiki converters.
+    let parts = text.split(delim).collect::<Vec<_>>();
+    if parts.len() < 3 || parts.len() % 2 == 0 {
+        return text.to_string();
+    }
+    let mut result = String::new();
+    for (index, part) in parts.iter().enumerate() {
+        if index > 0 {
+            result.push_str(if index % 2 == 1 { start } else { end });
+        }
+        result.push_str(part);
+    }
+    result
+}
@@ -3,7 +3,7 @@ use crate::model::*;
 use std::fs;
 use super::*;
 use crate::model::PUBLIC_ATTRIBUTES;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use crate::model::glossary::Glossary;
 
 #[derive(Debug)]
@@ -14,9 +14,11 @@ pub(crate) struct BuildProcess {
     pub(crate) gen_path_pages: String,
     pub(crate) compare_only: bool,
     pub(crate) is_filtered: bool,
-    pub(crate) filter_is_public: bool,
+    pub(crate) filter_audience: Option<String>,
     pub(crate) filter_main_topic_ref: Option<String>,
+    pub(crate) date_source: Option<DateSource>,
     pub(crate) topic_source_files: BTreeMap<String, TopicFile>,
+    pub(crate) redirect_source_files: BTreeMap<String, TopicFile>,
     pub(crate) topic_dest_files: BTreeMap<String, TopicFile>,
     pub(crate) topic_files_to_delete: Vec<String>,
     pub(crate) topic_refs: TopicRefs,
@@ -45,14 +47,14 @@ pub(crate) struct TopicParseState {
 }
 
 impl BuildProcess {
-    pub(crate) fn new(wiki_name: &str, namespace_main: &str, path_source: &str, compare_only: bool, filter_is_public: bool, filter_main_topic_ref: Option<String>, topic_limit: Option<usize>) -> Self {
+    pub(crate) fn new(wiki_name: &str, namespace_main: &str, path_source: &str, compare_only: bool, filter_audience: Option<String>, filter_main_topic_ref: Option<String>, date_source: Option<DateSource>, topic_limit: Option<usize>) -> Self {
 
-        let is_filtered = filter_is_public || filter_main_topic_ref.is_some();
+        let is_filtered = filter_audience.is_some() || filter_main_topic_ref.is_some();
 
         if is_filtered {
             assert!(compare_only);
         }
-        assert!(!filter_is_public || filter_main_topic_ref.is_none());
+        assert!(filter_audience.is_none() || filter_main_topic_ref.is_none());
 
         Self {
             wiki_name: wiki_name.to_string(),
@@ -61,9 +63,11 @@ impl BuildProcess {
             gen_path_pages: "".to_string(),
             compare_only,
             is_filtered,
-            filter_is_public,
+            filter_audience,
             filter_main_topic_ref,
+            date_source,
             topic_source_files: Default::default(),
+            redirect_source_files: Default::default(),
             topic_dest_files: Default::default(),
             topic_files_to_delete: vec![],
             topic_refs: Default::default(),
@@ -74,7 +78,7 @@ impl BuildProcess {
     }
 
     pub(crate) fn build(&mut self, project: Option<file_monitor::model::Project>) -> Model {
-        let mut model = Model::new(&self.wiki_name, &self.namespace_main, self.filter_is_public,self.filter_main_topic_ref.clone());
+        let mut model = Model::new(&self.wiki_name, &self.namespace_main, self.filter_audience.clone(), self.filter_main_topic_ref.clone());
 
         if let Some(project) = project {
             model.set_file_monitor_project(project);
@@ -91,20 +95,27 @@ impl BuildProcess {
         // self.parse_from_folder(&mut model, &namespace_book, topic_limit_per_namespace);
         assert!(!self.topic_source_files.is_empty());
 
+        // The topic refs of the pages left out of a filtered build, so that a redirect to one of
+        // them can be dropped quietly.
+        let mut excluded_topic_refs = BTreeSet::new();
         if self.is_filtered {
-            model.filter_set_topics_include_and_redacted();
+            let candidates = self.topic_source_files.values()
+                .map(|topic_file| topic_file.filter_candidate())
+                .collect::<Vec<_>>();
+            let excluded_topic_keys = model.filter_set_topics_include_and_redacted(&candidates);
             model.finalize_redacted_phrases();
             // model.print_redacted_phrases();
 
-            // Remove any topic source files whose title contains a redacted phrase. This will
-            // handle cases like a combination topic marked as Public in which one or both of the
-            // parent topics are marked Private.
+            // Remove the topic source files left out by the filter, along with any whose title
+            // contains a redacted phrase. The latter will handle cases like a combination topic
+            // marked as Public in which one or both of the parent topics are marked Private.
             let dbg_topic_count_before = self.topic_source_files.len();
-            // self.topic_source_files.retain(|topic_file| !redaction::text_contains_phrase(&topic_file.topic_name, model.get_redacted_phrases()));
             let mut filtered_source_files = BTreeMap::new();
             for (key, topic_file) in self.topic_source_files.drain_filter(|_k, _v| true) {
-                if redaction::text_contains_phrase(&topic_file.topic_name, model.get_redacted_phrases()) {
+                let topic_key = TopicKey::new(&topic_file.namespace_name, &topic_file.topic_name);
+                if excluded_topic_keys.contains(&topic_key) || model.get_redactor().text_contains_phrase(&topic_file.topic_name, &topic_file.namespace_name) {
                     // Remove this topic.
+                    excluded_topic_refs.insert(make_topic_ref(&topic_file.namespace_name, &topic_file.topic_name));
                     self.topic_files_to_delete.push(key);
                 } else {
                     // Keep this topic.
@@ -112,6 +123,12 @@ impl BuildProcess {
                 }
             }
             std::mem::swap(&mut self.topic_source_files, &mut filtered_source_files);
+            // A redirect page under a redacted name would publish that name even though it's
+            // only a stub.
+            let redirect_count_before = self.redirect_source_files.len();
+            let redactor = model.get_redactor();
+            self.redirect_source_files.retain(|_key, redirect_file| !redactor.text_contains_phrase(&redirect_file.topic_name, &redirect_file.namespace_name));
+            println!("BuildProcess::build(): dropped {} of {} redirects.", redirect_count_before - self.redirect_source_files.len(), redirect_count_before);
             let dbg_topic_count_after = self.topic_source_files.len();
             println!("BuildProcess::build(): started with {} topics, ended with {}.", dbg_topic_count_before, dbg_topic_count_after);
         }
@@ -125,6 +142,10 @@ impl BuildProcess {
         assert_eq!(dbg_topic_source_file_count, dbg_topic_count, "dbg_topic_source_file_count = {}, dbg_topic_count = {}", dbg_topic_source_file_count, dbg_topic_count);
         assert_eq!(dbg_topic_source_file_count, dbg_topic_ref_count, "dbg_topic_source_file_count = {}, dbg_topic_ref_count = {}", dbg_topic_source_file_count, dbg_topic_ref_count);
 
+        // Aliases have to be known before any links are resolved, since a link to an alias might
+        // come from a page that's refined before the alias's own page.
+        self.add_aliases(&mut model);
+        self.add_redirects(&mut model, &excluded_topic_refs);
         self.topic_refs = model.get_topic_refs().clone();
 
         // Figure out the real nature of each paragraph.
@@ -172,7 +193,11 @@ impl BuildProcess {
         if !self.is_filtered {
             // One-time cleanup. Remove Edited attributes that have the same date as Added.
             // model.remove_edited_same_as_added();
-            model.update_attributes_from_file_monitor();
+            match self.date_source {
+                Some(DateSource::FileMonitor) => model.update_attributes_from_file_monitor(),
+                Some(DateSource::Git) => model.update_attributes_from_git(&self.path_source, &GIT_AUTHORS_GEN),
+                None => {},
+            }
         }
 
         // One-time fix.
@@ -225,6 +250,17 @@ impl BuildProcess {
                 if content.contains(MARKER_DELETE_THIS_FILE) {
                     errors.push(format!("{} should be deleted.", file_name));
                 }
+                if content.starts_with(MARKER_REDIRECT_START) {
+                    // A redirect page isn't a topic. It's kept to the side so that it can be
+                    // resolved once the topics are known.
+                    let topic_name = match content.split(DELIM_LINEFEED).find(|line| line.starts_with(DELIM_HEADER)) {
+                        Some(line) => line.replace(DELIM_HEADER, "").trim().to_string(),
+                        None => util::parse::before(&file_name, ".txt").to_string(),
+                    };
+                    let redirect_source_file = TopicFile::new(namespace_name, &file_name, &topic_name, content);
+                    self.redirect_source_files.insert(redirect_source_file.get_key(), redirect_source_file);
+                    continue;
+                }
                 let topic_name_line = util::parse::before(&content, DELIM_LINEFEED);
                 assert!(topic_name_line.starts_with(DELIM_HEADER), "Topic name \"{}\" in file \"{}\" should start with \"{}\".", &topic_name_line, &file_name, DELIM_HEADER);
                 assert!(topic_name_line.ends_with(DELIM_HEADER), "Topic name \"{}\" in file \"{}\" should end with \"{}\".", &topic_name_line, &file_name, DELIM_HEADER);
@@ -286,7 +322,21 @@ impl BuildProcess {
             assert_no_extra_lines(&topic_source_file.file_name, &content);
 
             if model.is_filtered() {
-                if let Some(mut new_content) = redaction::redact_text(&content, model.get_redacted_phrases()) {
+                // Private blocks are dropped before anything else looks at the text, so they
+                // don't show up in the redaction report, links, or glossaries.
+                let topic_key = TopicKey::new(&topic_source_file.namespace_name, &topic_source_file.topic_name);
+                content = match remove_private_blocks(&content) {
+                    Ok(content) => content,
+                    Err(msg) => {
+                        self.errors.add(&topic_key, &msg);
+                        remove_from_first_private_marker(&content)
+                    },
+                };
+                // Keep a record of each redaction for the redaction report.
+                let matches = model.get_redactor().find_matches(&content, &topic_source_file.namespace_name);
+                let hits = model.get_redactor().make_hits(&topic_key, &content, &matches);
+                model.add_redaction_hits(hits);
+                if let Some(mut new_content) = redaction::Redactor::apply_matches(&content, &matches) {
                     //rintln!("BuildProcess::parse_topics(): redactions in \"{}\".", topic_source_file.topic_name);
                     std::mem::swap(&mut content, &mut new_content);
                 } else {
@@ -315,6 +365,69 @@ impl BuildProcess {
         }
     }
 
+    fn add_aliases(&mut self, model: &mut Model) {
+        // Look ahead for an Aliases row in each topic's attribute table, which comes before the
+        // first section header, like:
+        //   ^ [[tools:nav:attributes#Aliases|Aliases]] | K8s, kube |
+        // The same row is picked up again as a normal attribute when the paragraphs are refined.
+        let mut topic_aliases = vec![];
+        for topic in model.get_topics().values() {
+            let context = format!("Looking for aliases in \"{}\".", topic.get_name());
+            for paragraph in topic.get_paragraphs().iter() {
+                let text = match paragraph {
+                    Paragraph::Unknown { line_index: _, text } => text.trim(),
+                    _ => continue,
+                };
+                if text.starts_with(DELIM_HEADER) {
+                    break;
+                }
+                if let Ok(Some(table)) = parse_table_optional(text) {
+                    if !table.has_header() && table.get_column_count() == 2 {
+                        for row in table.get_rows().iter() {
+                            let label = text_or_topic_link_label(&row[0].get_text_block().get_unresolved_text());
+                            if label.map_or(false, |label| label.eq(ATTRIBUTE_NAME_ALIASES)) {
+                                match parse_attribute_values(&row[1].get_text_block().get_unresolved_text(), &context) {
+                                    Ok(aliases) => topic_aliases.push((topic.get_topic_key(), aliases)),
+                                    Err(msg) => self.errors.add(&topic.get_topic_key(), &msg),
+                                }
+                            }
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+        for (topic_key, aliases) in topic_aliases.iter() {
+            for alias in aliases.iter() {
+                if let Err(msg) = model.add_topic_alias(topic_key, alias) {
+                    self.errors.add(topic_key, &msg);
+                }
+            }
+        }
+    }
+
+    fn add_redirects(&mut self, model: &mut Model, excluded_topic_refs: &BTreeSet<String>) {
+        // A redirect page starts with a line like "~~REDIRECT>tools:kubernetes~~". In a filtered
+        // build the target may have been left out, in which case the redirect is dropped.
+        for redirect_source_file in self.redirect_source_files.values() {
+            let topic_key_from = TopicKey::new(&redirect_source_file.namespace_name, &redirect_source_file.topic_name);
+            let context = format!("Redirect page \"{}\".", redirect_source_file.file_name);
+            let first_line = util::parse::before(&redirect_source_file.content, DELIM_LINEFEED);
+            let target_ref = util::parse::between_trim(first_line, MARKER_REDIRECT_START, MARKER_REDIRECT_END);
+            if target_ref.contains(DELIM_NAMESPACE) {
+                let (target_name, target_namespace) = util::parse::rsplit_2(target_ref, DELIM_NAMESPACE);
+                if excluded_topic_refs.contains(&make_topic_ref(target_namespace, target_name)) {
+                    continue;
+                }
+            }
+            let result = topic_ref_to_topic_key(model.get_topic_refs(), target_ref, &context)
+                .and_then(|topic_key_to| model.add_redirect(&topic_key_from, &topic_key_to));
+            if let Err(msg) = result {
+                self.errors.add(&topic_key_from, &msg);
+            }
+        }
+    }
+
     fn refine_paragraphs(&mut self, model: &mut Model) {
         let mut glossaries = model.take_glossaries();
         for topic in model.get_topics_mut().values_mut() {
@@ -403,39 +516,47 @@ impl BuildProcess {
             if text.trim().contains(DELIM_LINEFEED) {
                 return err_func("The text seems to be a category format but it has linefeeds.");
             } else {
-                if self.is_filtered && text.contains(MARKER_REDACTION) {
-                    // This appears to be a category reference to a private topic, or at least
-                    // part of the referenced topic name is a redacted phrase, so leave this topic
-                    // without a category. Returning Ok(true) means this paragraph has been
-                    // handled and we don't want to keep trying to figure out what it is.
-                    //rintln!("{}: Ignoring category reference with redaction: \"{}\".", context, text);
-                    return Ok(true);
-                }
                 let category_part = util::parse::after(text, PREFIX_CATEGORY).trim().to_string();
-                match parse_link_optional(&self.topic_refs,&category_part, context) {
-                    Ok(Some(link)) => {
-                        match link.get_label() {
-                            Some(label) => {
-                                let category_name = label;
-                                //rintln!("\"{}\" in \"{}\"", topic.get_name(), category_name);
-                                topic.set_category(&category_name);
-                                return Ok(true);
-                            },
-                            None => {
-                                return err_func("Expected the link to have a label which is the category name.");
-                            },
-                        }
-                    },
-                    Ok(None) => {
-                        let category_name = category_part;
-                        //rintln!("\"{}\" in \"{}\"", topic.get_name(), category_name);
-                        topic.set_category(&category_name);
-                        return Ok(true);
-                    },
+                // A topic may be in several categories separated by commas, like:
+                //   Category: [[tools:rust_crates|Rust Crates]] (primary), [[tools:serialization|Serialization]]
+                // The one with the primary marker decides where the topic sits in the category
+                // tree. Without a marker the first category is the primary one.
+                let category_part = match util::parse::replace_within_delimiters_rc(&category_part, DELIM_LINK_START, DELIM_LINK_END, ",", TEMP_COMMA, true, context) {
+                    Ok(category_part) => category_part,
                     Err(msg) => {
                         return err_func(&msg);
                     }
+                };
+                for category_item in util::parse::split_trim(&category_part, ",").iter()
+                        .map(|category_item| category_item.replace(TEMP_COMMA, ",")) {
+                    if self.is_filtered && category_item.contains(MARKER_REDACTION) {
+                        // This appears to be a category reference to a private topic, or at least
+                        // part of the referenced topic name is a redacted phrase, so leave this
+                        // category off the topic. Even if every category is skipped, the
+                        // paragraph has been handled and we return Ok(true) below.
+                        //rintln!("{}: Ignoring category reference with redaction: \"{}\".", context, text);
+                        continue;
+                    }
+                    let is_primary = category_item.ends_with(MARKER_PRIMARY_CATEGORY);
+                    let category_item = category_item.trim_end_matches(MARKER_PRIMARY_CATEGORY).trim();
+                    let category_name = match parse_link_optional(&self.topic_refs, category_item, context) {
+                        Ok(Some(link)) => {
+                            match link.get_label() {
+                                Some(label) => label,
+                                None => {
+                                    return err_func("Expected the link to have a label which is the category name.");
+                                },
+                            }
+                        },
+                        Ok(None) => category_item.to_string(),
+                        Err(msg) => {
+                            return err_func(&msg);
+                        }
+                    };
+                    //rintln!("\"{}\" in \"{}\"", topic.get_name(), category_name);
+                    topic.add_category(&category_name, is_primary);
                 }
+                return Ok(true);
             }
         } else {
             Ok(false)
@@ -606,12 +727,12 @@ impl BuildProcess {
                         let text = row[0].get_text_block().get_unresolved_text();
                         let attr_type_name = text_or_topic_link_label(&text)?;
                         //bg!(&attr_type_name);
-                        // If this is a public build, ignore attributes where the type name or text
+                        // If this is a build for an audience, ignore attributes where the type name or text
                         // has been at least partially redacted. Also ignore certain attributes
                         // that don't go into a public build, like Visibility and contact
                         // information.
                         let mut use_this_attribute = true;
-                        if self.filter_is_public {
+                        if self.filter_audience.is_some() {
                             let is_attr_public = PUBLIC_ATTRIBUTES.contains(&&*attr_type_name);
                             //bg!(&attr_type_name, is_attr_public);
                             if !is_attr_public {
@@ -635,22 +756,10 @@ impl BuildProcess {
                             if self.is_filtered && text.contains(MARKER_REDACTION) {
                                 //rintln!("{}: Ignoring \"{}\" attribute because the values contain a redaction: \"{}\".", context, attr_type_name, text);
                             } else {
-                                // We want to split the attribute values using commas, but commas might be
-                                // part of a quoted string or inside a link, and in those cases we want to
-                                // avoid them during the split.
-                                // Replace any commas inside a quoted string with a placeholder.
-                                let text = util::parse::replace_within_delimiters_rc(&text, "\"", "\"", ",", TEMP_COMMA, true, context).unwrap();
-                                // Replace any commas inside a link with a placeholder.
-                                let text = util::parse::replace_within_delimiters_rc(&text, DELIM_LINK_START, DELIM_LINK_END, ",", TEMP_COMMA, true, context).unwrap();
-                                // Split the attribute values using the remaining commas, if any.
-                                let cell_items = util::parse::split_trim(&text, ",");
-                                // Put the commas back inside the quoted strings and links.
-                                let cell_items = cell_items.iter().map(|item| item.replace(TEMP_COMMA, ",")).collect::<Vec<_>>();
                                 // if topic.get_name().starts_with("Bayesian") { //bg!(topic.get_name(), &cell_items); }
                                 // let cell_items = util::parse::split_outside_of_delimiters_rc(&text, ",", "\"", "\"", context).unwrap();
 
-                                for cell_item in cell_items.iter() {
-                                    let value = text_or_topic_link_label(cell_item)?.trim().to_string();
+                                for value in parse_attribute_values(&text, context)?.into_iter() {
                                     assert!(!value.is_empty(), "In context \"{}\", attribute value is empty for \"{}\".", context, attr_type_name);
                                     AttributeType::assert_legal_attribute_value(&value);
                                     attr_values.push(value);
@@ -842,6 +951,35 @@ impl BuildProcess {
         }
     }
 
+    pub(crate) fn rename_topic_file(&mut self, topic_key_old: &TopicKey, topic_key_new: &TopicKey) {
+        // Move the page's text file to the name that DokuWiki will expect for the renamed topic,
+        // and keep track of it under the new name so that write_main_topic_files() compares the
+        // regenerated page against the original content.
+        let namespace_name = topic_key_old.get_namespace();
+        let file_name_old = legal_file_name(topic_key_old.get_topic_name());
+        let file_name_new = legal_file_name(topic_key_new.get_topic_name());
+        let key_old = make_topic_file_key(namespace_name, &file_name_old);
+        let mut topic_file = self.topic_source_files.remove(&key_old).expect(&format!("Source file not found for {}.", topic_key_old));
+        if !self.compare_only {
+            let path_namespace = format!("{}/{}", self.path_source, namespace_to_path(namespace_name));
+            fs::rename(format!("{}/{}.txt", path_namespace, file_name_old), format!("{}/{}.txt", path_namespace, file_name_new)).unwrap();
+        }
+        // Source file names keep their extension.
+        topic_file.file_name = format!("{}.txt", file_name_new);
+        topic_file.topic_name = topic_key_new.get_topic_name().to_string();
+        self.add_topic_source_file(topic_file);
+    }
+
+    pub(crate) fn remove_topic_file(&mut self, topic_key: &TopicKey) {
+        // Stop tracking the page of a topic that's no longer in the model, as when it has been
+        // merged into another topic. write_main_topic_files() moves every page out of the folder
+        // and brings back only the ones in the model, so the file goes away, and the backup made
+        // at the start of the round trip keeps a copy.
+        let key = make_topic_file_key(topic_key.get_namespace(), &legal_file_name(topic_key.get_topic_name()));
+        assert!(self.topic_source_files.remove(&key).is_some(), "Source file not found for {}.", topic_key);
+        self.topic_files_to_delete.push(key);
+    }
+
     pub(crate) fn add_topic_source_file(&mut self, topic_file: TopicFile) {
         let key = topic_file.get_key();
         assert!(!self.topic_source_files.contains_key(&key));
@@ -900,7 +1038,7 @@ impl BuildProcess {
                 //bg!(&topic_file_dest);
                 //self.print_source_file_keys();
                 //bg!(&key);
-                if let Some(topic_file_source) = self.topic_source_files.get(key) {
+                if let Some(topic_file_source) = self.topic_source_files.get(key).or_else(|| self.redirect_source_files.get(key)) {
                     //bg!(&topic_file_source);
                     // This file/topic existed before the round trip. See if it has changed.
                     is_changed = topic_file_source.content.ne(&topic_file_dest.content);
@@ -969,6 +1107,47 @@ impl TopicFile {
     fn get_key(&self) -> String {
         make_topic_file_key(&self.namespace_name, &self.file_name)
     }
+
+    fn filter_candidate(&self) -> crate::model::filter::TopicFilterCandidate {
+        // What a filtered build needs to know about the topic before it's parsed: the audiences
+        // in its Visibility attribute, and its parents from the breadcrumbs. Both come before the
+        // first section header.
+        let topic_key = TopicKey::new(&self.namespace_name, &self.topic_name);
+        let context = format!("Filtering \"{}\".", self.topic_name);
+        let audiences = self.visibility_values(&context);
+        let parent_topic_keys = self.content.split(DELIM_PARAGRAPH)
+            .skip(1)
+            .map(|paragraph| paragraph.trim())
+            .take_while(|paragraph| !paragraph.starts_with(DELIM_HEADER))
+            .filter(|paragraph| paragraph.starts_with(DELIM_BOLD))
+            .find_map(|paragraph| parse_breadcrumb_optional(paragraph, &context, false).ok().flatten())
+            .unwrap_or_default();
+        crate::model::filter::TopicFilterCandidate::new(topic_key, audiences, parent_topic_keys)
+    }
+
+    fn visibility_values(&self, context: &str) -> Vec<String> {
+        // Find the Visibility row in the attribute table, like:
+        //   ^ Visibility | Team, Client Acme |
+        // The table hasn't been parsed yet, so look at each row on its own. A page without a
+        // Visibility row isn't visible to any audience.
+        self.content.lines()
+            .skip(1)
+            .map(|line| line.trim())
+            .take_while(|line| !line.starts_with(DELIM_HEADER))
+            .filter(|line| line.starts_with(DELIM_TABLE_CELL_BOLD))
+            .filter_map(|line| parse_table_optional(line).ok().flatten())
+            .filter(|table| table.get_column_count() == 2)
+            .find_map(|table| {
+                let row = &table.get_rows()[0];
+                let label = text_or_topic_link_label(&row[0].get_text_block().get_unresolved_text());
+                if label.map_or(false, |label| label.eq(ATTRIBUTE_NAME_VISIBILITY)) {
+                    parse_attribute_values(&row[1].get_text_block().get_unresolved_text(), context).ok()
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
 }
 
 pub fn make_topic_file_key(namespace_name: &str, file_name: &str) -> String {
@@ -976,8 +1155,8 @@ pub fn make_topic_file_key(namespace_name: &str, file_name: &str) -> String {
     format!("{}:{}", namespace_name, file_name_before_extension)
 }
 
-pub(crate) fn build_model(name: &str, namespace_main: &str, compare_only: bool, filter_is_public: bool, filter_main_topic_ref: Option<String>, topic_limit: Option<usize>, project: Option<file_monitor::model::Project>) -> (Model, BuildProcess) {
-    let mut bp = BuildProcess::new(name, namespace_main,PATH_PAGES, compare_only, filter_is_public, filter_main_topic_ref, topic_limit);
+pub(crate) fn build_model(name: &str, namespace_main: &str, compare_only: bool, filter_audience: Option<String>, filter_main_topic_ref: Option<String>, date_source: Option<DateSource>, topic_limit: Option<usize>, project: Option<file_monitor::model::Project>) -> (Model, BuildProcess) {
+    let mut bp = BuildProcess::new(name, namespace_main,PATH_PAGES, compare_only, filter_audience, filter_main_topic_ref, date_source, topic_limit);
     let model = bp.build(project);
     (model, bp)
 }
@@ -1007,6 +1186,60 @@ fn assert_no_extra_lines(file_name: &str, content: &str) {
     assert!(!content.contains(&three_linefeeds), "Page content has extra blank lines: {}", file_name);
 }
 
+fn remove_private_blocks(content: &str) -> Result<String, String> {
+    // A private block is the lines between marker lines, like:
+    //   <private>
+    //   The server credentials are in the blue binder.
+    //   </private>
+    // The markers may also be paragraphs of their own, with blank lines around them. In a full
+    // build the markers are kept like any other marker. In a filtered build the whole block
+    // including the markers is removed. Blocks can't be nested, and a marker has to be on a line
+    // by itself. Anything else is an error, since otherwise private text would be published.
+    if !content.contains(MARKER_PRIVATE_START) && !content.contains(MARKER_PRIVATE_END) {
+        return Ok(content.to_string());
+    }
+    let mut lines = vec![];
+    let mut is_in_private_block = false;
+    for line in content.split(DELIM_LINEFEED) {
+        let marker = line.trim();
+        if marker.eq(MARKER_PRIVATE_START) {
+            if is_in_private_block {
+                return Err(format!("Nested \"{}\".", MARKER_PRIVATE_START));
+            }
+            is_in_private_block = true;
+        } else if marker.eq(MARKER_PRIVATE_END) {
+            if !is_in_private_block {
+                return Err(format!("\"{}\" without \"{}\".", MARKER_PRIVATE_END, MARKER_PRIVATE_START));
+            }
+            is_in_private_block = false;
+        } else if line.contains(MARKER_PRIVATE_START) || line.contains(MARKER_PRIVATE_END) {
+            return Err(format!("\"{}\" and \"{}\" have to be on lines by themselves: \"{}\".", MARKER_PRIVATE_START, MARKER_PRIVATE_END, marker));
+        } else if !is_in_private_block {
+            lines.push(line);
+        }
+    }
+    if is_in_private_block {
+        return Err(format!("\"{}\" without \"{}\".", MARKER_PRIVATE_START, MARKER_PRIVATE_END));
+    }
+    // Removing a block that was its own paragraph leaves the blank lines from both sides.
+    let mut content = lines.join(DELIM_LINEFEED);
+    let three_linefeeds = DELIM_LINEFEED.repeat(3);
+    while content.contains(&three_linefeeds) {
+        content = content.replace(&three_linefeeds, DELIM_PARAGRAPH);
+    }
+    Ok(content)
+}
+
+fn remove_from_first_private_marker(content: &str) -> String {
+    // When the private blocks in a page can't be worked out, keep only the text before the first
+    // marker rather than risk publishing what comes after it.
+    let index = [MARKER_PRIVATE_START, MARKER_PRIVATE_END].iter()
+        .filter_map(|marker| content.find(marker))
+        .min()
+        .unwrap_or(content.len());
+    content[..index].trim_end().to_string()
+}
+
 /*
 fn remove_brackets_rc(text: &str, context: &str) -> Result<String, String> {
     let text = text.trim();
@@ -1030,6 +1263,9 @@ pub(crate) fn complete_model(model: &mut Model) {
     errors.clear();
     check_links(&model, &mut errors);
     errors.print(Some("After adding missing category topics and checking links."));
+    // A broken section link still goes to the right page, so these are listed to be fixed by
+    // hand (they're also on the Reports page) rather than stopping the build.
+    model.check_section_links().print_without_panic(Some("Section links"));
 
     // Call the make tree functions after the last call to model.catalog_links().
     model.make_category_tree();
@@ -1058,3 +1294,29 @@ fn remove_edited_attribute_from_private_topics(model: &mut Model) {
     }
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_block_on_its_own_lines_is_removed() {
+        let content = "======Server======\n\nThe server is in the attic.\n<private>\nThe password is hunter2.\n</private>\nIt runs Debian.\n";
+        assert_eq!(remove_private_blocks(content).unwrap(), "======Server======\n\nThe server is in the attic.\nIt runs Debian.\n");
+    }
+
+    #[test]
+    fn private_block_as_paragraphs_is_removed() {
+        let content = "======Server======\n\nThe server is in the attic.\n\n<private>\n\nThe password is hunter2.\n\n</private>\n\nIt runs Debian.\n";
+        assert_eq!(remove_private_blocks(content).unwrap(), "======Server======\n\nThe server is in the attic.\n\nIt runs Debian.\n");
+    }
+
+    #[test]
+    fn misplaced_private_markers_are_errors() {
+        assert!(remove_private_blocks("Text.\n<private>\n<private>\nSecret.\n</private>\n").is_err());
+        assert!(remove_private_blocks("Text.\n</private>\n").is_err());
+        assert!(remove_private_blocks("Text.\n<private>\nSecret.\n").is_err());
+        assert!(remove_private_blocks("Text <private>secret</private>.\n").is_err());
+        assert_eq!(remove_from_first_private_marker("Text.\n\nMore <private>secret</private>.\n"), "Text.\n\nMore");
+    }
+}
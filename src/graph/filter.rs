@@ -0,0 +1,100 @@
+use crate::model::{Model, Topic, TopicKey};
+
+// Which topics go into a graph. All of the conditions that are set have to match. The root topic
+// and depth limit the graph to the neighborhood around one topic, where the depth is the number of
+// edges to follow in either direction. With an audience, only the topics visible to that audience
+// are included, as in a filtered build for the same audience.
+#[derive(Clone, Debug, Default)]
+pub struct GraphFilter {
+    category: Option<String>,
+    namespace: Option<String>,
+    attribute: Option<(String, String)>,
+    audience: Option<String>,
+    root_topic_name: Option<String>,
+    depth: Option<usize>,
+}
+
+impl GraphFilter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn set_category(&mut self, category: &str) {
+        self.category = Some(category.to_string());
+    }
+
+    pub fn set_namespace(&mut self, namespace: &str) {
+        self.namespace = Some(namespace.to_lowercase());
+    }
+
+    pub fn set_attribute(&mut self, attr_type_name: &str, attr_value: &str) {
+        self.attribute = Some((attr_type_name.to_string(), attr_value.to_string()));
+    }
+
+    pub fn set_audience(&mut self, audience: &str) {
+        self.audience = Some(audience.to_string());
+    }
+
+    pub fn set_root(&mut self, root_topic_name: &str, depth: usize) {
+        self.root_topic_name = Some(root_topic_name.to_string());
+        self.depth = Some(depth);
+    }
+
+    pub(crate) fn get_depth(&self) -> Option<usize> {
+        self.depth
+    }
+
+    pub(crate) fn get_root_topic_key(&self, model: &Model) -> Option<TopicKey> {
+        // The root topic can be given by its name as it appears in the wiki or in its link form.
+        self.root_topic_name.as_ref().map(|topic_name| {
+            let namespace = self.namespace.as_ref().map_or(model.get_main_namespace().to_string(), |namespace| model.qualify_namespace(namespace));
+            Model::get_corrected_topic_key(model.get_topic_refs(), &namespace, topic_name).unwrap()
+        })
+    }
+
+    pub(crate) fn is_match(&self, model: &Model, topic: &Topic) -> bool {
+        if let Some(category) = &self.category {
+            // A category topic counts as part of its own category.
+            if !topic.has_category(category) && !topic.get_name().eq(category) {
+                return false;
+            }
+        }
+        if let Some(namespace) = &self.namespace {
+            if !topic.get_namespace().eq(&model.qualify_namespace(namespace)) {
+                return false;
+            }
+        }
+        if let Some((attr_type_name, attr_value)) = &self.attribute {
+            if !topic.has_attribute_value(attr_type_name, attr_value) {
+                return false;
+            }
+        }
+        if let Some(audience) = &self.audience {
+            if !topic.is_visible_to(audience) {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub(crate) fn get_description(&self) -> String {
+        // Used in file names and graph titles, like "Category Rust, Root Cargo 2".
+        let mut parts = vec![];
+        if let Some(category) = &self.category {
+            parts.push(format!("Category {}", category));
+        }
+        if let Some(namespace) = &self.namespace {
+            parts.push(format!("Namespace {}", namespace));
+        }
+        if let Some((attr_type_name, attr_value)) = &self.attribute {
+            parts.push(format!("{} {}", attr_type_name, attr_value));
+        }
+        if let Some(audience) = &self.audience {
+            parts.push(format!("Audience {}", audience));
+        }
+        if let Some(root_topic_name) = &self.root_topic_name {
+            parts.push(format!("Root {} {}", root_topic_name, self.depth.unwrap()));
+        }
+        parts.join(", ")
+    }
+}
@@ -0,0 +1,37 @@
+use super::*;
+
+pub(crate) fn gen_dot(graph: &TopicGraph) -> String {
+    // Like:
+    //   digraph "Links" {
+    //       rankdir=LR;
+    //       node [shape=box, style=rounded];
+    //       "tools:Rust" [label="Rust"];
+    //       "tools:Rust" -> "tools:Cargo";
+    //   }
+    // Category topics are drawn as folders and private topics with dashed outlines.
+    let mut dot = format!("digraph {} {{\n", quote(graph.get_title()));
+    dot.push_str("    rankdir=LR;\n");
+    dot.push_str("    node [shape=box, style=rounded];\n");
+    for (topic_key, node) in graph.get_nodes().iter() {
+        let mut attributes = vec![format!("label={}", quote(node.get_label()))];
+        if node.is_category() {
+            attributes.push("shape=folder".to_string());
+        }
+        if !node.is_public() {
+            attributes.push("style=\"rounded,dashed\"".to_string());
+        }
+        if !node.get_categories().is_empty() {
+            attributes.push(format!("tooltip={}", quote(&node.get_categories().join(", "))));
+        }
+        dot.push_str(&format!("    {} [{}];\n", quote(&topic_key.get_display_text()), attributes.join(", ")));
+    }
+    for (from, to) in graph.get_edges().iter() {
+        dot.push_str(&format!("    {} -> {};\n", quote(&from.get_display_text()), quote(&to.get_display_text())));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace("\\", "\\\\").replace("\"", "\\\""))
+}
@@ -0,0 +1,44 @@
+use super::*;
+
+pub(crate) fn gen_graphml(graph: &TopicGraph) -> String {
+    // The node IDs are simply numbers. The topic name, primary category, all categories, and
+    // visibility are node data so that tools like Gephi and yEd can label, color, and filter the
+    // nodes.
+    let mut xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+    xml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    xml.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+    xml.push_str("  <key id=\"namespace\" for=\"node\" attr.name=\"namespace\" attr.type=\"string\"/>\n");
+    xml.push_str("  <key id=\"category\" for=\"node\" attr.name=\"category\" attr.type=\"string\"/>\n");
+    xml.push_str("  <key id=\"categories\" for=\"node\" attr.name=\"categories\" attr.type=\"string\"/>\n");
+    xml.push_str("  <key id=\"is_category\" for=\"node\" attr.name=\"is_category\" attr.type=\"boolean\"/>\n");
+    xml.push_str("  <key id=\"is_public\" for=\"node\" attr.name=\"is_public\" attr.type=\"boolean\"/>\n");
+    xml.push_str(&format!("  <graph id=\"{}\" edgedefault=\"directed\">\n", escape(graph.get_title())));
+    let mut node_ids = std::collections::BTreeMap::new();
+    for (index, (topic_key, node)) in graph.get_nodes().iter().enumerate() {
+        let node_id = format!("n{}", index);
+        xml.push_str(&format!("    <node id=\"{}\">\n", node_id));
+        xml.push_str(&format!("      <data key=\"label\">{}</data>\n", escape(node.get_label())));
+        xml.push_str(&format!("      <data key=\"namespace\">{}</data>\n", escape(topic_key.get_namespace())));
+        if let Some(category) = node.get_category() {
+            xml.push_str(&format!("      <data key=\"category\">{}</data>\n", escape(category)));
+            xml.push_str(&format!("      <data key=\"categories\">{}</data>\n", escape(&node.get_categories().join(", "))));
+        }
+        xml.push_str(&format!("      <data key=\"is_category\">{}</data>\n", node.is_category()));
+        xml.push_str(&format!("      <data key=\"is_public\">{}</data>\n", node.is_public()));
+        xml.push_str("    </node>\n");
+        node_ids.insert(topic_key, node_id);
+    }
+    for (from, to) in graph.get_edges().iter() {
+        xml.push_str(&format!("    <edge source=\"{}\" target=\"{}\"/>\n", node_ids[from], node_ids[to]));
+    }
+    xml.push_str("  </graph>\n");
+    xml.push_str("</graphml>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    text.replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+        .replace("\"", "&quot;")
+}
@@ -0,0 +1,44 @@
+use std::fs;
+use crate::model::Model;
+use crate::dokuwiki::gen_tools_wiki::PROJECT_NAME;
+use super::*;
+use super::gen_dot::gen_dot;
+use super::gen_graphml::gen_graphml;
+
+pub fn gen_graphs(filter: &GraphFilter) {
+    // Write the link, category, and subtopic graphs for the topics in the DokuWiki pages, each
+    // as both a DOT file and a GraphML file.
+    println!("\nGraph export: Start.");
+
+    let model = build_model_from_dokuwiki();
+    for graph_type in [GraphType::Links, GraphType::Categories, GraphType::Subtopics].iter() {
+        gen_graph(&model, *graph_type, filter);
+    }
+
+    println!("\nGraph export: Done.");
+}
+
+pub fn gen_graph_one_type(graph_type: GraphType, filter: &GraphFilter) {
+    let model = build_model_from_dokuwiki();
+    gen_graph(&model, graph_type, filter);
+}
+
+fn gen_graph(model: &Model, graph_type: GraphType, filter: &GraphFilter) {
+    let graph = TopicGraph::new(model, graph_type, filter);
+    graph.print_summary();
+    util::file::path_create_if_necessary_r(FOLDER_GRAPHS.to_string()).unwrap();
+    let file_name = crate::dokuwiki::legal_file_name(graph.get_title());
+    let path_dot = format!("{}/{}{}", FOLDER_GRAPHS, file_name, FILE_EXTENSION_DOT);
+    fs::write(&path_dot, gen_dot(&graph)).unwrap();
+    let path_graphml = format!("{}/{}{}", FOLDER_GRAPHS, file_name, FILE_EXTENSION_GRAPHML);
+    fs::write(&path_graphml, gen_graphml(&graph)).unwrap();
+    println!("Wrote the {} graph to \"{}\" and \"{}\".", graph.get_graph_type().get_name(), path_dot, path_graphml);
+}
+
+fn build_model_from_dokuwiki() -> Model {
+    // Nothing is written back to the DokuWiki pages, so there's no need for the file monitor.
+    // The model is complete so that the categories and subtopics are filled in.
+    let (mut model, _build_process) = crate::dokuwiki::to_model::build_model(PROJECT_NAME, &PROJECT_NAME.to_lowercase(), true, None, None, None, None, None);
+    crate::dokuwiki::to_model::complete_model(&mut model);
+    model
+}
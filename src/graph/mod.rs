@@ -0,0 +1,18 @@
+pub(crate) mod filter;
+pub use filter::*;
+
+pub(crate) mod gen_dot;
+
+pub(crate) mod gen_graphml;
+
+pub mod gen_tools_wiki;
+
+pub(crate) mod topic_graph;
+pub(crate) use topic_graph::*;
+pub use topic_graph::GraphType;
+
+// Graphs of the topics written as DOT files for Graphviz and GraphML files for tools like yEd
+// and Gephi.
+pub(crate) const FOLDER_GRAPHS: &str = "C:/Wiki Gen Backup/Graphs";
+pub(crate) const FILE_EXTENSION_DOT: &str = ".dot";
+pub(crate) const FILE_EXTENSION_GRAPHML: &str = ".graphml";
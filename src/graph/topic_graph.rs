@@ -0,0 +1,213 @@
+use crate::*;
+use crate::model::{Model, TopicKey};
+use super::GraphFilter;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GraphType {
+    // Links within the topics' paragraphs, from the linking topic to the linked topic.
+    Links,
+    // From each category topic to the topics in that category.
+    Categories,
+    // From each parent topic to its subtopics, including combination topics.
+    Subtopics,
+}
+
+#[derive(Debug)]
+pub(crate) struct TopicGraph {
+    graph_type: GraphType,
+    title: String,
+    nodes: BTreeMap<TopicKey, GraphNode>,
+    edges: Vec<(TopicKey, TopicKey)>,
+}
+
+#[derive(Debug)]
+pub(crate) struct GraphNode {
+    label: String,
+    // The primary category comes first.
+    categories: Vec<String>,
+    is_category: bool,
+    is_public: bool,
+}
+
+impl GraphType {
+    pub(crate) fn get_name(&self) -> &str {
+        match self {
+            GraphType::Links => "Links",
+            GraphType::Categories => "Categories",
+            GraphType::Subtopics => "Subtopics",
+        }
+    }
+}
+
+impl TopicGraph {
+    pub(crate) fn new(model: &Model, graph_type: GraphType, filter: &GraphFilter) -> Self {
+        let description = filter.get_description();
+        let title = if description.is_empty() { graph_type.get_name().to_string() } else { format!("{} ({})", graph_type.get_name(), description) };
+        let mut graph = Self {
+            graph_type,
+            title,
+            nodes: Default::default(),
+            edges: vec![],
+        };
+        let topic_keys = model.get_topics().values()
+            .filter(|topic| filter.is_match(model, topic))
+            .map(|topic| topic.get_topic_key())
+            .collect::<BTreeSet<_>>();
+        let edges = Self::make_edges(model, graph_type).drain(..)
+            .filter(|(from, to)| topic_keys.contains(from) && topic_keys.contains(to))
+            .collect::<Vec<_>>();
+        let topic_keys = match filter.get_root_topic_key(model) {
+            Some(root_topic_key) => Self::neighborhood(&root_topic_key, filter.get_depth().unwrap(), &edges),
+            None => topic_keys,
+        };
+        for topic_key in topic_keys.iter() {
+            let topic = model.get_topics().get(topic_key).unwrap();
+            let node = GraphNode {
+                label: topic.get_name().to_string(),
+                categories: topic.get_categories().clone(),
+                is_category: topic.is_category(),
+                is_public: topic.is_public(),
+            };
+            graph.nodes.insert(topic_key.clone(), node);
+        }
+        graph.edges = edges.into_iter()
+            .filter(|(from, to)| topic_keys.contains(from) && topic_keys.contains(to))
+            .collect();
+        graph
+    }
+
+    fn make_edges(model: &Model, graph_type: GraphType) -> Vec<(TopicKey, TopicKey)> {
+        // Use the same relationships that the category tree, subtopic tree, and inbound links
+        // are built from.
+        let mut edges = vec![];
+        for topic in model.get_topics().values() {
+            let topic_key = topic.get_topic_key();
+            match graph_type {
+                GraphType::Links => {
+                    for dest_topic_key in topic.get_referenced_topic_keys(false, false).drain(..)
+                            .filter(|dest_topic_key| !dest_topic_key.eq(&topic_key)) {
+                        edges.push((topic_key.clone(), dest_topic_key));
+                    }
+                },
+                GraphType::Categories => {
+                    // Secondary categories too, since the filter matches a topic on any of its
+                    // categories.
+                    for category_name in topic.get_categories().iter() {
+                        let category_topic_key = TopicKey::new(model.get_main_namespace(), category_name);
+                        edges.push((category_topic_key, topic_key.clone()));
+                    }
+                },
+                GraphType::Subtopics => {
+                    for index in 0..topic.get_parent_count() {
+                        let parent_topic_key = b!(&topic.get_parent(index)).get_topic_key().unwrap();
+                        edges.push((parent_topic_key, topic_key.clone()));
+                    }
+                },
+            }
+        }
+        edges.sort();
+        edges.dedup();
+        edges
+    }
+
+    fn neighborhood(root_topic_key: &TopicKey, depth: usize, edges: &Vec<(TopicKey, TopicKey)>) -> BTreeSet<TopicKey> {
+        // The topics that can be reached from the root in at most the given number of steps,
+        // following edges in either direction.
+        let mut found = BTreeSet::new();
+        found.insert(root_topic_key.clone());
+        let mut frontier = vec![root_topic_key.clone()];
+        for _ in 0..depth {
+            let mut next_frontier = vec![];
+            for topic_key in frontier.iter() {
+                for (from, to) in edges.iter() {
+                    let other = if from.eq(topic_key) { to } else if to.eq(topic_key) { from } else { continue };
+                    if found.insert(other.clone()) {
+                        next_frontier.push(other.clone());
+                    }
+                }
+            }
+            frontier = next_frontier;
+        }
+        found
+    }
+
+    pub(crate) fn get_graph_type(&self) -> GraphType {
+        self.graph_type
+    }
+
+    pub(crate) fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    pub(crate) fn get_nodes(&self) -> &BTreeMap<TopicKey, GraphNode> {
+        &self.nodes
+    }
+
+    pub(crate) fn get_edges(&self) -> &Vec<(TopicKey, TopicKey)> {
+        &self.edges
+    }
+
+    pub(crate) fn get_clusters(&self) -> Vec<Vec<TopicKey>> {
+        // The connected components of the graph, ignoring the direction of the edges, largest
+        // first. A topic with no edges is a cluster of one.
+        let mut neighbors: BTreeMap<&TopicKey, Vec<&TopicKey>> = BTreeMap::new();
+        for (from, to) in self.edges.iter() {
+            neighbors.entry(from).or_insert(vec![]).push(to);
+            neighbors.entry(to).or_insert(vec![]).push(from);
+        }
+        let mut visited = BTreeSet::new();
+        let mut clusters = vec![];
+        for topic_key in self.nodes.keys() {
+            if visited.contains(topic_key) {
+                continue;
+            }
+            let mut cluster = vec![];
+            let mut stack = vec![topic_key];
+            visited.insert(topic_key);
+            while let Some(topic_key) = stack.pop() {
+                cluster.push(topic_key.clone());
+                for neighbor in neighbors.get(topic_key).map_or(vec![], |list| list.clone()) {
+                    if visited.insert(neighbor) {
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            TopicKey::sort_topic_keys_by_name(&mut cluster);
+            clusters.push(cluster);
+        }
+        clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.len()));
+        clusters
+    }
+
+    pub(crate) fn print_summary(&self) {
+        let clusters = self.get_clusters();
+        println!("\n{}: {} topics, {} edges, {} clusters.", self.title, self.nodes.len(), self.edges.len(), clusters.len());
+        // Everything past the largest cluster is cut off from the bulk of the graph.
+        for cluster in clusters.iter().skip(1) {
+            println!("\t{}", cluster.iter().map(|topic_key| topic_key.get_topic_name()).join(", "));
+        }
+    }
+}
+
+impl GraphNode {
+    pub(crate) fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    pub(crate) fn get_category(&self) -> Option<&String> {
+        self.categories.first()
+    }
+
+    pub(crate) fn get_categories(&self) -> &Vec<String> {
+        &self.categories
+    }
+
+    pub(crate) fn is_category(&self) -> bool {
+        self.is_category
+    }
+
+    pub(crate) fn is_public(&self) -> bool {
+        self.is_public
+    }
+}
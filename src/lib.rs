@@ -4,7 +4,11 @@
 #![feature(drain_filter)]
 
 // pub(crate) mod connectedtext;
+pub mod attribute_export;
 pub mod dokuwiki;
+pub mod graph;
+pub mod markdown;
+pub mod mediawiki;
 pub mod model;
 pub mod tools_wiki;
 
@@ -1,16 +1,20 @@
 use wiki::*;
 #[allow(unused_imports)]
-use wiki::model::{make_topic_ref, NAMESPACE_TOOLS};
+use wiki::model::{make_topic_ref, NAMESPACE_TOOLS, ATTRIBUTE_VALUE_PUBLIC, DateSource};
 
 pub(crate) fn main() {
 
     let compare_only = false;
-    let filter_is_public = false;
+    let filter_audience: Option<String> = None;
     let filter_main_topic_ref = None;
+    let date_source = DateSource::FileMonitor;
 
     // let compare_only = false;
-    // let filter_is_public = false;
+    // let filter_audience = Some(ATTRIBUTE_VALUE_PUBLIC.to_string());
+    // let filter_audience = Some("Team".to_string());
+    // let filter_audience: Option<String> = None;
     // let filter_main_topic_ref = Some(make_topic_ref(NAMESPACE_TOOLS, "tempo_project"));
+    // let date_source = DateSource::Git;
 
     // connectedtext::to_dokuwiki::main();
     // connectedtext::to_model::main();
@@ -20,8 +24,34 @@ pub(crate) fn main() {
     // dokuwiki::gen_tools_wiki::gen_from_connectedtext_round_trip();
 
     util::date_time::print_elapsed(true, "round trip", "", ||
-        dokuwiki::gen_tools_wiki::dokuwiki_round_trip(compare_only, filter_is_public, filter_main_topic_ref.clone())
+        dokuwiki::gen_tools_wiki::dokuwiki_round_trip(compare_only, filter_audience.clone(), filter_main_topic_ref.clone(), date_source)
     );
     
+    // dokuwiki::gen_tools_wiki::dokuwiki_round_trip_with_markdown_import(compare_only, "C:/Wiki Import/Markdown");
+
+    // dokuwiki::gen_tools_wiki::dokuwiki_rename_topic(compare_only, "Old Topic Name", "New Topic Name", true);
+    // dokuwiki::gen_tools_wiki::dokuwiki_merge_topics(compare_only, "Rust Analyzer", "rust-analyzer", true);
+    // dokuwiki::gen_tools_wiki::dokuwiki_split_topic(compare_only, "Rust", &["Installation", "Error Handling"]);
+    // dokuwiki::gen_tools_wiki::dokuwiki_link_unlinked_mentions(compare_only);
+    // dokuwiki::gen_tools_wiki::dokuwiki_added_date_preview(date_source);
+    // dokuwiki::gen_tools_wiki::dokuwiki_estimate_added_dates(compare_only, date_source);
+    // dokuwiki::gen_tools_wiki::dokuwiki_lint();
+    // dokuwiki::gen_tools_wiki::dokuwiki_redaction_report(Some(ATTRIBUTE_VALUE_PUBLIC.to_string()), None);
+    // dokuwiki::gen_tools_wiki::dokuwiki_acl();
+    // dokuwiki::gen_tools_wiki::dokuwiki_fmt(&["Rust", "Rust Analyzer"], false);
+    // dokuwiki::gen_tools_wiki::dokuwiki_fmt(&["Rust", "Rust Analyzer"], true);
+
+    // mediawiki::gen_tools_wiki::mediawiki_round_trip(filter_audience.clone());
+    // mediawiki::gen_tools_wiki::mediawiki_export(filter_audience.clone());
+    // mediawiki::gen_tools_wiki::mediawiki_import(compare_only, "C:/Wiki Import/MediaWiki/Export.xml");
+
+    // let mut graph_filter = graph::GraphFilter::new();
+    // graph_filter.set_root("Rust", 2);
+    // graph_filter.set_audience(ATTRIBUTE_VALUE_PUBLIC);
+    // graph::gen_tools_wiki::gen_graphs(&graph_filter);
+
+    // attribute_export::gen_tools_wiki::export_attributes(Some("Books"), filter_audience.clone());
+    // attribute_export::gen_tools_wiki::export_attributes_all_categories(filter_audience.clone());
+
     // tools_wiki::project::update_coding_project_info(compare_only);
 }
@@ -0,0 +1,52 @@
+pub mod to_model;
+
+// Markdown notes as written in Obsidian and similar tools. Each note is a file like
+// [Rust Analyzer.md] where the file name is the topic name, optionally with YAML frontmatter:
+//   ---
+//   category: Tools
+//   tags: [rust, ide]
+//   added: 2021-07-24
+//   ---
+
+pub(crate) const FILE_EXTENSION_MARKDOWN: &str = ".md";
+
+pub(crate) const DELIM_FRONTMATTER: &str = "---";
+pub(crate) const DELIM_FRONTMATTER_KEY: &str = ":";
+pub(crate) const DELIM_FRONTMATTER_LIST_START: &str = "[";
+pub(crate) const DELIM_FRONTMATTER_LIST_END: &str = "]";
+pub(crate) const DELIM_FRONTMATTER_LIST_ITEM: &str = "- ";
+pub(crate) const DELIM_HEADER: &str = "#";
+pub(crate) const DELIM_TAG: char = '#';
+pub(crate) const DELIM_WIKILINK_START: &str = "[[";
+pub(crate) const DELIM_WIKILINK_END: &str = "]]";
+pub(crate) const DELIM_EMBED_START: &str = "![[";
+pub(crate) const DELIM_IMAGE_START: &str = "![";
+pub(crate) const DELIM_LINK_LABEL: &str = "|";
+pub(crate) const DELIM_LINK_SECTION: &str = "#";
+pub(crate) const DELIM_LINK_TEXT_START: &str = "[";
+pub(crate) const DELIM_LINK_TEXT_END: &str = "]";
+pub(crate) const DELIM_LINK_URL_START: &str = "(";
+pub(crate) const DELIM_LINK_URL_END: &str = ")";
+pub(crate) const DELIM_CODE_FENCE: &str = "```";
+pub(crate) const DELIM_CODE_INLINE: &str = "`";
+pub(crate) const DELIM_STRIKETHROUGH: &str = "~~";
+pub(crate) const DELIM_BOLD_UNDERSCORE: &str = "__";
+pub(crate) const DELIM_ITALIC_UNDERSCORE: &str = "_";
+pub(crate) const DELIM_QUOTE: &str = ">";
+pub(crate) const DELIM_TABLE_CELL: &str = "|";
+pub(crate) const DELIM_TABLE_ALIGN: &str = ":";
+pub(crate) const DELIM_LIST_ITEMS_UNORDERED: [&str; 3] = ["- ", "* ", "+ "];
+pub(crate) const DELIM_LIST_ITEM_ORDERED: &str = ". ";
+pub(crate) const DELIM_LIST_ITEM_DEPTH: &str = "  ";
+pub(crate) const DELIM_LIST_ITEM_DEPTH_TAB: &str = "\t";
+
+pub(crate) const FRONTMATTER_KEY_CATEGORY: &str = "category";
+pub(crate) const FRONTMATTER_KEY_CATEGORIES: &str = "categories";
+pub(crate) const FRONTMATTER_KEY_PARENT: &str = "parent";
+pub(crate) const FRONTMATTER_KEY_PARENTS: &str = "parents";
+pub(crate) const FRONTMATTER_KEY_TAGS: &str = "tags";
+// Obsidian accepts both forms.
+pub(crate) const FRONTMATTER_KEY_ALIAS: &str = "alias";
+pub(crate) const FRONTMATTER_KEY_ALIASES: &str = "aliases";
+// Obsidian-specific keys that don't have a place in the model.
+pub(crate) const FRONTMATTER_KEYS_IGNORED: [&str; 2] = ["cssclass", "publish"];
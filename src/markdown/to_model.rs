@@ -0,0 +1,831 @@
+use crate::*;
+use crate::model::*;
+use super::*;
+use std::fs;
+use std::collections::BTreeMap;
+use crate::dokuwiki::{replace_delimiter_pairs, DELIM_LINEFEED, DELIM_BOLD, DELIM_ITALIC, DELIM_MONOSPACE, DELIM_NO_FORMAT, MARKER_CODE_START_PREFIX, MARKER_CODE_END, MARKER_LINE_END, MARKER_QUOTE_START, MARKER_QUOTE_END};
+
+const IMAGE_FILE_EXTENSIONS: [&str; 6] = ["gif", "jpeg", "jpg", "png", "svg", "webp"];
+
+#[derive(Debug)]
+pub(crate) struct MarkdownImport {
+    namespace: String,
+    path_source: String,
+    source_files: BTreeMap<String, MarkdownFile>,
+    topic_refs: TopicRefs,
+    errors: TopicErrorList,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct MarkdownFile {
+    file_name: String,
+    topic_name: String,
+    frontmatter: Vec<(String, Vec<String>)>,
+    content: String,
+}
+
+impl MarkdownImport {
+    pub(crate) fn new(namespace: &str, path_source: &str) -> Self {
+        TopicKey::assert_legal_namespace(namespace);
+        Self {
+            namespace: namespace.to_string(),
+            path_source: path_source.to_string(),
+            source_files: Default::default(),
+            topic_refs: Default::default(),
+            errors: TopicErrorList::new(),
+            warnings: vec![],
+        }
+    }
+
+    pub(crate) fn import(&mut self, model: &mut Model) -> Vec<TopicKey> {
+        // Read the notes, add them to the model as topics, and return the keys of the new topics.
+        // The paragraphs end up in the same form the DokuWiki reader produces after
+        // refine_paragraphs(), so the rest of the round trip doesn't care where a topic came
+        // from.
+        self.read_from_folder();
+
+        // Add every note to the model as an empty topic first so that wikilinks between the notes
+        // resolve no matter which order the notes are parsed in.
+        let source_files = std::mem::replace(&mut self.source_files, BTreeMap::new());
+        let mut imported = vec![];
+        for md_file in source_files.values() {
+            let topic_key = TopicKey::new(&self.namespace, &md_file.topic_name);
+            let topic_ref = make_topic_ref(&self.namespace, &md_file.topic_name);
+            if model.has_topic(&topic_key) || model.get_topic_refs().contains_key(&topic_ref) {
+                // Merging a note into an existing topic is a separate, manual step.
+                self.errors.add(&topic_key, &format!("Skipping \"{}\" because the wiki already has a topic with this name.", md_file.file_name));
+            } else {
+                model.add_topic(Topic::new(&self.namespace, &md_file.topic_name));
+                imported.push((topic_key, md_file));
+            }
+        }
+        self.topic_refs = model.get_topic_refs().clone();
+
+        for (topic_key, md_file) in imported.iter() {
+            let context = format!("Importing Markdown file \"{}\".", md_file.file_name);
+            let topic = model.find_topic_mut(topic_key, &context);
+            self.fill_topic(topic, md_file);
+        }
+        imported.drain(..).map(|(topic_key, _md_file)| topic_key).collect()
+    }
+
+    fn read_from_folder(&mut self) {
+        for dir_entry_result in fs::read_dir(&self.path_source).unwrap() {
+            let dir_entry = dir_entry_result.as_ref().unwrap();
+            let file_name = util::file::dir_entry_to_file_name(dir_entry);
+            if !file_name.to_lowercase().ends_with(FILE_EXTENSION_MARKDOWN) {
+                continue;
+            }
+            let content_raw = fs::read_to_string(&dir_entry.path()).unwrap();
+
+            // As with the DokuWiki reader, get rid of whitespace at the end of lines. Notes
+            // written on Windows may also have "\r\n" line endings.
+            let content = content_raw.replace("\r\n", DELIM_LINEFEED)
+                .split(DELIM_LINEFEED)
+                .map(|line| line.trim_end())
+                .join(DELIM_LINEFEED);
+            let (frontmatter, content) = split_frontmatter(&content);
+
+            // In Obsidian the file name is the note's title, but some notes also start with a
+            // level-one header, in which case that's the better title since it isn't limited to
+            // characters that are legal in file names.
+            let content = util::parse::trim_linefeeds(&content).to_string();
+            let first_line = content.split(DELIM_LINEFEED).next().unwrap();
+            let (topic_name, content) = if first_line.starts_with(&format!("{} ", DELIM_HEADER)) {
+                let topic_name = first_line[DELIM_HEADER.len()..].trim().to_string();
+                let content = content[first_line.len()..].to_string();
+                (topic_name, content)
+            } else {
+                let topic_name = file_name[..file_name.len() - FILE_EXTENSION_MARKDOWN.len()].trim().to_string();
+                (topic_name, content)
+            };
+
+            if !TopicKey::is_legal_topic_name(&topic_name) {
+                self.warnings.push(format!("Skipping \"{}\" because \"{}\" isn't a legal topic name.", file_name, topic_name));
+                continue;
+            }
+            // Notes whose names differ only in case would end up as the same DokuWiki page.
+            let key = file_name.to_lowercase();
+            if let Some(md_file) = self.source_files.get(&key) {
+                self.warnings.push(format!("Skipping \"{}\" because \"{}\" differs from it only in case.", file_name, md_file.file_name));
+                continue;
+            }
+            self.source_files.insert(key, MarkdownFile::new(&file_name, &topic_name, frontmatter, content));
+        }
+    }
+
+    fn fill_topic(&mut self, topic: &mut Topic, md_file: &MarkdownFile) {
+        let topic_key = topic.get_topic_key();
+        let mut tags = vec![];
+        for (key, values) in md_file.frontmatter.iter() {
+            self.apply_frontmatter(topic, key, values, &mut tags);
+        }
+        for paragraph in self.make_paragraphs(&topic_key, &md_file.content, &mut tags) {
+            topic.add_paragraph(paragraph);
+        }
+
+        // Tags don't have their own place in the model. The closest thing is the Domain
+        // attribute, which is also a set of short labels that relate topics to each other.
+        tags.sort();
+        tags.dedup();
+        if !tags.is_empty() {
+            topic.add_temp_attribute_values(ATTRIBUTE_NAME_DOMAIN.to_string(), tags);
+        }
+        topic.assert_all_text_blocks_resolved();
+    }
+
+    fn apply_frontmatter(&mut self, topic: &mut Topic, key: &str, values: &Vec<String>, tags: &mut Vec<String>) {
+        let topic_key = topic.get_topic_key();
+        let values = values.iter()
+            .map(|value| wikilink_target(value))
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<_>>();
+        match key.to_lowercase().as_str() {
+            FRONTMATTER_KEY_CATEGORY | FRONTMATTER_KEY_CATEGORIES => {
+                // With several categories the first is the primary one.
+                for value in values.iter() {
+                    topic.add_category(value, false);
+                }
+            },
+            FRONTMATTER_KEY_PARENT | FRONTMATTER_KEY_PARENTS => {
+                if values.len() > 2 {
+                    self.errors.add(&topic_key, &format!("Expected one or two parents but found {}: {}.", values.len(), values.join(", ")));
+                    return;
+                }
+                let mut parents = vec![];
+                for value in values.iter() {
+                    match Model::get_corrected_topic_key(&self.topic_refs, &self.namespace, value) {
+                        Ok(parent_topic_key) => parents.push(r!(Link::new_topic_from_key(None, &parent_topic_key))),
+                        Err(msg) => self.errors.add(&topic_key, &msg),
+                    }
+                }
+                if !parents.is_empty() {
+                    topic.set_parents(parents);
+                }
+            },
+            FRONTMATTER_KEY_ALIAS | FRONTMATTER_KEY_ALIASES => {
+                // Other names for the topic, the same as the Aliases attribute.
+                if !values.is_empty() {
+                    topic.add_temp_attribute_values(ATTRIBUTE_NAME_ALIASES.to_string(), values);
+                }
+            },
+            FRONTMATTER_KEY_TAGS => {
+                tags.extend(values.iter().map(|value| value.trim_start_matches(DELIM_TAG).to_string()));
+            },
+            key if FRONTMATTER_KEYS_IGNORED.contains(&key) => {},
+            key => {
+                // Any other key has to be one of the attribute types the wiki already knows
+                // about, like "author" or "added".
+                match ATTRIBUTE_ORDER.iter().find(|attr_type_name| attr_type_name.to_lowercase().eq(key)) {
+                    Some(attr_type_name) => {
+                        if !values.is_empty() {
+                            topic.add_temp_attribute_values(attr_type_name.to_string(), values);
+                        }
+                    },
+                    None => {
+                        self.errors.add(&topic_key, &format!("No attribute type matches the frontmatter key \"{}\".", key));
+                    }
+                }
+            }
+        }
+    }
+
+    fn make_paragraphs(&mut self, topic_key: &TopicKey, content: &str, tags: &mut Vec<String>) -> Vec<Paragraph> {
+        // Break the note into blocks separated by blank lines. Fenced code is taken exactly as it
+        // is, and a header is always a paragraph of its own even if the note doesn't have blank
+        // lines around it.
+        let mut paragraphs = vec![];
+        let mut block = vec![];
+        let mut code_lines: Option<Vec<&str>> = None;
+        for line in content.split(DELIM_LINEFEED) {
+            if let Some(lines) = code_lines.as_mut() {
+                if line.trim_start().starts_with(DELIM_CODE_FENCE) {
+                    add_code_paragraphs(&mut paragraphs, lines);
+                    code_lines = None;
+                } else {
+                    lines.push(line);
+                }
+            } else if line.trim_start().starts_with(DELIM_CODE_FENCE) {
+                self.add_block(topic_key, &mut block, &mut paragraphs, tags);
+                let language = line.trim()[DELIM_CODE_FENCE.len()..].trim();
+                let marker = if language.is_empty() {
+                    format!("{}{}", MARKER_CODE_START_PREFIX, MARKER_LINE_END)
+                } else {
+                    format!("{} {}{}", MARKER_CODE_START_PREFIX, language, MARKER_LINE_END)
+                };
+                paragraphs.push(Paragraph::new_marker(&marker));
+                code_lines = Some(vec![]);
+            } else if line.trim().is_empty() {
+                self.add_block(topic_key, &mut block, &mut paragraphs, tags);
+            } else if parse_header_optional(line).is_some() {
+                self.add_block(topic_key, &mut block, &mut paragraphs, tags);
+                block.push(line);
+                self.add_block(topic_key, &mut block, &mut paragraphs, tags);
+            } else {
+                block.push(line);
+            }
+        }
+        if let Some(lines) = code_lines.as_ref() {
+            self.errors.add(topic_key, "Code block is missing its closing fence.");
+            add_code_paragraphs(&mut paragraphs, lines);
+        }
+        self.add_block(topic_key, &mut block, &mut paragraphs, tags);
+        paragraphs
+    }
+
+    fn add_block(&mut self, topic_key: &TopicKey, block: &mut Vec<&str>, paragraphs: &mut Vec<Paragraph>, tags: &mut Vec<String>) {
+        if block.is_empty() {
+            return;
+        }
+        let lines = block.drain(..).collect::<Vec<_>>();
+        if let Some((name, depth)) = parse_header_optional(lines[0]) {
+            paragraphs.push(Paragraph::new_section_header(&name, depth));
+        } else if lines.iter().all(|line| line.trim_start().starts_with(DELIM_QUOTE)) {
+            // A quote becomes the same box the DokuWiki pages use for quotes.
+            let text = lines.iter()
+                .map(|line| line.trim_start()[DELIM_QUOTE.len()..].trim())
+                .join(DELIM_LINEFEED);
+            paragraphs.push(Paragraph::new_marker(MARKER_QUOTE_START));
+            paragraphs.push(Paragraph::new_text(self.make_text_block(topic_key, &text, tags)));
+            paragraphs.push(Paragraph::new_marker(MARKER_QUOTE_END));
+        } else if let Some(table) = self.block_as_table(topic_key, &lines, tags) {
+            paragraphs.push(Paragraph::new_table(table));
+        } else if let Some(list) = self.block_as_list(topic_key, &lines, tags) {
+            paragraphs.push(Paragraph::new_list(list));
+        } else if lines.iter().all(|line| line.split_whitespace().all(|word| parse_tag_optional(word).is_some())) {
+            // A line with nothing but tags like "#rust #cli" is metadata, not text.
+            for line in lines.iter() {
+                tags.extend(line.split_whitespace().filter_map(|word| parse_tag_optional(word)));
+            }
+        } else {
+            let text = lines.join(DELIM_LINEFEED);
+            paragraphs.push(Paragraph::new_text(self.make_text_block(topic_key, &text, tags)));
+        }
+    }
+
+    fn block_as_table(&mut self, topic_key: &TopicKey, lines: &Vec<&str>, tags: &mut Vec<String>) -> Option<Table> {
+        // A Markdown table has a header row, then a separator row that also gives the alignment
+        // of each column, then the body rows:
+        //   | Name | Version |
+        //   |:-----|--------:|
+        //   | [[Rust Analyzer]] | 0.3 |
+        if lines.len() < 2 || !lines.iter().all(|line| line.trim().starts_with(DELIM_TABLE_CELL)) {
+            return None;
+        }
+        let context = format!("Markdown table in {}.", topic_key);
+        let separator = split_table_row(lines[1], &context).ok()?;
+        if !separator.iter().all(|cell| !cell.is_empty() && cell.chars().all(|c| c == '-' || c == ':')) {
+            return None;
+        }
+        let alignments = separator.iter()
+            .map(|cell| {
+                if cell.starts_with(DELIM_TABLE_ALIGN) && cell.ends_with(DELIM_TABLE_ALIGN) {
+                    HorizontalAlignment::Center
+                } else if cell.ends_with(DELIM_TABLE_ALIGN) {
+                    HorizontalAlignment::Right
+                } else {
+                    HorizontalAlignment::Left
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut table = Table::new(true);
+        for (row_index, line) in lines.iter().enumerate().filter(|(row_index, _line)| *row_index != 1) {
+            let cell_texts = match split_table_row(line, &context) {
+                Ok(cell_texts) => cell_texts,
+                Err(msg) => {
+                    self.errors.add(topic_key, &msg);
+                    return None;
+                }
+            };
+            // The header row is bold, the same as a DokuWiki row using "^".
+            let is_bold = row_index == 0;
+            let mut cells = vec![];
+            for (col_index, cell_text) in cell_texts.iter().enumerate() {
+                let horizontal = alignments.get(col_index).cloned().unwrap_or(HorizontalAlignment::Left);
+                let text_block = self.make_text_block(topic_key, cell_text, tags);
+                cells.push(TableCell::new_text_block(text_block, is_bold, &horizontal));
+            }
+            table.add_row(cells);
+        }
+        Some(table)
+    }
+
+    fn block_as_list(&mut self, topic_key: &TopicKey, lines: &Vec<&str>, tags: &mut Vec<String>) -> Option<List> {
+        // As in DokuWiki, a list may have a header line like "Tools:" followed only by list
+        // items. Without a header the list type is General.
+        let (header, item_lines) = if parse_list_item_optional(lines[0]).is_some() {
+            (None, &lines[..])
+        } else {
+            (Some(lines[0]), &lines[1..])
+        };
+        if item_lines.is_empty() {
+            return None;
+        }
+        let items = item_lines.iter()
+            .map(|line| parse_list_item_optional(line))
+            .collect::<Option<Vec<_>>>()?;
+        let mut list = match header {
+            Some(header) => {
+                let type_ = List::header_to_type(header);
+                List::new(&type_, Some(self.make_text_block(topic_key, header, tags)))
+            },
+            None => List::new(LIST_TYPE_GENERAL, None),
+        };
+        for (depth, is_ordered, text) in items.iter() {
+            let text_block = self.make_text_block(topic_key, text, tags);
+            list.add_item(ListItem::new(*depth, *is_ordered, text_block));
+        }
+        Some(list)
+    }
+
+    fn make_text_block(&mut self, topic_key: &TopicKey, text: &str, tags: &mut Vec<String>) -> TextBlock {
+        // Break the text into plain text and links. Inline code is kept as plain text and not
+        // searched for links or tags.
+        let mut items = vec![];
+        let mut plain = String::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let mut link = None;
+            let mut consumed = 0;
+            if rest.starts_with(DELIM_CODE_INLINE) {
+                if let Some((code, len)) = parse_inline_code_optional(rest) {
+                    self.push_plain_text(&mut items, &mut plain, tags);
+                    items.push(TextItem::new_text(&inline_code_to_dokuwiki(code)));
+                    rest = &rest[len..];
+                    continue;
+                }
+                // An unmatched run of backticks is plain text.
+                let len = rest.len() - rest.trim_start_matches(DELIM_CODE_INLINE).len();
+                plain.push_str(&rest[..len]);
+                rest = &rest[len..];
+                continue;
+            } else if rest.starts_with(DELIM_EMBED_START) || rest.starts_with(DELIM_WIKILINK_START) {
+                let start = if rest.starts_with(DELIM_EMBED_START) { DELIM_EMBED_START } else { DELIM_WIKILINK_START };
+                if let Some(end) = rest.find(DELIM_WIKILINK_END) {
+                    let inner = &rest[start.len()..end];
+                    consumed = end + DELIM_WIKILINK_END.len();
+                    link = if start == DELIM_EMBED_START && is_image_file_name(inner) {
+                        Some(self.make_image_link(inner))
+                    } else {
+                        self.make_wikilink(topic_key, inner)
+                    };
+                    if link.is_none() {
+                        // Show the label, or else the note name, as plain text.
+                        let (dest, label) = util::parse::split_1_or_2(inner, DELIM_LINK_LABEL);
+                        plain.push_str(label.unwrap_or(dest).trim());
+                    }
+                }
+            } else if rest.starts_with(DELIM_IMAGE_START) || rest.starts_with(DELIM_LINK_TEXT_START) {
+                let is_image = rest.starts_with(DELIM_IMAGE_START);
+                let label_start = if is_image { DELIM_IMAGE_START.len() } else { DELIM_LINK_TEXT_START.len() };
+                if let Some((label, url, len)) = parse_markdown_link_optional(&rest[label_start..]) {
+                    consumed = label_start + len;
+                    link = if is_image {
+                        Some(self.make_image_link(url))
+                    } else if Link::is_external_ref(url) {
+                        Some(Link::new_external(Some(label), url))
+                    } else {
+                        // A relative link to another note like "[Rust Analyzer](Rust%20Analyzer.md)".
+                        let note_name = url.replace("%20", " ");
+                        let note_name = note_name.trim_end_matches(FILE_EXTENSION_MARKDOWN);
+                        let link = self.make_wikilink(topic_key, &format!("{}{}{}", note_name, DELIM_LINK_LABEL, label));
+                        if link.is_none() {
+                            plain.push_str(label);
+                        }
+                        link
+                    };
+                }
+            }
+            if let Some(link) = link {
+                self.push_plain_text(&mut items, &mut plain, tags);
+                items.push(TextItem::new_link(link));
+            }
+            if consumed > 0 {
+                rest = &rest[consumed..];
+            } else {
+                let c = rest.chars().next().unwrap();
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        self.push_plain_text(&mut items, &mut plain, tags);
+        TextBlock::new_resolved(items)
+    }
+
+    fn push_plain_text(&mut self, items: &mut Vec<TextItem>, plain: &mut String, tags: &mut Vec<String>) {
+        if plain.is_empty() {
+            return;
+        }
+        let text = markdown_inline_to_dokuwiki(plain, tags);
+        items.push(TextItem::new_text(&text));
+        plain.clear();
+    }
+
+    fn make_wikilink(&mut self, topic_key: &TopicKey, inner: &str) -> Option<Link> {
+        // A wikilink might be:
+        //   [[Rust Analyzer]]
+        //   [[Rust Analyzer|the analyzer]]
+        //   [[Rust Analyzer#Settings]]
+        //   [[#Settings]] (a section in the same note)
+        //   [[Tools/Rust Analyzer]] (a note in a subfolder)
+        let (dest, label) = util::parse::split_1_or_2(inner, DELIM_LINK_LABEL);
+        let label = label.map(|label| label.trim());
+        let (note_name, section_name) = util::parse::split_1_or_2(dest, DELIM_LINK_SECTION);
+        let note_name = note_name.rsplit('/').next().unwrap().trim();
+        let link_topic_key = if note_name.is_empty() {
+            topic_key.clone()
+        } else {
+            match Model::get_corrected_topic_key(&self.topic_refs, &self.namespace, note_name) {
+                Ok(link_topic_key) => link_topic_key,
+                Err(_) => {
+                    // Obsidian allows links to notes that don't exist yet.
+                    self.warnings.push(format!("{}: link to missing note \"{}\" was imported as plain text.", topic_key, note_name));
+                    return None;
+                }
+            }
+        };
+        let link = match section_name {
+            Some(section_name) => Link::new_section(label, link_topic_key.get_namespace(), link_topic_key.get_topic_name(), section_name.trim()),
+            None => Link::new_topic_from_key(label, &link_topic_key),
+        };
+        Some(link)
+    }
+
+    fn make_image_link(&self, file_ref: &str) -> Link {
+        // Obsidian puts size options after a "|" as in "![[diagram.png|300]]". For now, as with
+        // the DokuWiki reader, assume the image link type, size, and alignment. The image files
+        // themselves still have to be copied into the media folder by hand.
+        let (file_ref, _options) = util::parse::split_1_or_2(file_ref, DELIM_LINK_LABEL);
+        let file_ref = file_ref.trim();
+        let image_source = if Link::is_external_ref(file_ref) {
+            ImageSource::new_external(file_ref)
+        } else {
+            let file_name = file_ref.rsplit('/').next().unwrap();
+            ImageSource::new_internal(&self.namespace, file_name)
+        };
+        Link::new_image(None, image_source, ImageAlignment::Left, ImageSize::Original, ImageLinkType::Direct)
+    }
+
+    fn print_warnings(&self) {
+        if !self.warnings.is_empty() {
+            println!("\nWarnings from markdown::to_model:");
+            for msg in self.warnings.iter() {
+                println!("\t{}", msg);
+            }
+        }
+    }
+}
+
+impl MarkdownFile {
+    pub(crate) fn new(file_name: &str, topic_name: &str, frontmatter: Vec<(String, Vec<String>)>, content: String) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            topic_name: topic_name.to_string(),
+            frontmatter,
+            content,
+        }
+    }
+}
+
+pub(crate) fn import_folder(model: &mut Model, path_source: &str) -> Vec<TopicKey> {
+    // Add the Markdown notes in the folder to the model's main namespace. This is meant to be
+    // called between building the model from the DokuWiki pages and completing the model, so that
+    // the new topics get linked, categorized, and written out like any other topic.
+    let namespace = model.get_main_namespace().to_string();
+    let mut import = MarkdownImport::new(&namespace, path_source);
+    let topic_keys = import.import(model);
+    println!("\nmarkdown::to_model::import_folder(): imported {} topics from \"{}\".", topic_keys.len(), path_source);
+    import.print_warnings();
+    import.errors.print(Some("markdown::to_model::import_folder()"));
+    topic_keys
+}
+
+fn split_frontmatter(content: &str) -> (Vec<(String, Vec<String>)>, String) {
+    // Frontmatter is a small block of YAML at the very top of the note. We handle only the simple
+    // forms used for note metadata:
+    //   category: Tools
+    //   tags: [rust, ide]
+    //   parents:
+    //     - "[[Rust]]"
+    let mut lines = content.split(DELIM_LINEFEED).collect::<Vec<_>>();
+    if lines.first().map_or(true, |line| line.trim() != DELIM_FRONTMATTER) {
+        return (vec![], content.to_string());
+    }
+    let end = match lines.iter().skip(1).position(|line| line.trim() == DELIM_FRONTMATTER) {
+        Some(index) => index + 1,
+        None => return (vec![], content.to_string()),
+    };
+    let body = lines.split_off(end + 1).join(DELIM_LINEFEED);
+    let mut frontmatter: Vec<(String, Vec<String>)> = vec![];
+    for line in lines[1..end].iter() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.starts_with(DELIM_FRONTMATTER_LIST_ITEM) {
+            // An item in a list under the previous key.
+            if let Some((_key, values)) = frontmatter.last_mut() {
+                values.push(unquote(&trimmed[DELIM_FRONTMATTER_LIST_ITEM.len()..]));
+            }
+        } else if trimmed.contains(DELIM_FRONTMATTER_KEY) {
+            let key = util::parse::before(trimmed, DELIM_FRONTMATTER_KEY).trim().to_string();
+            let value = unquote(util::parse::after(trimmed, DELIM_FRONTMATTER_KEY));
+            let values = if value.is_empty() {
+                vec![]
+            } else if value.starts_with(DELIM_FRONTMATTER_LIST_START) && !value.starts_with(DELIM_WIKILINK_START) && value.ends_with(DELIM_FRONTMATTER_LIST_END) {
+                let value = &value[DELIM_FRONTMATTER_LIST_START.len()..value.len() - DELIM_FRONTMATTER_LIST_END.len()];
+                value.split(",")
+                    .map(|item| unquote(item))
+                    .filter(|item| !item.is_empty())
+                    .collect()
+            } else {
+                vec![value]
+            };
+            frontmatter.push((key, values));
+        }
+    }
+    (frontmatter, body)
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').trim_matches('\'').trim().to_string()
+}
+
+fn wikilink_target(value: &str) -> String {
+    // Frontmatter values may be wikilinks like "[[Rust]]" or "[[Rust|the language]]". We want
+    // just the note name.
+    let value = value.trim();
+    if value.starts_with(DELIM_WIKILINK_START) && value.ends_with(DELIM_WIKILINK_END) {
+        let inner = &value[DELIM_WIKILINK_START.len()..value.len() - DELIM_WIKILINK_END.len()];
+        util::parse::split_1_or_2(inner, DELIM_LINK_LABEL).0.trim().to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_header_optional(line: &str) -> Option<(String, usize)> {
+    // A header is one to six "#" followed by a space, as in "## Settings". A level-one header
+    // at the top of the note was taken as the topic name, so any other level-one header is
+    // treated the same as a level-two header. The depths match the DokuWiki section headers
+    // where 0 is the page title.
+    let line = line.trim();
+    let level = line.chars().take_while(|c| *c == DELIM_TAG).count();
+    if level == 0 || level > 6 || !line[level..].starts_with(' ') {
+        return None;
+    }
+    let name = line[level..].trim().trim_end_matches(DELIM_TAG).trim();
+    if name.is_empty() {
+        return None;
+    }
+    let depth = std::cmp::min(std::cmp::max(level - 1, 1), 5);
+    Some((name.to_string(), depth))
+}
+
+fn parse_list_item_optional(line: &str) -> Option<(usize, bool, String)> {
+    // Nesting is either by tabs or by two spaces per level. DokuWiki list depths start at 1.
+    let mut level = 0;
+    let mut rest = line;
+    loop {
+        if rest.starts_with(DELIM_LIST_ITEM_DEPTH_TAB) {
+            rest = &rest[DELIM_LIST_ITEM_DEPTH_TAB.len()..];
+        } else if rest.starts_with(DELIM_LIST_ITEM_DEPTH) {
+            rest = &rest[DELIM_LIST_ITEM_DEPTH.len()..];
+        } else {
+            break;
+        }
+        level += 1;
+    }
+    let rest = rest.trim_start();
+    let depth = level + 1;
+    if let Some(delim) = DELIM_LIST_ITEMS_UNORDERED.iter().find(|delim| rest.starts_with(*delim)) {
+        return Some((depth, false, rest[delim.len()..].trim().to_string()));
+    }
+    // An ordered item is like "1. First step".
+    let digit_count = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digit_count > 0 && rest[digit_count..].starts_with(DELIM_LIST_ITEM_ORDERED) {
+        return Some((depth, true, rest[digit_count + DELIM_LIST_ITEM_ORDERED.len()..].trim().to_string()));
+    }
+    None
+}
+
+fn split_table_row(line: &str, context: &str) -> Result<Vec<String>, String> {
+    // A "|" inside a wikilink like "[[Rust Analyzer|analyzer]]" doesn't split cells. Obsidian
+    // also allows an escaped "\|" there.
+    let line = line.trim().replace("\\|", DELIM_LINK_LABEL);
+    let mut splits = util::parse::split_outside_of_delimiters_rc(&line, DELIM_TABLE_CELL, DELIM_WIKILINK_START, DELIM_WIKILINK_END, false, context)?;
+    // Ignore the empty strings outside the first and last "|".
+    if splits.len() < 2 {
+        return Err(format!("{} Expected a table row starting and ending with \"{}\": \"{}\".", context, DELIM_TABLE_CELL, line));
+    }
+    splits.remove(0);
+    splits.remove(splits.len() - 1);
+    Ok(splits.iter().map(|split| split.trim().to_string()).collect())
+}
+
+fn parse_markdown_link_optional(text: &str) -> Option<(&str, &str, usize)> {
+    // Given the part of "[label](url)" after the opening "[", return the label, the URL, and the
+    // length of everything through the closing ")".
+    let label_end = text.find(DELIM_LINK_TEXT_END)?;
+    let after_label = &text[label_end + DELIM_LINK_TEXT_END.len()..];
+    if !after_label.starts_with(DELIM_LINK_URL_START) {
+        return None;
+    }
+    let url_end = after_label.find(DELIM_LINK_URL_END)?;
+    let label = text[..label_end].trim();
+    let url = after_label[DELIM_LINK_URL_START.len()..url_end].trim();
+    if url.is_empty() {
+        return None;
+    }
+    Some((label, url, label_end + DELIM_LINK_TEXT_END.len() + url_end + DELIM_LINK_URL_END.len()))
+}
+
+fn parse_tag_optional(word: &str) -> Option<String> {
+    // A tag is like "#rust" or "#tools/ide", but not a heading marker and not something like
+    // "#1".
+    let word = word.trim_end_matches(|c: char| c == ',' || c == '.' || c == ';');
+    if !word.starts_with(DELIM_TAG) {
+        return None;
+    }
+    let tag = &word[1..];
+    if !tag.chars().next().map_or(false, |c| c.is_alphabetic())
+        || !tag.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '/') {
+        return None;
+    }
+    Some(tag.to_string())
+}
+
+fn parse_inline_code_optional(text: &str) -> Option<(&str, usize)> {
+    // Inline code starts with a run of backticks and ends with a run of the same length, so that
+    // "``a `tick` here``" can hold backticks. Returns the code and the length of everything
+    // through the closing run.
+    let fence_len = text.len() - text.trim_start_matches(DELIM_CODE_INLINE).len();
+    let fence = &text[..fence_len];
+    let mut search_start = fence_len;
+    while let Some(offset) = text[search_start..].find(fence) {
+        let start = search_start + offset;
+        let run_len = text[start..].len() - text[start..].trim_start_matches(DELIM_CODE_INLINE).len();
+        if run_len == fence_len {
+            let code = &text[fence_len..start];
+            // One space just inside each fence is padding around code that starts or ends with
+            // a backtick.
+            let code = if code.len() > 2 && code.starts_with(' ') && code.ends_with(' ') { &code[1..code.len() - 1] } else { code };
+            return Some((code, start + run_len));
+        }
+        search_start = start + run_len;
+    }
+    None
+}
+
+fn inline_code_to_dokuwiki(code: &str) -> String {
+    // Monospace in DokuWiki, with "%%" so that nothing inside is taken as markup.
+    format!("{}{}{}{}{}", DELIM_MONOSPACE, DELIM_NO_FORMAT, code, DELIM_NO_FORMAT, DELIM_MONOSPACE)
+}
+
+fn markdown_inline_to_dokuwiki(text: &str, tags: &mut Vec<String>) -> String {
+    // Tags within running text keep the word but drop the "#". Bold ("**") is the same in both
+    // markups, and "__" becomes "**". Italic with single asterisks becomes "//" as long as the
+    // asterisks are paired, as does "_" around words. Strikethrough becomes "<del>". Inline code
+    // was already taken out by make_text_block().
+    let text = text.split(' ')
+        .map(|word| match parse_tag_optional(word) {
+            Some(tag) => {
+                tags.push(tag);
+                word[1..].to_string()
+            },
+            None => word.to_string(),
+        })
+        .join(" ");
+    let text = replace_delimiter_pairs(&text, DELIM_STRIKETHROUGH, "<del>", "</del>");
+    let text = replace_underscore_pairs(&text, DELIM_BOLD_UNDERSCORE, DELIM_BOLD);
+    let text = replace_underscore_pairs(&text, DELIM_ITALIC_UNDERSCORE, DELIM_ITALIC);
+    let single_asterisk_count = text.replace(DELIM_BOLD, "").matches('*').count();
+    if single_asterisk_count == 0 || single_asterisk_count % 2 != 0 {
+        return text;
+    }
+    text.split(DELIM_BOLD)
+        .map(|part| part.replace('*', DELIM_ITALIC))
+        .join(DELIM_BOLD)
+}
+
+fn replace_underscore_pairs(text: &str, delim: &str, replacement: &str) -> String {
+    // Unlike asterisks, underscores only mark emphasis at the edges of words, so names like
+    // "file_name" are left alone. The opening delimiter has to come at the start of a word and
+    // the closing one at the end of a word.
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut result = String::new();
+    let mut rest = text;
+    let mut prev_char: Option<char> = None;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with(delim) && !prev_char.map_or(false, is_word_char) {
+            let after_open = &rest[delim.len()..];
+            if after_open.chars().next().map_or(false, |c| !c.is_whitespace() && c != '_') {
+                let close = after_open.match_indices(delim).find(|(index, _)| {
+                    let before = after_open[..*index].chars().last();
+                    let after = after_open[index + delim.len()..].chars().next();
+                    *index > 0 && before.map_or(false, |c| !c.is_whitespace() && c != '_') && !after.map_or(false, is_word_char)
+                });
+                if let Some((index, _)) = close {
+                    result.push_str(replacement);
+                    result.push_str(&after_open[..index]);
+                    result.push_str(replacement);
+                    prev_char = delim.chars().last();
+                    rest = &after_open[index + delim.len()..];
+                    continue;
+                }
+            }
+        }
+        result.push(c);
+        prev_char = Some(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+fn is_image_file_name(file_ref: &str) -> bool {
+    let file_ref = util::parse::split_1_or_2(file_ref, DELIM_LINK_LABEL).0.trim().to_lowercase();
+    IMAGE_FILE_EXTENSIONS.iter().any(|extension| file_ref.ends_with(&format!(".{}", extension)))
+}
+
+fn add_code_paragraphs(paragraphs: &mut Vec<Paragraph>, lines: &Vec<&str>) {
+    // Match what the DokuWiki reader produces for "<code>" blocks: the start marker (already
+    // added), the code as plain text with no link parsing, then the end marker.
+    if !lines.is_empty() {
+        let text_item = TextItem::new_text(&lines.join(DELIM_LINEFEED));
+        paragraphs.push(Paragraph::new_text(TextBlock::new_resolved(vec![text_item])));
+    }
+    paragraphs.push(Paragraph::new_marker(MARKER_CODE_END));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_markup_becomes_dokuwiki_markup() {
+        let mut tags = vec![];
+        let text = markdown_inline_to_dokuwiki("Use __cargo__ and _rustc_ on file_name with *care* and ~~haste~~ #rust.", &mut tags);
+        assert_eq!(text, "Use **cargo** and //rustc// on file_name with //care// and <del>haste</del> rust.");
+        assert_eq!(tags, vec!["rust".to_string()]);
+    }
+
+    #[test]
+    fn inline_code_is_monospace_without_links_or_tags() {
+        assert_eq!(parse_inline_code_optional("`a` b"), Some(("a", 3)));
+        assert_eq!(parse_inline_code_optional("``a `tick` b`` c"), Some(("a `tick` b", 14)));
+        assert_eq!(parse_inline_code_optional("`open"), None);
+
+        let mut import = MarkdownImport::new("tools", "");
+        let topic_key = TopicKey::new("tools", "Notes");
+        let mut tags = vec![];
+        let text_block = import.make_text_block(&topic_key, "Run `cargo #build [[Rust]] _x_` now.", &mut tags);
+        assert_eq!(text_block.get_display_text(), "Run ''%%cargo #build [[Rust]] _x_%%'' now.");
+        assert!(text_block.get_links().is_empty());
+        assert!(tags.is_empty());
+        assert!(import.warnings.is_empty());
+    }
+
+    #[test]
+    fn headers_and_list_items() {
+        assert_eq!(parse_header_optional("## Settings"), Some(("Settings".to_string(), 1)));
+        assert_eq!(parse_header_optional("#rust"), None);
+        assert_eq!(parse_list_item_optional("  - Nested"), Some((2, false, "Nested".to_string())));
+        assert_eq!(parse_list_item_optional("1. First"), Some((1, true, "First".to_string())));
+        assert_eq!(parse_list_item_optional("Plain text"), None);
+    }
+
+    #[test]
+    fn frontmatter_is_split_from_the_body() {
+        let (frontmatter, body) = split_frontmatter("---\ncategory: Tools\ntags: [rust, ide]\nparents:\n  - \"[[Rust]]\"\n---\nBody text.");
+        assert_eq!(frontmatter, vec![
+            ("category".to_string(), vec!["Tools".to_string()]),
+            ("tags".to_string(), vec!["rust".to_string(), "ide".to_string()]),
+            ("parents".to_string(), vec!["[[Rust]]".to_string()]),
+        ]);
+        assert_eq!(body, "Body text.");
+        assert_eq!(wikilink_target("[[Rust|the language]]"), "Rust");
+    }
+
+    #[test]
+    fn import_skips_case_duplicates_and_leaves_added_unset() {
+        let path = std::env::temp_dir().join(format!("wiki_markdown_import_{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::write(path.join("Rust.md"), "A language.\n").unwrap();
+        std::fs::write(path.join("rust.md"), "Another language.\n").unwrap();
+        std::fs::write(path.join("Cargo.md"), "# Cargo\n\nThe #build tool for [[Rust]].\n").unwrap();
+        let mut model = Model::new("Tools", "tools", None, None);
+        let mut import = MarkdownImport::new("tools", path.to_str().unwrap());
+        let topic_keys = import.import(&mut model);
+        std::fs::remove_dir_all(&path).unwrap();
+
+        assert_eq!(topic_keys.len(), 2);
+        assert_eq!(import.warnings.len(), 1);
+        assert!(import.errors.is_empty());
+        for topic_key in topic_keys.iter() {
+            assert!(!model.get_topics()[topic_key].has_temp_attribute(ATTRIBUTE_NAME_ADDED));
+        }
+        let topic = &model.get_topics()[&TopicKey::new("tools", "Cargo")];
+        assert_eq!(topic.get_links(false, false).len(), 1);
+        assert!(topic.has_temp_attribute(ATTRIBUTE_NAME_DOMAIN));
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::collections::BTreeMap;
+
+// Reading and writing the XML dump format used by Special:Export and maintenance/importDump.php.
+// We only need the title and the wikitext of the latest revision of each page, so rather than
+// pulling in an XML library this works directly on the strings. Other elements such as
+// <siteinfo> and <contributor> are ignored when reading.
+
+const DUMP_HEADER: &str = "<mediawiki xmlns=\"http://www.mediawiki.org/xml/export-0.10/\" version=\"0.10\" xml:lang=\"en\">";
+const DUMP_FOOTER: &str = "</mediawiki>";
+const TAG_PAGE_START: &str = "<page>";
+const TAG_PAGE_END: &str = "</page>";
+const TAG_TITLE_START: &str = "<title>";
+const TAG_TITLE_END: &str = "</title>";
+const TAG_TEXT_START: &str = "<text";
+const TAG_TEXT_END: &str = "</text>";
+
+pub(crate) fn write_dump(path_file: &str, pages: &BTreeMap<String, String>) {
+    // Every page goes in namespace 0. Pages from the other wiki namespaces like "Book:Rust in
+    // Action" become pseudo-namespaces, which MediaWiki handles without any extra setup.
+    let mut content = format!("{}\n", DUMP_HEADER);
+    for (title, text) in pages.iter() {
+        content.push_str(&format!("  {}\n", TAG_PAGE_START));
+        content.push_str(&format!("    {}{}{}\n", TAG_TITLE_START, escape(title), TAG_TITLE_END));
+        content.push_str("    <ns>0</ns>\n");
+        content.push_str("    <revision>\n");
+        content.push_str("      <model>wikitext</model>\n");
+        content.push_str("      <format>text/x-wiki</format>\n");
+        content.push_str(&format!("      {} xml:space=\"preserve\">{}{}\n", TAG_TEXT_START, escape(text), TAG_TEXT_END));
+        content.push_str("    </revision>\n");
+        content.push_str(&format!("  {}\n", TAG_PAGE_END));
+    }
+    content.push_str(&format!("{}\n", DUMP_FOOTER));
+    fs::write(path_file, content).unwrap();
+}
+
+pub(crate) fn read_dump(path_file: &str) -> Result<BTreeMap<String, String>, String> {
+    // Return a map of page titles to wikitext.
+    let content = fs::read_to_string(path_file).map_err(|e| format!("Unable to read \"{}\": {}", path_file, e))?;
+    let content = content.replace("\r\n", "\n");
+    let mut pages = BTreeMap::new();
+    let mut rest = content.as_str();
+    while let Some(page_start) = rest.find(TAG_PAGE_START) {
+        let page_end = rest[page_start..].find(TAG_PAGE_END)
+            .ok_or(format!("Missing \"{}\" in \"{}\".", TAG_PAGE_END, path_file))? + page_start;
+        let page = &rest[page_start + TAG_PAGE_START.len()..page_end];
+        let title = unescape(element_text(page, TAG_TITLE_START, TAG_TITLE_END)
+            .ok_or(format!("Page without a title in \"{}\".", path_file))?.trim());
+        let text = unescape(text_element(page)
+            .ok_or(format!("Page \"{}\" has no text in \"{}\".", title, path_file))?);
+        if pages.contains_key(&title) {
+            // A dump with several revisions of the same page. Keep the last one.
+            println!("mediawiki::dump::read_dump(): more than one revision of \"{}\".", title);
+        }
+        pages.insert(title, text);
+        rest = &rest[page_end + TAG_PAGE_END.len()..];
+    }
+    Ok(pages)
+}
+
+fn element_text<'a>(text: &'a str, tag_start: &str, tag_end: &str) -> Option<&'a str> {
+    let start = text.find(tag_start)? + tag_start.len();
+    let end = text[start..].find(tag_end)? + start;
+    Some(&text[start..end])
+}
+
+fn text_element(page: &str) -> Option<&str> {
+    // The <text> element has attributes and may be empty, as in:
+    //   <text xml:space="preserve">...</text>
+    //   <text bytes="0" />
+    // Take the last one in case the page has more than one revision.
+    let start = page.rfind(TAG_TEXT_START)?;
+    let tag_end = page[start..].find('>')? + start;
+    if page[..tag_end].ends_with('/') {
+        return Some("");
+    }
+    let text_start = tag_end + 1;
+    let text_end = page[text_start..].find(TAG_TEXT_END)? + text_start;
+    Some(&page[text_start..text_end])
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+        .replace("\"", "&quot;")
+}
+
+pub(crate) fn unescape(text: &str) -> String {
+    // "&amp;" has to be last so that something like "&amp;lt;" comes out as "&lt;".
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#039;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
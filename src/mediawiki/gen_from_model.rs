@@ -0,0 +1,409 @@
+use crate::*;
+use crate::model::{self, Model, Topic, TopicKey, LinkRc, TopicErrorList};
+use crate::model::glossary::Glossary;
+use crate::dokuwiki as wiki;
+use std::collections::BTreeMap;
+use super::*;
+
+// Generate MediaWiki pages from the model. This covers what a reader sees on a DokuWiki topic
+// page that comes from the topic itself: breadcrumbs, category, attributes, and paragraphs. The
+// generated navigation such as subtopic lists and the "Generated Links" section are left out
+// since MediaWiki has its own ways of showing categories and "What links here".
+
+pub(crate) struct MediaWikiGen<'a> {
+    model: &'a Model,
+    current_topic_key: Option<TopicKey>,
+    // While inside a code block this is the closing tag to use, either "</syntaxhighlight>" or
+    // "</pre>" depending on whether the block had a language.
+    code_end: Option<&'static str>,
+    errors: TopicErrorList,
+}
+
+impl <'a> MediaWikiGen<'a> {
+
+    pub(crate) fn new(model: &'a Model) -> Self {
+        Self {
+            model,
+            current_topic_key: None,
+            code_end: None,
+            errors: TopicErrorList::new(),
+        }
+    }
+
+    pub(crate) fn gen(&mut self, topic_keys: &Vec<TopicKey>) -> BTreeMap<String, String> {
+        // Return a map of page titles to wikitext.
+        let mut map = BTreeMap::new();
+        for topic_key in topic_keys.iter() {
+            let topic = self.model.get_topics().get(topic_key).unwrap();
+            self.current_topic_key = Some(topic_key.clone());
+            self.code_end = None;
+            let mut page = MediaWikiGenPage::new(&self.page_title(topic_key));
+            self.add_attributes_optional(&mut page, topic);
+            self.add_breadcrumbs_optional(&mut page, topic);
+            self.add_paragraphs(&mut page, topic, self.model.get_glossaries());
+            if self.code_end.is_some() {
+                self.add_error("Code block without an end marker.");
+            }
+            // MediaWiki has no notion of a primary category, so the primary one is simply listed
+            // first and the importer takes the first as primary.
+            for category in topic.get_categories().iter() {
+                page.add_category(category);
+            }
+            page.fix_content_before_write();
+            assert!(!map.contains_key(&page.title), "Duplicate MediaWiki page title \"{}\".", page.title);
+            map.insert(page.title, page.content);
+        }
+        self.errors.print(Some("MediaWikiGen::gen()"));
+        map
+    }
+
+    fn add_attributes_optional(&mut self, page: &mut MediaWikiGenPage, topic: &Topic) {
+        // The values are shown the same way as in the DokuWiki attribute table, with quotes
+        // around any single value that contains a comma.
+        if topic.get_attribute_count() == 0 {
+            return;
+        }
+        page.add_line(&format!("{}{}", DELIM_TEMPLATE_START, TEMPLATE_NAME_ATTRIBUTES));
+        for attr_instance in topic.get_attributes().values()
+                .sorted_by_key(|attr_instance| attr_instance.get_sequence()) {
+            let attr_type = self.model.get_attribute_type(attr_instance.get_attribute_type_name()).unwrap();
+            let value_list = attr_instance.get_values().iter()
+                .map(|value| {
+                    let label = attr_type.get_value_display_string(value).replace(DELIM_TEMPLATE_PARAM, TEMPLATE_PIPE);
+                    if label.contains(",") {
+                        format!("\"{}\"", label)
+                    } else {
+                        label
+                    }
+                })
+                .join(", ");
+            page.add_line(&format!("{} {} {} {}", DELIM_TEMPLATE_PARAM, attr_type.get_name(), DELIM_TEMPLATE_VALUE, value_list));
+        }
+        page.add_line(DELIM_TEMPLATE_END);
+        page.add_linefeed();
+    }
+
+    fn add_breadcrumbs_optional(&mut self, page: &mut MediaWikiGenPage, topic: &Topic) {
+        // Like "'''[[Rust]] => [[Cargo]] => Cargo Features'''" or, for a combination topic,
+        // "'''[[Rust]] => Rust and WebAssembly <= [[WebAssembly]]'''".
+        match topic.get_parent_count() {
+            0 => {},
+            1 => {
+                let mut topic_keys = vec![];
+                let mut parent_topic_key = b!(&topic.get_parent(0)).get_topic_key().unwrap();
+                loop {
+                    topic_keys.push(parent_topic_key.clone());
+                    // The chain stops early if a parent isn't part of this model, which can
+                    // happen when the model was built from a subset of the pages.
+                    match self.model.get_topics().get(&parent_topic_key) {
+                        Some(parent_topic) if parent_topic.get_parent_count() == 1 => {
+                            parent_topic_key = b!(&parent_topic.get_parent(0)).get_topic_key().unwrap();
+                        },
+                        _ => break,
+                    }
+                }
+                topic_keys.reverse();
+                let breadcrumbs = topic_keys.iter()
+                    .map(|topic_key| self.page_link(topic_key, None))
+                    .join(&format!(" {} ", DELIM_BREADCRUMB_RIGHT));
+                page.add_paragraph(&format!("{}{} {} {}{}", DELIM_BOLD, breadcrumbs, DELIM_BREADCRUMB_RIGHT, topic.get_name(), DELIM_BOLD));
+            },
+            2 => {
+                let link_a = self.page_link(&b!(&topic.get_parent(0)).get_topic_key().unwrap(), None);
+                let link_b = self.page_link(&b!(&topic.get_parent(1)).get_topic_key().unwrap(), None);
+                page.add_paragraph(&format!("{}{} {} {} {} {}{}", DELIM_BOLD, link_a, DELIM_BREADCRUMB_RIGHT, topic.get_name(), DELIM_BREADCRUMB_LEFT, link_b, DELIM_BOLD));
+            },
+            _ => {
+                panic!("Unexpected number of parent topics for topic \"{}\".", topic.get_name());
+            },
+        }
+    }
+
+    fn add_paragraphs(&mut self, page: &mut MediaWikiGenPage, topic: &Topic, glossaries: &BTreeMap<String, Glossary>) {
+        let msg_func_unexpected = |variant_name: &str| format!("In mediawiki::gen_from_model::add_paragraphs(), unexpected Paragraph variant = \"{}\"", variant_name);
+        for paragraph in topic.get_paragraphs().iter() {
+            match paragraph {
+                model::Paragraph::Attributes => {}, // This was already added to the page.
+                model::Paragraph::Breadcrumbs => {}, // This was already added to the page.
+                model::Paragraph::Category => {}, // This is added at the end of the page.
+                model::Paragraph::GenStart => {},
+                model::Paragraph::GenEnd => {},
+                model::Paragraph::Glossary { name } => {
+                    // There's no glossary page on the MediaWiki side, so the glossary becomes a
+                    // plain table and will come back from an import as a table.
+                    self.add_glossary(page, glossaries, name);
+                },
+                model::Paragraph::List { list} => {
+                    self.add_list(page, list);
+                },
+                model::Paragraph::Marker { text } => {
+                    let markup = self.marker_to_markup(text);
+                    page.add_paragraph(&markup);
+                },
+                model::Paragraph::Placeholder => {},
+                model::Paragraph::SectionHeader { name, depth, .. } => {
+                    page.add_headline(name, *depth);
+                }
+                model::Paragraph::Table { table} => {
+                    self.add_table(page, table);
+                }
+                model::Paragraph::Text { text_block} => {
+                    // Text inside a code block is taken as is.
+                    let markup = if self.code_end.is_some() {
+                        text_block.get_display_text()
+                    } else {
+                        self.text_block_to_markup(text_block, false)
+                    };
+                    page.add(&markup);
+                    page.end_paragraph();
+                }
+                model::Paragraph::TextUnresolved { .. } => {
+                    self.add_error(&msg_func_unexpected("TextUnresolved"));
+                }
+                model::Paragraph::Unknown { .. } => {
+                    self.add_error(&msg_func_unexpected("Unknown"));
+                }
+            }
+        }
+    }
+
+    fn marker_to_markup(&mut self, text: &str) -> String {
+        // DokuWiki markers like "<code rust>" and "<WRAP round box>" become the nearest MediaWiki
+        // tags. Anything else is passed through and will likely need attention on the MediaWiki
+        // side.
+        if text.starts_with(wiki::MARKER_CODE_START_PREFIX) {
+            let language = util::parse::between_trim(text, wiki::MARKER_CODE_START_PREFIX, wiki::MARKER_LINE_END);
+            if language.is_empty() {
+                self.code_end = Some(MARKER_PRE_END);
+                MARKER_PRE_START.to_string()
+            } else {
+                self.code_end = Some(MARKER_CODE_END);
+                format!("{}{}{}", MARKER_CODE_START_PREFIX, language, MARKER_CODE_START_SUFFIX)
+            }
+        } else if text.eq(wiki::MARKER_CODE_END) {
+            match self.code_end.take() {
+                Some(code_end) => code_end.to_string(),
+                None => {
+                    self.add_error("Code block end marker without a start marker.");
+                    MARKER_PRE_END.to_string()
+                }
+            }
+        } else if text.starts_with(wiki::MARKER_QUOTE_START_PREFIX) {
+            MARKER_QUOTE_START.to_string()
+        } else if text.eq(wiki::MARKER_QUOTE_END) {
+            MARKER_QUOTE_END.to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn text_block_to_markup(&mut self, text_block: &model::TextBlock, is_in_table: bool) -> String {
+        let mut markup = "".to_string();
+        match text_block {
+            model::TextBlock::Resolved { items} => {
+                for text_item in items.iter() {
+                    match text_item {
+                        model::TextItem::Text { text } => {
+                            let text = dokuwiki_inline_to_mediawiki(text);
+                            // Within a table cell or template a literal "|" would start a new
+                            // cell or parameter.
+                            let text = if is_in_table { text.replace(DELIM_TABLE_CELL, TEMPLATE_PIPE) } else { text };
+                            markup.push_str(&text);
+                        },
+                        model::TextItem::Link { link } => {
+                            markup.push_str(&self.link_to_markup(link));
+                        }
+                    }
+                }
+            },
+            model::TextBlock::Unresolved { text } => {
+                panic!("Text block should be resolved by this point. Text = \"{}\".", text)
+            }
+        }
+        markup
+    }
+
+    fn add_glossary(&mut self, page: &mut MediaWikiGenPage, glossaries: &BTreeMap<String, Glossary>, glossary_name: &str) {
+        let glossary = glossaries.get(glossary_name).unwrap();
+        let table = glossary.make_table(true, true, true, &None, &None, self.model.filter_audience().is_some());
+        self.add_table(page, &table);
+    }
+
+    fn add_list(&mut self, page: &mut MediaWikiGenPage, list: &model::List) {
+        if list.is_generated() {
+            return;
+        }
+        if let Some(header) = list.get_header() {
+            let markup = self.text_block_to_markup(header, false);
+            page.add_line(&markup);
+        }
+        for list_item in list.get_items().iter() {
+            let markup = self.text_block_to_markup(list_item.get_text_block(), false);
+            page.add_list_item(list_item.get_depth(), list_item.is_ordered(), &markup);
+        }
+        page.add_linefeed();
+    }
+
+    fn add_table(&mut self, page: &mut MediaWikiGenPage, table: &model::Table) {
+        // One cell per line, like:
+        //   {| class="wikitable"
+        //   ! Name
+        //   ! Version
+        //   |-
+        //   | [[Rust Analyzer]]
+        //   | style="text-align:right;" | 0.3
+        //   |}
+        page.add_line(DELIM_TABLE_START);
+        for (row_index, cells) in table.get_rows().iter().enumerate() {
+            if row_index > 0 {
+                page.add_line(DELIM_TABLE_ROW);
+            }
+            for cell in cells.iter() {
+                let delimiter = if cell.is_bold() { DELIM_TABLE_CELL_BOLD } else { DELIM_TABLE_CELL };
+                let text = self.text_block_to_markup(cell.get_text_block(), true);
+                let text = text.trim();
+                let line = match cell.get_horizontal() {
+                    model::HorizontalAlignment::Left => format!("{} {}", delimiter, text),
+                    model::HorizontalAlignment::Center => format!("{} {}{}{}", delimiter, STYLE_ALIGN_CENTER, DELIM_TABLE_CELL_STYLE, text),
+                    model::HorizontalAlignment::Right => format!("{} {}{}{}", delimiter, STYLE_ALIGN_RIGHT, DELIM_TABLE_CELL_STYLE, text),
+                };
+                page.add_line(line.trim_end());
+            }
+        }
+        page.add_line(DELIM_TABLE_END);
+        page.add_linefeed();
+    }
+
+    fn link_to_markup(&mut self, link: &LinkRc) -> String {
+        let msg_func_unexpected = |type_, variant: &str| format!("In mediawiki::gen_from_model::link_to_markup(), unexpected {} variant = \"{}\"", type_, variant);
+        let link = b!(link);
+        let label = link.get_label();
+        match link.get_type() {
+            model::LinkType::External { url } => {
+                // Like "[https://www.rust-lang.org/ Rust]". Without a label MediaWiki would show
+                // a number in brackets, so use the URL as the label.
+                format!("{}{}{}{}{}", DELIM_EXTERNAL_LINK_START, url, DELIM_EXTERNAL_LINK_LABEL, label.unwrap_or(url.to_string()), DELIM_EXTERNAL_LINK_END)
+            },
+            model::LinkType::File { file_ref } => {
+                // MediaWiki files aren't in namespaces, so keep only the file name.
+                let file_name = file_ref.rsplit(wiki::DELIM_NAMESPACE).next().unwrap();
+                internal_link(&format!("{}{}", PREFIX_MEDIA, file_name), label.as_deref())
+            },
+            model::LinkType::Image { source, alignment: _, size: _, type_: _ } => {
+                // As with DokuWiki, ignore alignment, size, and type for now.
+                match source {
+                    model::ImageSource::Internal { namespace: _, file_name } => {
+                        internal_link(&format!("{}{}", PREFIX_FILE, file_name), None)
+                    }
+                    model::ImageSource::External { url } => {
+                        // MediaWiki shows an external image if the URL is on its own, assuming
+                        // $wgAllowExternalImages is set.
+                        url.to_string()
+                    }
+                }
+            },
+            model::LinkType::InternalUnresolved { .. } => {
+                self.add_error(&msg_func_unexpected("LinkType", "InternalUnresolved"));
+                "".to_string()
+            }
+            model::LinkType::Section { section_key } => {
+                let dest = format!("{}{}{}", self.page_title(section_key.get_topic_key()), DELIM_LINK_SECTION, section_key.get_section_name());
+                internal_link(&dest, label.as_deref())
+            },
+            model::LinkType::Topic { topic_key } => {
+                self.page_link(topic_key, label.as_deref())
+            },
+        }
+    }
+
+    pub(crate) fn page_title(&self, topic_key: &TopicKey) -> String {
+        page_title(self.model.get_main_namespace(), topic_key.get_namespace(), self.model.get_topic_name(topic_key))
+    }
+
+    fn page_link(&self, topic_key: &TopicKey, label: Option<&str>) -> String {
+        internal_link(&self.page_title(topic_key), label)
+    }
+
+    fn add_error(&mut self, msg: &str) {
+        self.errors.add(&self.current_topic_key.as_ref().unwrap(),msg);
+    }
+}
+
+pub(crate) fn page_title(namespace_main: &str, namespace: &str, topic_name: &str) -> String {
+    // Topics in the main namespace use the topic name as the title. Topics in other namespaces
+    // like "tools:book" get a prefix, as in "Book:Rust in Action".
+    if namespace.eq(namespace_main) {
+        return topic_name.to_string();
+    }
+    let namespace_main_prefix = format!("{}{}", namespace_main, DELIM_NAMESPACE);
+    let namespace = namespace.strip_prefix(&namespace_main_prefix).unwrap_or(namespace);
+    let mut chars = namespace.chars();
+    let prefix = match chars.next() {
+        Some(c) => format!("{}{}", c.to_uppercase(), chars.as_str()),
+        None => "".to_string(),
+    };
+    format!("{}{}{}", prefix, DELIM_NAMESPACE, topic_name)
+}
+
+pub(crate) fn internal_link(dest: &str, label: Option<&str>) -> String {
+    // Like "[[Rust Analyzer]]" or "[[Rust Analyzer#Settings|settings]]".
+    match label {
+        Some(label) if !label.eq(dest) => format!("{}{}{}{}{}", DELIM_LINK_START, dest, DELIM_LINK_LABEL, label, DELIM_LINK_END),
+        _ => format!("{}{}{}", DELIM_LINK_START, dest, DELIM_LINK_END),
+    }
+}
+
+pub(crate) fn dokuwiki_inline_to_mediawiki(text: &str) -> String {
+    // The inline markup that differs between the two wikis:
+    //   DokuWiki      MediaWiki
+    //   **bold**      '''bold'''
+    //   //italic//    ''italic''
+    //   ''mono''      <code>mono</code>
+    //   %%raw%%       <nowiki>raw</nowiki>
+    //   \\            <br />
+    // Strikethrough with <del> is the same in both.
+    let text = text.replace(&format!("{}{}", wiki::DELIM_MONOSPACE, wiki::DELIM_NO_FORMAT), &format!("{}{}", MARKUP_CODE_START, MARKUP_NOWIKI_START))
+        .replace(&format!("{}{}", wiki::DELIM_NO_FORMAT, wiki::DELIM_MONOSPACE), &format!("{}{}", MARKUP_NOWIKI_END, MARKUP_CODE_END));
+    let text = wiki::replace_delimiter_pairs(&text, wiki::DELIM_NO_FORMAT, MARKUP_NOWIKI_START, MARKUP_NOWIKI_END);
+    // Leave anything within <nowiki> alone.
+    split_nowiki(&text).iter()
+        .map(|(is_nowiki, part)| {
+            if *is_nowiki {
+                part.to_string()
+            } else {
+                let part = wiki::replace_delimiter_pairs(part, wiki::DELIM_MONOSPACE, MARKUP_CODE_START, MARKUP_CODE_END);
+                let part = part.replace(wiki::DELIM_BOLD, DELIM_BOLD);
+                // Don't take the "//" in something like "https://" as italic.
+                let part = part.replace("://", TEMP_URL_DELIM);
+                let part = wiki::replace_delimiter_pairs(&part, wiki::DELIM_ITALIC, DELIM_ITALIC, DELIM_ITALIC);
+                let part = part.replace(TEMP_URL_DELIM, "://");
+                part.replace(&format!("{}{}", wiki::DELIM_LINE_BREAK, DELIM_LINEFEED), &format!("{}{}", MARKUP_LINE_BREAK, DELIM_LINEFEED))
+                    .replace(&format!("{} ", wiki::DELIM_LINE_BREAK), &format!("{} ", MARKUP_LINE_BREAK))
+            }
+        })
+        .join("")
+}
+
+const TEMP_URL_DELIM: &str = ":~~slash~~";
+
+pub(crate) fn split_nowiki(text: &str) -> Vec<(bool, String)> {
+    // Break the text into parts that are either inside or outside of <nowiki> tags. The parts
+    // inside keep their tags.
+    let mut parts = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find(MARKUP_NOWIKI_START) {
+        if start > 0 {
+            parts.push((false, rest[..start].to_string()));
+        }
+        let end = match rest[start..].find(MARKUP_NOWIKI_END) {
+            Some(end) => start + end + MARKUP_NOWIKI_END.len(),
+            None => rest.len(),
+        };
+        parts.push((true, rest[start..end].to_string()));
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        parts.push((false, rest.to_string()));
+    }
+    parts
+}
@@ -0,0 +1,67 @@
+use super::*;
+
+#[derive(Debug)]
+pub(crate) struct MediaWikiGenPage {
+    pub(crate) title: String,
+    pub(crate) content: String,
+}
+
+impl MediaWikiGenPage {
+    pub(crate) fn new(title: &str) -> Self {
+        // Unlike DokuWiki, MediaWiki shows the page title itself, so there's no level 0 headline.
+        Self {
+            title: title.to_string(),
+            content: "".to_string()
+        }
+    }
+
+    pub(crate) fn add_headline(&mut self, text: &str, depth: usize) {
+        // Like "== Settings ==" for a level 1 headline. A single "=" is reserved for the page
+        // title.
+        debug_assert!(depth >= 1 && depth <= 5);
+        let equal_signs = DELIM_HEADER.repeat(depth + 1);
+        self.content.push_str(&format!("{} {} {}\n\n", equal_signs, text, equal_signs));
+    }
+
+    pub(crate) fn add_category(&mut self, category_name: &str) {
+        // Like "[[Category:APIs]]". MediaWiki moves this to the bottom of the rendered page no
+        // matter where it appears, so we put it at the end of the page source as well.
+        self.content.push_str(&format!("{}{}{}{}\n\n", DELIM_LINK_START, PREFIX_CATEGORY, category_name, DELIM_LINK_END));
+    }
+
+    pub(crate) fn add_linefeed(&mut self) {
+        self.content.push_str("\n");
+    }
+
+    pub(crate) fn end_paragraph(&mut self) {
+        self.content.push_str("\n\n");
+    }
+
+    pub(crate) fn add(&mut self, text: &str) {
+        self.content.push_str(text);
+    }
+
+    pub(crate) fn add_line(&mut self, text: &str) {
+        self.content.push_str(&format!("{}\n", text));
+    }
+
+    pub(crate) fn add_list_item(&mut self, depth: usize, is_ordered: bool, text: &str) {
+        // Like "** Second level" or "# First step". The depth is the number of symbols.
+        let delimiter = if is_ordered { DELIM_LIST_ITEM_ORDERED } else { DELIM_LIST_ITEM_UNORDERED };
+        let prefix = delimiter.to_string().repeat(depth);
+        self.content.push_str(&format!("{} {}\n", prefix, text));
+    }
+
+    pub(crate) fn add_paragraph(&mut self, text: &str) {
+        self.content.push_str(&format!("{}\n\n", text));
+    }
+
+    pub(crate) fn fix_content_before_write(&mut self) {
+        self.content = util::parse::trim_linefeeds(&self.content);
+        while self.content.contains("\n\n\n") {
+            self.content = self.content.replace("\n\n\n", "\n\n");
+        }
+        self.content = self.content.replace(crate::dokuwiki::MARKER_REDACTION, MARKUP_REDACTION_FINAL);
+        self.content.push_str("\n");
+    }
+}
@@ -0,0 +1,100 @@
+use crate::model::{Model, DateSource};
+use crate::dokuwiki::gen_tools_wiki::{PROJECT_NAME, prep_round_trip, complete_round_trip};
+use std::collections::BTreeMap;
+use super::*;
+use super::gen_from_model::MediaWikiGen;
+
+pub fn mediawiki_export(filter_audience: Option<String>) {
+    // Write the topics from the DokuWiki pages to a MediaWiki dump file that can be loaded with
+    // importDump.php.
+    println!("\nMediaWiki export: Start.");
+
+    let model = build_model_from_dokuwiki(filter_audience);
+    let pages = gen_pages(&model);
+    let path_dump = write_dump_to_folder(FILE_NAME_DUMP_EXPORT, &pages);
+    println!("Wrote {} pages to \"{}\".", pages.len(), path_dump);
+
+    println!("\nMediaWiki export: Done.");
+}
+
+pub fn mediawiki_import(compare_only: bool, path_dump: &str) {
+    // Merge the pages from a MediaWiki dump into the wiki, then regenerate the DokuWiki pages so
+    // the imported pages become regular topics.
+    println!("\nDokuWiki round trip with MediaWiki import: Start.");
+
+    let (mut model, build_process) = prep_round_trip(compare_only, None, None, DateSource::FileMonitor);
+    super::to_model::import_dump(&mut model, path_dump);
+    complete_round_trip(model, build_process);
+
+    println!("\nDokuWiki round trip with MediaWiki import: Done.");
+}
+
+pub fn mediawiki_round_trip(filter_audience: Option<String>) {
+    // Generate the MediaWiki pages, write and read back the dump file, parse the pages into a new
+    // model, and generate the pages again from that model. Every page should come out exactly
+    // the same as the first time.
+    println!("\nMediaWiki round trip test: Start.");
+
+    let model = build_model_from_dokuwiki(filter_audience);
+    let pages = gen_pages(&model);
+    let path_dump = write_dump_to_folder(FILE_NAME_DUMP_EXPORT, &pages);
+
+    // Make sure nothing is lost in the XML escaping.
+    let pages_read = read_dump(&path_dump).unwrap();
+    compare_pages("Dump file", &pages, &pages_read);
+
+    let model_round_trip = super::to_model::build_model(PROJECT_NAME, model.get_main_namespace(), &pages_read);
+    let pages_round_trip = gen_pages(&model_round_trip);
+    write_dump_to_folder(FILE_NAME_DUMP_ROUND_TRIP, &pages_round_trip);
+    compare_pages("Round trip", &pages, &pages_round_trip);
+
+    println!("\nMediaWiki round trip test: Done.");
+}
+
+fn build_model_from_dokuwiki(filter_audience: Option<String>) -> Model {
+    // Nothing is written back to the DokuWiki pages, so there's no need for the file monitor.
+    let (mut model, _build_process) = crate::dokuwiki::to_model::build_model(PROJECT_NAME, &PROJECT_NAME.to_lowercase(), true, filter_audience, None, None, None, None);
+    crate::dokuwiki::to_model::complete_model(&mut model);
+    model
+}
+
+fn gen_pages(model: &Model) -> BTreeMap<String, String> {
+    let topic_keys = model.topic_keys_alphabetical_by_topic_name();
+    let mut gen = MediaWikiGen::new(model);
+    gen.gen(&topic_keys)
+}
+
+fn write_dump_to_folder(file_name: &str, pages: &BTreeMap<String, String>) -> String {
+    util::file::path_create_if_necessary_r(FOLDER_MEDIAWIKI.to_string()).unwrap();
+    let path_dump = format!("{}/{}", FOLDER_MEDIAWIKI, file_name);
+    write_dump(&path_dump, pages);
+    path_dump
+}
+
+fn compare_pages(label: &str, pages_before: &BTreeMap<String, String>, pages_after: &BTreeMap<String, String>) {
+    let mut diff_count = 0;
+    for (title, text_before) in pages_before.iter() {
+        match pages_after.get(title) {
+            Some(text_after) => {
+                if !text_before.eq(text_after) {
+                    diff_count += 1;
+                    // Show the first line that differs.
+                    let (line_before, line_after) = text_before.split(DELIM_LINEFEED)
+                        .zip(text_after.split(DELIM_LINEFEED))
+                        .find(|(line_before, line_after)| !line_before.eq(line_after))
+                        .unwrap_or(("", ""));
+                    println!("{}: \"{}\" changed:\n\tbefore: \"{}\"\n\tafter:  \"{}\"", label, title, line_before, line_after);
+                }
+            },
+            None => {
+                diff_count += 1;
+                println!("{}: \"{}\" is missing.", label, title);
+            }
+        }
+    }
+    for title in pages_after.keys().filter(|title| !pages_before.contains_key(*title)) {
+        diff_count += 1;
+        println!("{}: \"{}\" was added.", label, title);
+    }
+    println!("{}: {} of {} pages differ.", label, diff_count, pages_before.len());
+}
@@ -0,0 +1,77 @@
+pub(crate) mod dump;
+pub(crate) use dump::*;
+
+pub(crate) mod gen_from_model;
+
+pub(crate) mod gen_page;
+pub(crate) use gen_page::*;
+
+pub mod gen_tools_wiki;
+
+pub mod to_model;
+
+// MediaWiki pages are exported and imported as a single XML dump file in the same format as
+// Special:Export and importDump.php, so the result can be loaded into a stock MediaWiki install.
+pub(crate) const FOLDER_MEDIAWIKI: &str = "C:/Wiki Gen Backup/MediaWiki";
+pub(crate) const FILE_NAME_DUMP_EXPORT: &str = "Export.xml";
+pub(crate) const FILE_NAME_DUMP_ROUND_TRIP: &str = "Round Trip.xml";
+
+pub(crate) const DELIM_LINEFEED: &str = "\n";
+pub(crate) const DELIM_PARAGRAPH: &str = "\n\n";
+pub(crate) const DELIM_NAMESPACE: &str = ":";
+pub(crate) const DELIM_HEADER: &str = "=";
+pub(crate) const DELIM_BOLD: &str = "'''";
+pub(crate) const DELIM_ITALIC: &str = "''";
+pub(crate) const DELIM_BREADCRUMB_RIGHT: &str = "=>";
+pub(crate) const DELIM_BREADCRUMB_LEFT: &str = "<=";
+pub(crate) const DELIM_LINK_START: &str = "[[";
+pub(crate) const DELIM_LINK_END: &str = "]]";
+pub(crate) const DELIM_LINK_LABEL: &str = "|";
+pub(crate) const DELIM_LINK_SECTION: &str = "#";
+pub(crate) const DELIM_EXTERNAL_LINK_START: &str = "[";
+pub(crate) const DELIM_EXTERNAL_LINK_END: &str = "]";
+pub(crate) const DELIM_EXTERNAL_LINK_LABEL: &str = " ";
+pub(crate) const DELIM_LIST_ITEM_UNORDERED: char = '*';
+pub(crate) const DELIM_LIST_ITEM_ORDERED: char = '#';
+pub(crate) const DELIM_TABLE_START: &str = "{| class=\"wikitable\"";
+pub(crate) const DELIM_TABLE_END: &str = "|}";
+pub(crate) const DELIM_TABLE_ROW: &str = "|-";
+pub(crate) const DELIM_TABLE_CELL: &str = "|";
+pub(crate) const DELIM_TABLE_CELL_BOLD: &str = "!";
+pub(crate) const DELIM_TABLE_CELL_STYLE: &str = " | ";
+pub(crate) const DELIM_TEMPLATE_START: &str = "{{";
+pub(crate) const DELIM_TEMPLATE_END: &str = "}}";
+pub(crate) const DELIM_TEMPLATE_PARAM: &str = "|";
+pub(crate) const DELIM_TEMPLATE_VALUE: &str = "=";
+// A literal "|" inside a template parameter.
+pub(crate) const TEMPLATE_PIPE: &str = "{{!}}";
+
+pub(crate) const STYLE_ALIGN_CENTER: &str = "style=\"text-align:center;\"";
+pub(crate) const STYLE_ALIGN_RIGHT: &str = "style=\"text-align:right;\"";
+
+// The attributes table becomes a call to a template that has to be created on the MediaWiki
+// side, like:
+//   {{Infobox topic
+//   | Added = 2021-Jul-24
+//   | Domain = Rust, IDE
+//   }}
+pub(crate) const TEMPLATE_NAME_ATTRIBUTES: &str = "Infobox topic";
+
+pub(crate) const PREFIX_CATEGORY: &str = "Category:";
+pub(crate) const PREFIX_FILE: &str = "File:";
+pub(crate) const PREFIX_MEDIA: &str = "Media:";
+
+pub(crate) const MARKUP_CODE_START: &str = "<code>";
+pub(crate) const MARKUP_CODE_END: &str = "</code>";
+pub(crate) const MARKUP_NOWIKI_START: &str = "<nowiki>";
+pub(crate) const MARKUP_NOWIKI_END: &str = "</nowiki>";
+pub(crate) const MARKUP_LINE_BREAK: &str = "<br />";
+pub(crate) const MARKUP_REDACTION_FINAL: &str = "<nowiki>*****</nowiki>";
+
+pub(crate) const MARKER_CODE_START_PREFIX: &str = "<syntaxhighlight lang=\"";
+pub(crate) const MARKER_CODE_START_SUFFIX: &str = "\">";
+pub(crate) const MARKER_CODE_END: &str = "</syntaxhighlight>";
+pub(crate) const MARKER_PRE_START: &str = "<pre>";
+pub(crate) const MARKER_PRE_END: &str = "</pre>";
+pub(crate) const MARKER_QUOTE_START: &str = "<blockquote>";
+pub(crate) const MARKER_QUOTE_END: &str = "</blockquote>";
@@ -0,0 +1,527 @@
+use crate::*;
+use crate::model::*;
+use crate::dokuwiki as wiki;
+use super::*;
+use super::gen_from_model::split_nowiki;
+use std::collections::BTreeMap;
+
+// Parse MediaWiki pages of the kind written by gen_from_model back into topics. The paragraphs
+// end up in the same form the DokuWiki reader produces, so generating the pages again from the
+// new model should give exactly the same wikitext. That's the idempotency check in
+// gen_tools_wiki::mediawiki_round_trip().
+
+pub(crate) struct MediaWikiImport {
+    namespace_main: String,
+    topic_refs: TopicRefs,
+    errors: TopicErrorList,
+    warnings: Vec<String>,
+}
+
+impl MediaWikiImport {
+    pub(crate) fn new(namespace_main: &str) -> Self {
+        TopicKey::assert_legal_namespace(namespace_main);
+        Self {
+            namespace_main: namespace_main.to_string(),
+            topic_refs: Default::default(),
+            errors: TopicErrorList::new(),
+            warnings: vec![],
+        }
+    }
+
+    pub(crate) fn import(&mut self, model: &mut Model, pages: &BTreeMap<String, String>) -> Vec<TopicKey> {
+        // Add every page to the model as an empty topic first so that links between the pages
+        // resolve no matter which order the pages are parsed in.
+        let mut imported = vec![];
+        for (title, text) in pages.iter() {
+            let topic_key = match self.title_to_topic_key(title) {
+                Some(topic_key) => topic_key,
+                None => {
+                    self.warnings.push(format!("Skipping \"{}\" because it isn't a legal topic name.", title));
+                    continue;
+                }
+            };
+            let topic_ref = make_topic_ref(topic_key.get_namespace(), topic_key.get_topic_name());
+            if model.has_topic(&topic_key) || model.get_topic_refs().contains_key(&topic_ref) {
+                self.errors.add(&topic_key, &format!("Skipping \"{}\" because the wiki already has a topic with this name.", title));
+                continue;
+            }
+            if !model.has_namespace(topic_key.get_namespace()) {
+                model.add_namespace(topic_key.get_namespace());
+            }
+            model.add_topic(Topic::new(topic_key.get_namespace(), topic_key.get_topic_name()));
+            imported.push((topic_key, text));
+        }
+        self.topic_refs = model.get_topic_refs().clone();
+
+        for (topic_key, text) in imported.iter() {
+            let context = format!("Importing MediaWiki page \"{}\".", topic_key);
+            let topic = model.find_topic_mut(topic_key, &context);
+            self.fill_topic(topic, text);
+        }
+        imported.drain(..).map(|(topic_key, _text)| topic_key).collect()
+    }
+
+    fn title_to_topic_key(&self, title: &str) -> Option<TopicKey> {
+        // The reverse of gen_from_model::page_title(). A title like "Book:Rust in Action" is in
+        // the "book" namespace under the main namespace, but a title like "Rust: The Book" with a
+        // space after the colon is simply a topic name with a colon.
+        let (namespace, topic_name) = match util::parse::split_1_or_2(title, DELIM_NAMESPACE) {
+            (prefix, Some(topic_name)) if !prefix.is_empty()
+                    && prefix.chars().all(|c| c.is_ascii_alphabetic())
+                    && !topic_name.starts_with(' ') => {
+                (format!("{}{}{}", self.namespace_main, DELIM_NAMESPACE, prefix.to_lowercase()), topic_name.trim())
+            },
+            _ => (self.namespace_main.clone(), title.trim()),
+        };
+        TopicKey::new_if_legal(&namespace, topic_name)
+    }
+
+    fn fill_topic(&mut self, topic: &mut Topic, text: &str) {
+        let topic_key = topic.get_topic_key();
+        let text = text.replace("\r\n", DELIM_LINEFEED);
+        let mut paragraphs = vec![];
+        let mut block: Vec<&str> = vec![];
+        // When inside a code block, the closing tag and the lines so far.
+        let mut code: Option<(&str, Vec<&str>)> = None;
+        // Tables and templates are gathered whole, including any blank lines.
+        let mut table_lines: Option<Vec<&str>> = None;
+        let mut template_lines: Option<Vec<&str>> = None;
+        for line in text.split(DELIM_LINEFEED) {
+            if let Some((code_end, lines)) = code.as_mut() {
+                if line.trim().eq(*code_end) {
+                    add_code_paragraphs(&mut paragraphs, lines);
+                    code = None;
+                } else {
+                    lines.push(line);
+                }
+            } else if let Some(lines) = table_lines.as_mut() {
+                if line.trim().eq(DELIM_TABLE_END) {
+                    if let Some(table) = self.lines_to_table(&topic_key, lines) {
+                        paragraphs.push(Paragraph::new_table(table));
+                    }
+                    table_lines = None;
+                } else {
+                    lines.push(line);
+                }
+            } else if let Some(lines) = template_lines.as_mut() {
+                if line.trim().eq(DELIM_TEMPLATE_END) {
+                    self.apply_attributes(topic, lines);
+                    template_lines = None;
+                } else {
+                    lines.push(line);
+                }
+            } else if line.trim().is_empty() {
+                self.add_block(topic, &mut block, &mut paragraphs);
+            } else if line.starts_with(MARKER_CODE_START_PREFIX) || line.trim().eq(MARKER_PRE_START) {
+                self.add_block(topic, &mut block, &mut paragraphs);
+                let (marker, code_end) = if line.starts_with(MARKER_CODE_START_PREFIX) {
+                    let language = util::parse::between_trim(line, MARKER_CODE_START_PREFIX, MARKER_CODE_START_SUFFIX);
+                    (format!("{} {}{}", wiki::MARKER_CODE_START_PREFIX, language, wiki::MARKER_LINE_END), MARKER_CODE_END)
+                } else {
+                    (format!("{}{}", wiki::MARKER_CODE_START_PREFIX, wiki::MARKER_LINE_END), MARKER_PRE_END)
+                };
+                paragraphs.push(Paragraph::new_marker(&marker));
+                code = Some((code_end, vec![]));
+            } else if line.starts_with(DELIM_TABLE_START) {
+                self.add_block(topic, &mut block, &mut paragraphs);
+                table_lines = Some(vec![]);
+            } else if line.trim().eq(&format!("{}{}", DELIM_TEMPLATE_START, TEMPLATE_NAME_ATTRIBUTES)) {
+                self.add_block(topic, &mut block, &mut paragraphs);
+                template_lines = Some(vec![]);
+            } else if parse_header_optional(line).is_some() {
+                self.add_block(topic, &mut block, &mut paragraphs);
+                block.push(line);
+                self.add_block(topic, &mut block, &mut paragraphs);
+            } else {
+                block.push(line);
+            }
+        }
+        if let Some((_code_end, lines)) = code.as_ref() {
+            self.errors.add(&topic_key, "Code block is missing its end tag.");
+            add_code_paragraphs(&mut paragraphs, lines);
+        }
+        if table_lines.is_some() {
+            self.errors.add(&topic_key, "Table is missing its end.");
+        }
+        if template_lines.is_some() {
+            self.errors.add(&topic_key, "Attribute template is missing its end.");
+        }
+        self.add_block(topic, &mut block, &mut paragraphs);
+        for paragraph in paragraphs.drain(..) {
+            topic.add_paragraph(paragraph);
+        }
+        topic.assert_all_text_blocks_resolved();
+    }
+
+    fn add_block(&mut self, topic: &mut Topic, block: &mut Vec<&str>, paragraphs: &mut Vec<Paragraph>) {
+        if block.is_empty() {
+            return;
+        }
+        let topic_key = topic.get_topic_key();
+        let lines = block.drain(..).collect::<Vec<_>>();
+        let first_line = lines[0].trim();
+        if let Some((name, depth)) = parse_header_optional(first_line) {
+            paragraphs.push(Paragraph::new_section_header(&name, depth));
+        } else if lines.len() == 1 && first_line.eq(MARKER_QUOTE_START) {
+            paragraphs.push(Paragraph::new_marker(wiki::MARKER_QUOTE_START));
+        } else if lines.len() == 1 && first_line.eq(MARKER_QUOTE_END) {
+            paragraphs.push(Paragraph::new_marker(wiki::MARKER_QUOTE_END));
+        } else if lines.len() == 1 && first_line.starts_with(&format!("{}{}", DELIM_LINK_START, PREFIX_CATEGORY)) && first_line.ends_with(DELIM_LINK_END) {
+            let category = util::parse::between_trim(first_line, &format!("{}{}", DELIM_LINK_START, PREFIX_CATEGORY), DELIM_LINK_END);
+            topic.add_category(category, false);
+        } else if lines.len() == 1 && self.apply_breadcrumbs_optional(topic, first_line) {
+            // The parents were set from the breadcrumbs.
+        } else if let Some(list) = self.block_as_list(&topic_key, &lines) {
+            paragraphs.push(Paragraph::new_list(list));
+        } else {
+            let text = lines.join(DELIM_LINEFEED);
+            paragraphs.push(Paragraph::new_text(self.make_text_block(&topic_key, &text, false)));
+        }
+    }
+
+    fn apply_attributes(&mut self, topic: &mut Topic, lines: &Vec<&str>) {
+        // Each line is like "| Domain = Rust, IDE". As in the DokuWiki attribute table, a value
+        // with a comma is in quotes.
+        let topic_key = topic.get_topic_key();
+        let context = format!("Attributes for {}.", topic_key);
+        for line in lines.iter() {
+            let line = line.trim();
+            if !line.starts_with(DELIM_TEMPLATE_PARAM) || !line.contains(DELIM_TEMPLATE_VALUE) {
+                self.errors.add(&topic_key, &format!("Unexpected line in attribute template: \"{}\".", line));
+                continue;
+            }
+            let line = &line[DELIM_TEMPLATE_PARAM.len()..];
+            let (attr_type_name, text) = util::parse::split_1_or_2(line, DELIM_TEMPLATE_VALUE);
+            let attr_type_name = attr_type_name.trim().to_string();
+            let text = text.unwrap().trim();
+            let text = match util::parse::replace_within_delimiters_rc(text, "\"", "\"", ",", wiki::TEMP_COMMA, true, &context) {
+                Ok(text) => text,
+                Err(msg) => {
+                    self.errors.add(&topic_key, &msg);
+                    continue;
+                }
+            };
+            let values = util::parse::split_trim(&text, ",").iter()
+                .map(|value| value.replace(wiki::TEMP_COMMA, ",").replace(TEMPLATE_PIPE, DELIM_TEMPLATE_PARAM).trim_matches('"').trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect::<Vec<_>>();
+            if !values.is_empty() {
+                topic.add_temp_attribute_values(attr_type_name, values);
+            }
+        }
+    }
+
+    fn apply_breadcrumbs_optional(&mut self, topic: &mut Topic, line: &str) -> bool {
+        // Breadcrumbs are a bold line ending in the topic's own name, like
+        // "'''[[Rust]] => [[Cargo]] => Cargo Features'''", or for a combination topic,
+        // "'''[[Rust]] => Rust and WebAssembly <= [[WebAssembly]]'''". Only the nearest parents
+        // matter since the rest of the chain comes from the parents' own pages.
+        if line.len() < 2 * DELIM_BOLD.len() || !line.starts_with(DELIM_BOLD) || !line.ends_with(DELIM_BOLD) {
+            return false;
+        }
+        let inner = &line[DELIM_BOLD.len()..line.len() - DELIM_BOLD.len()];
+        let delim_right = format!(" {} ", DELIM_BREADCRUMB_RIGHT);
+        let delim_left = format!(" {} ", DELIM_BREADCRUMB_LEFT);
+        let parent_links = match util::parse::split_1_or_2(inner, &delim_left) {
+            (left, Some(link_b)) => {
+                let splits = left.split(&delim_right).collect::<Vec<_>>();
+                if splits.len() != 2 || !splits[1].eq(topic.get_name()) {
+                    return false;
+                }
+                vec![splits[0], link_b]
+            },
+            (_, None) => {
+                let splits = inner.split(&delim_right).collect::<Vec<_>>();
+                if splits.len() < 2 || !splits[splits.len() - 1].eq(topic.get_name()) {
+                    return false;
+                }
+                vec![splits[splits.len() - 2]]
+            },
+        };
+        let topic_key = topic.get_topic_key();
+        let mut parents = vec![];
+        for link in parent_links.iter() {
+            let link = link.trim();
+            if !link.starts_with(DELIM_LINK_START) || !link.ends_with(DELIM_LINK_END) {
+                return false;
+            }
+            let title = util::parse::between_trim(link, DELIM_LINK_START, DELIM_LINK_END);
+            match self.resolve_title(title) {
+                Some(parent_topic_key) => parents.push(r!(Link::new_topic_from_key(None, &parent_topic_key))),
+                None => self.errors.add(&topic_key, &format!("Parent topic \"{}\" not found.", title)),
+            }
+        }
+        if !parents.is_empty() {
+            topic.set_parents(parents);
+        }
+        true
+    }
+
+    fn lines_to_table(&mut self, topic_key: &TopicKey, lines: &Vec<&str>) -> Option<Table> {
+        // One cell per line, with "|-" between rows. A cell starting with "!" is bold. See
+        // gen_from_model::add_table().
+        let mut rows: Vec<Vec<TableCell>> = vec![vec![]];
+        for line in lines.iter() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.eq(DELIM_TABLE_ROW) {
+                rows.push(vec![]);
+                continue;
+            }
+            let (is_bold, cell_text) = if line.starts_with(DELIM_TABLE_CELL_BOLD) {
+                (true, &line[DELIM_TABLE_CELL_BOLD.len()..])
+            } else if line.starts_with(DELIM_TABLE_CELL) {
+                (false, &line[DELIM_TABLE_CELL.len()..])
+            } else {
+                self.errors.add(topic_key, &format!("Unexpected line in table: \"{}\".", line));
+                return None;
+            };
+            let cell_text = cell_text.trim();
+            let (horizontal, cell_text) = if cell_text.starts_with(STYLE_ALIGN_CENTER) {
+                (HorizontalAlignment::Center, util::parse::after(cell_text, DELIM_TABLE_CELL_STYLE).trim())
+            } else if cell_text.starts_with(STYLE_ALIGN_RIGHT) {
+                (HorizontalAlignment::Right, util::parse::after(cell_text, DELIM_TABLE_CELL_STYLE).trim())
+            } else {
+                (HorizontalAlignment::Left, cell_text)
+            };
+            let text_block = self.make_text_block(topic_key, cell_text, true);
+            rows.last_mut().unwrap().push(TableCell::new_text_block(text_block, is_bold, &horizontal));
+        }
+        let mut table = Table::new(false);
+        for row in rows.drain(..).filter(|row| !row.is_empty()) {
+            table.add_row(row);
+        }
+        let has_header = table.assume_has_header();
+        table.set_has_header(has_header);
+        Some(table)
+    }
+
+    fn block_as_list(&mut self, topic_key: &TopicKey, lines: &Vec<&str>) -> Option<List> {
+        // As in DokuWiki, a list may have a header line like "Tools:" followed only by list
+        // items. Without a header the list type is General.
+        let (header, item_lines) = if parse_list_item_optional(lines[0]).is_some() {
+            (None, &lines[..])
+        } else {
+            (Some(lines[0]), &lines[1..])
+        };
+        if item_lines.is_empty() {
+            return None;
+        }
+        let items = item_lines.iter()
+            .map(|line| parse_list_item_optional(line))
+            .collect::<Option<Vec<_>>>()?;
+        let mut list = match header {
+            Some(header) => {
+                let type_ = List::header_to_type(header);
+                List::new(&type_, Some(self.make_text_block(topic_key, header, false)))
+            },
+            None => List::new(LIST_TYPE_GENERAL, None),
+        };
+        for (depth, is_ordered, text) in items.iter() {
+            let text_block = self.make_text_block(topic_key, text, false);
+            list.add_item(ListItem::new(*depth, *is_ordered, text_block));
+        }
+        Some(list)
+    }
+
+    fn make_text_block(&mut self, topic_key: &TopicKey, text: &str, is_in_table: bool) -> TextBlock {
+        // Break the text into plain text and links, like:
+        //   [[Rust Analyzer]]
+        //   [[Rust Analyzer#Settings|settings]]
+        //   [[File:diagram.png]]
+        //   [[Media:notes.pdf|notes]]
+        //   [https://www.rust-lang.org/ Rust]
+        let mut items = vec![];
+        let mut plain = String::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let mut link = None;
+            let mut consumed = 0;
+            if rest.starts_with(MARKUP_NOWIKI_START) {
+                // Nothing inside <nowiki> is a link.
+                let end = rest.find(MARKUP_NOWIKI_END).map_or(rest.len(), |end| end + MARKUP_NOWIKI_END.len());
+                plain.push_str(&rest[..end]);
+                rest = &rest[end..];
+                continue;
+            } else if rest.starts_with(DELIM_LINK_START) {
+                if let Some(end) = rest.find(DELIM_LINK_END) {
+                    let inner = &rest[DELIM_LINK_START.len()..end];
+                    consumed = end + DELIM_LINK_END.len();
+                    link = self.make_internal_link(topic_key, inner);
+                    if link.is_none() {
+                        let (dest, label) = util::parse::split_1_or_2(inner, DELIM_LINK_LABEL);
+                        plain.push_str(label.unwrap_or(dest).trim());
+                    }
+                }
+            } else if rest.starts_with(DELIM_EXTERNAL_LINK_START) && Link::is_external_ref(&rest[DELIM_EXTERNAL_LINK_START.len()..]) {
+                if let Some(end) = rest.find(DELIM_EXTERNAL_LINK_END) {
+                    let inner = &rest[DELIM_EXTERNAL_LINK_START.len()..end];
+                    consumed = end + DELIM_EXTERNAL_LINK_END.len();
+                    let (url, label) = util::parse::split_1_or_2(inner, DELIM_EXTERNAL_LINK_LABEL);
+                    let label = label.map(|label| label.trim()).filter(|label| !label.eq(&url));
+                    link = Some(Link::new_external(label, url));
+                }
+            }
+            if let Some(link) = link {
+                push_plain_text(&mut items, &mut plain, is_in_table);
+                items.push(TextItem::new_link(link));
+            }
+            if consumed > 0 {
+                rest = &rest[consumed..];
+            } else {
+                let c = rest.chars().next().unwrap();
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        push_plain_text(&mut items, &mut plain, is_in_table);
+        TextBlock::new_resolved(items)
+    }
+
+    fn make_internal_link(&mut self, topic_key: &TopicKey, inner: &str) -> Option<Link> {
+        let (dest, label) = util::parse::split_1_or_2(inner, DELIM_LINK_LABEL);
+        let dest = dest.trim();
+        let label = label.map(|label| label.trim());
+        if dest.starts_with(PREFIX_FILE) {
+            // Images are assumed to be in the main namespace on the DokuWiki side.
+            let file_name = &dest[PREFIX_FILE.len()..];
+            let image_source = ImageSource::new_internal(&self.namespace_main, file_name);
+            return Some(Link::new_image(None, image_source, ImageAlignment::Left, ImageSize::Original, ImageLinkType::Direct));
+        }
+        if dest.starts_with(PREFIX_MEDIA) {
+            return Some(Link::new_file(label, &dest[PREFIX_MEDIA.len()..]));
+        }
+        let (title, section_name) = util::parse::split_1_or_2(dest, DELIM_LINK_SECTION);
+        let link_topic_key = if title.trim().is_empty() {
+            topic_key.clone()
+        } else {
+            match self.resolve_title(title) {
+                Some(link_topic_key) => link_topic_key,
+                None => {
+                    // A red link in MediaWiki terms.
+                    self.warnings.push(format!("{}: link to missing page \"{}\" was imported as plain text.", topic_key, title));
+                    return None;
+                }
+            }
+        };
+        let link = match section_name {
+            Some(section_name) => Link::new_section(label, link_topic_key.get_namespace(), link_topic_key.get_topic_name(), section_name.trim()),
+            None => Link::new_topic_from_key(label, &link_topic_key),
+        };
+        Some(link)
+    }
+
+    fn resolve_title(&self, title: &str) -> Option<TopicKey> {
+        let topic_key = self.title_to_topic_key(title)?;
+        Model::get_corrected_topic_key(&self.topic_refs, topic_key.get_namespace(), topic_key.get_topic_name()).ok()
+    }
+
+    fn print_warnings(&self) {
+        if !self.warnings.is_empty() {
+            println!("\nWarnings from mediawiki::to_model:");
+            for msg in self.warnings.iter() {
+                println!("\t{}", msg);
+            }
+        }
+    }
+}
+
+pub(crate) fn import_pages(model: &mut Model, pages: &BTreeMap<String, String>) -> Vec<TopicKey> {
+    // Add the pages to the model. As with markdown::to_model::import_folder(), this is meant to
+    // be called before the model is completed so that the new topics get linked and categorized
+    // like any other topic.
+    let namespace_main = model.get_main_namespace().to_string();
+    let mut import = MediaWikiImport::new(&namespace_main);
+    let topic_keys = import.import(model, pages);
+    println!("\nmediawiki::to_model::import_pages(): imported {} topics.", topic_keys.len());
+    import.print_warnings();
+    import.errors.print(Some("mediawiki::to_model::import_pages()"));
+    topic_keys
+}
+
+pub fn import_dump(model: &mut Model, path_file: &str) -> Vec<TopicKey> {
+    let pages = read_dump(path_file).unwrap();
+    import_pages(model, &pages)
+}
+
+pub(crate) fn build_model(name: &str, namespace_main: &str, pages: &BTreeMap<String, String>) -> Model {
+    // Create a model holding only the given pages. This is used by the round trip check, which
+    // doesn't need the links to be cataloged, only the attributes.
+    let mut model = Model::new(name, namespace_main, None, None);
+    import_pages(&mut model, pages);
+    let errors = model.catalog_attributes();
+    errors.print(Some("mediawiki::to_model::build_model(): model.catalog_attributes()"));
+    model
+}
+
+fn push_plain_text(items: &mut Vec<TextItem>, plain: &mut String, is_in_table: bool) {
+    if plain.is_empty() {
+        return;
+    }
+    let text = mediawiki_inline_to_dokuwiki(plain);
+    let text = if is_in_table { text.replace(TEMPLATE_PIPE, DELIM_TABLE_CELL) } else { text };
+    items.push(TextItem::new_text(&text));
+    plain.clear();
+}
+
+pub(crate) fn mediawiki_inline_to_dokuwiki(text: &str) -> String {
+    // The reverse of gen_from_model::dokuwiki_inline_to_mediawiki(). Bold has to come before
+    // italic since "'''" contains "''", and <code> has to come after italic since it becomes "''"
+    // in DokuWiki.
+    split_nowiki(text).iter()
+        .map(|(is_nowiki, part)| {
+            if *is_nowiki {
+                part.replace(MARKUP_NOWIKI_START, wiki::DELIM_NO_FORMAT).replace(MARKUP_NOWIKI_END, wiki::DELIM_NO_FORMAT)
+            } else {
+                let part = wiki::replace_delimiter_pairs(part, DELIM_BOLD, wiki::DELIM_BOLD, wiki::DELIM_BOLD);
+                let part = wiki::replace_delimiter_pairs(&part, DELIM_ITALIC, wiki::DELIM_ITALIC, wiki::DELIM_ITALIC);
+                part.replace(MARKUP_CODE_START, wiki::DELIM_MONOSPACE)
+                    .replace(MARKUP_CODE_END, wiki::DELIM_MONOSPACE)
+                    .replace(MARKUP_LINE_BREAK, wiki::DELIM_LINE_BREAK)
+            }
+        })
+        .join("")
+}
+
+fn parse_header_optional(line: &str) -> Option<(String, usize)> {
+    // Like "== Settings ==" for a level 1 section. The depths match the DokuWiki section headers
+    // where 0 is the page title.
+    let line = line.trim();
+    let level = line.chars().take_while(|c| c.to_string().eq(DELIM_HEADER)).count();
+    if level < 2 || level > 6 || !line.ends_with(&DELIM_HEADER.repeat(level)) || line.len() <= 2 * level {
+        return None;
+    }
+    let name = line[level..line.len() - level].trim();
+    if name.is_empty() {
+        return None;
+    }
+    Some((name.to_string(), level - 1))
+}
+
+fn parse_list_item_optional(line: &str) -> Option<(usize, bool, String)> {
+    // Like "** Second level" or "# First step". The depth is the number of symbols and the last
+    // symbol says whether the item itself is ordered.
+    let prefix = line.chars().take_while(|c| *c == DELIM_LIST_ITEM_UNORDERED || *c == DELIM_LIST_ITEM_ORDERED).collect::<String>();
+    if prefix.is_empty() || !line[prefix.len()..].starts_with(' ') {
+        return None;
+    }
+    let is_ordered = prefix.ends_with(DELIM_LIST_ITEM_ORDERED);
+    Some((prefix.len(), is_ordered, line[prefix.len()..].trim().to_string()))
+}
+
+fn add_code_paragraphs(paragraphs: &mut Vec<Paragraph>, lines: &Vec<&str>) {
+    // Match what the DokuWiki reader produces for "<code>" blocks: the start marker (already
+    // added), the code as plain text split on blank lines the same way the DokuWiki page is split
+    // into paragraphs, then the end marker.
+    let code = lines.join(DELIM_LINEFEED);
+    for text in code.split(DELIM_PARAGRAPH) {
+        let text = util::parse::trim_linefeeds(text);
+        if !text.is_empty() {
+            let text_item = TextItem::new_text(&text);
+            paragraphs.push(Paragraph::new_text(TextBlock::new_resolved(vec![text_item])));
+        }
+    }
+    paragraphs.push(Paragraph::new_marker(wiki::MARKER_CODE_END));
+}
@@ -123,6 +123,18 @@ impl AttributeList {
         self.attribute_values = attribute_values;
     }
 
+    pub(crate) fn remove_topic(&mut self, topic_key: &TopicKey) {
+        // Drop every reference to a topic that's leaving the model, such as one that has been
+        // renamed or merged into another topic. Values that no other topic has go away too.
+        for attribute_type in self.attribute_types.values_mut() {
+            attribute_type.remove_topic(topic_key);
+        }
+        for topic_list in self.attribute_values.values_mut() {
+            topic_list.retain(|(list_topic_key, _attribute_type_name)| list_topic_key.ne(topic_key));
+        }
+        self.attribute_values.retain(|_value, topic_list| !topic_list.is_empty());
+    }
+
     pub(crate) fn take_types_and_values(&mut self) -> (BTreeMap<String, AttributeType>, BTreeMap<String, Vec<(TopicKey, String)>>) {
         (std::mem::replace(&mut self.attribute_types, BTreeMap::new()), std::mem::replace(&mut self.attribute_values, BTreeMap::new()))
     }
@@ -194,6 +206,13 @@ impl AttributeType {
         });
     }
 
+    pub(crate) fn remove_topic(&mut self, topic_key: &TopicKey) {
+        for topic_keys in self.values.values_mut() {
+            topic_keys.retain(|value_topic_key| value_topic_key.ne(topic_key));
+        }
+        self.values.retain(|_value, topic_keys| !topic_keys.is_empty());
+    }
+
     pub(crate) fn add_value_for_topic(&mut self, value: &str, topic_key: &TopicKey) -> Result<String, String> {
         // If this attribute type does not have the value, add it. Then either way add a reference
         // to the topic, showing that this topic has this value for this attribute type.
@@ -0,0 +1,354 @@
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+
+use crate::*;
+use crate::model::{Model, Topic, TopicKey, Paragraph, TextBlock, TextItem, Link};
+use crate::dokuwiki::{DELIM_MONOSPACE, DELIM_NO_FORMAT, MARKER_CODE_START_PREFIX, MARKER_CODE_END};
+
+// Very short topic names like "C", "R", or "Go" turn up inside ordinary sentences far more often
+// than they're meant as the topic, so they're left out.
+const AUTO_LINK_MIN_NAME_LENGTH: usize = 3;
+
+#[derive(Clone, Debug)]
+pub(crate) struct UnlinkedMention {
+    topic_key: TopicKey,
+    mentioned_topic_key: TopicKey,
+    first_text: String,
+    count: usize,
+}
+
+struct TopicNameMatcher {
+    // The lowercase topic names, longest first so that "Rust Analyzer" wins over "Rust" when both
+    // start at the same place. The topic keys are in the same order as the automaton's patterns.
+    automaton: AhoCorasick,
+    topic_keys: Vec<TopicKey>,
+}
+
+struct TopicScan<'a> {
+    matcher: &'a TopicNameMatcher,
+    topic_key: TopicKey,
+    excluded_topic_keys: Vec<TopicKey>,
+    mentions: BTreeMap<TopicKey, UnlinkedMention>,
+    rewrite: bool,
+}
+
+impl UnlinkedMention {
+    pub(crate) fn get_topic_key(&self) -> &TopicKey {
+        &self.topic_key
+    }
+
+    pub(crate) fn get_mentioned_topic_key(&self) -> &TopicKey {
+        &self.mentioned_topic_key
+    }
+
+    pub(crate) fn get_first_text(&self) -> &str {
+        &self.first_text
+    }
+
+    pub(crate) fn get_count(&self) -> usize {
+        self.count
+    }
+}
+
+pub(crate) fn find_unlinked_mentions(model: &Model) -> Vec<UnlinkedMention> {
+    // Find places where a topic's text names another topic without linking to it. Each pair of
+    // topic and mentioned topic appears once along with the number of mentions.
+    let matcher = TopicNameMatcher::new(model);
+    let mut mentions = vec![];
+    for topic in model.get_topics().values() {
+        let mut scan = TopicScan::new(&matcher, topic, false);
+        for paragraph_index in scanned_paragraph_indexes(topic).into_iter() {
+            scan.scan_paragraph(&topic.get_paragraphs()[paragraph_index]);
+        }
+        mentions.append(&mut scan.mentions.into_iter().map(|(_, mention)| mention).collect());
+    }
+    mentions
+}
+
+pub(crate) fn link_unlinked_mentions(model: &mut Model) -> Vec<UnlinkedMention> {
+    // The opt-in rewrite. The first unlinked mention of each topic on a page becomes a link to
+    // that topic, keeping the text as it was written as the label. Later mentions are left alone
+    // the same way they would be if the page had been written with the link.
+    let context = "link_unlinked_mentions()";
+    let matcher = TopicNameMatcher::new(model);
+    let mut mentions = vec![];
+    let topic_keys = model.get_topics().keys().cloned().collect::<Vec<_>>();
+    for topic_key in topic_keys.iter() {
+        let topic = model.find_topic_mut(topic_key, context);
+        let mut scan = TopicScan::new(&matcher, topic, true);
+        let mut replacements = vec![];
+        for paragraph_index in scanned_paragraph_indexes(topic).into_iter() {
+            if let Some(paragraph_new) = scan.scan_paragraph(&topic.get_paragraphs()[paragraph_index]) {
+                replacements.push((paragraph_index, paragraph_new));
+            }
+        }
+        for (paragraph_index, paragraph_new) in replacements.into_iter() {
+            topic.replace_paragraph(paragraph_index, paragraph_new);
+        }
+        mentions.append(&mut scan.mentions.into_iter().map(|(_, mention)| mention).collect());
+    }
+    println!("Linked the first unlinked mention in {} cases.", mentions.len());
+    mentions
+}
+
+fn scanned_paragraph_indexes(topic: &Topic) -> Vec<usize> {
+    // Skip code blocks, which are text paragraphs between "<code>" and "</code>" markers, and
+    // anything between the generated markers.
+    let mut paragraph_indexes = vec![];
+    let mut is_in_code = false;
+    let mut is_generated = false;
+    for (paragraph_index, paragraph) in topic.get_paragraphs().iter().enumerate() {
+        match paragraph {
+            Paragraph::Marker { text } if text.trim().starts_with(MARKER_CODE_START_PREFIX) => is_in_code = true,
+            Paragraph::Marker { text } if text.trim().eq(MARKER_CODE_END) => is_in_code = false,
+            Paragraph::GenStart => is_generated = true,
+            Paragraph::GenEnd => is_generated = false,
+            _ => {
+                if !is_in_code && !is_generated {
+                    paragraph_indexes.push(paragraph_index);
+                }
+            },
+        }
+    }
+    paragraph_indexes
+}
+
+impl TopicNameMatcher {
+    fn new(model: &Model) -> Self {
+        let mut names = model.get_topics().keys()
+            .map(|topic_key| (topic_key.get_topic_name().chars().map(lowercase_char).collect::<String>(), topic_key.clone()))
+            .filter(|(name, _)| name.chars().count() >= AUTO_LINK_MIN_NAME_LENGTH)
+            .collect::<Vec<_>>();
+        names.sort_by(|a, b| b.0.chars().count().cmp(&a.0.chars().count()).then(a.1.cmp(&b.1)));
+        // Overlapping matches are only reported with the standard match kind. The choice between
+        // overlapping names is made afterward in find_matches().
+        let automaton = AhoCorasickBuilder::new()
+            .match_kind(MatchKind::Standard)
+            .build(names.iter().map(|(name, _)| name));
+        Self {
+            automaton,
+            topic_keys: names.into_iter().map(|(_, topic_key)| topic_key).collect(),
+        }
+    }
+
+    fn find_matches(&self, text: &str) -> Vec<(usize, usize, TopicKey)> {
+        // Case-insensitive matches that start and end on word boundaries, as character ranges.
+        // Inline code and unformatted text are skipped, as are URLs. Lowercasing is done one
+        // character at a time so that character positions in the lowercase text are the same as
+        // in the original.
+        let chars = text.chars().collect::<Vec<_>>();
+        let text_lower = chars.iter().map(|c| lowercase_char(*c)).collect::<String>();
+        let mut char_indexes = vec![0; text_lower.len() + 1];
+        for (char_index, (byte_index, _)) in text_lower.char_indices().enumerate() {
+            char_indexes[byte_index] = char_index;
+        }
+        char_indexes[text_lower.len()] = chars.len();
+        let protected = protected_ranges(&chars);
+        let mut candidates = self.automaton.find_overlapping_iter(&text_lower)
+            .map(|found| (char_indexes[found.start()], char_indexes[found.end()], found.pattern()))
+            .filter(|(start, end, _)| {
+                is_word_char(chars[*start])
+                    && (*start == 0 || !is_word_char(chars[*start - 1]))
+                    && (*end == chars.len() || !is_word_char(chars[*end]))
+                    && !protected.iter().any(|(protected_start, protected_end)| *start >= *protected_start && *start < *protected_end)
+                    && !protected.iter().any(|(protected_start, protected_end)| *end > *protected_start && *end <= *protected_end)
+            })
+            .collect::<Vec<_>>();
+        // Going left to right, take the longest name at each place and skip past it. Patterns are
+        // numbered longest first.
+        candidates.sort_by(|a, b| a.0.cmp(&b.0).then(a.2.cmp(&b.2)));
+        let mut matches = vec![];
+        let mut end_previous = 0;
+        for (start, end, pattern_index) in candidates.into_iter() {
+            if start >= end_previous {
+                matches.push((start, end, self.topic_keys[pattern_index].clone()));
+                end_previous = end;
+            }
+        }
+        matches
+    }
+}
+
+impl <'a> TopicScan<'a> {
+    fn new(matcher: &'a TopicNameMatcher, topic: &Topic, rewrite: bool) -> Self {
+        // A topic doesn't need a link to itself, to its parents, which are already linked in the
+        // breadcrumbs, or to topics it links to somewhere else on the page.
+        let mut excluded_topic_keys = topic.get_referenced_topic_keys(true, false);
+        excluded_topic_keys.push(topic.get_topic_key());
+        for index in 0..topic.get_parent_count() {
+            if let Some(topic_key) = b!(&topic.get_parent(index)).get_topic_key() {
+                excluded_topic_keys.push(topic_key);
+            }
+        }
+        Self {
+            matcher,
+            topic_key: topic.get_topic_key(),
+            excluded_topic_keys,
+            mentions: BTreeMap::new(),
+            rewrite,
+        }
+    }
+
+    fn scan_paragraph(&mut self, paragraph: &Paragraph) -> Option<Paragraph> {
+        // Returns the changed paragraph if any links were added.
+        match paragraph {
+            Paragraph::Text { text_block } => {
+                self.scan_text_block(text_block).map(|text_block| Paragraph::new_text(text_block))
+            },
+            Paragraph::List { list } => {
+                let mut list = list.clone();
+                let mut is_changed = false;
+                if let Some(text_block) = list.get_header().clone() {
+                    if let Some(text_block) = self.scan_text_block(&text_block) {
+                        list.set_header(Some(text_block));
+                        is_changed = true;
+                    }
+                }
+                for index in 0..list.get_items().len() {
+                    let text_block = list.get_items()[index].get_text_block().clone();
+                    if let Some(text_block) = self.scan_text_block(&text_block) {
+                        list.set_item_text_block(index, text_block);
+                        is_changed = true;
+                    }
+                }
+                if is_changed { Some(Paragraph::new_list(list)) } else { None }
+            },
+            Paragraph::Table { table } => {
+                let mut table = table.clone();
+                let mut is_changed = false;
+                for row in table.get_rows_mut().iter_mut() {
+                    for cell in row.iter_mut() {
+                        let text_block = cell.get_text_block().clone();
+                        if let Some(text_block) = self.scan_text_block(&text_block) {
+                            cell.set_text_block(text_block);
+                            is_changed = true;
+                        }
+                    }
+                }
+                if is_changed { Some(Paragraph::new_table(table)) } else { None }
+            },
+            _ => None,
+        }
+    }
+
+    fn scan_text_block(&mut self, text_block: &TextBlock) -> Option<TextBlock> {
+        let items = match text_block {
+            TextBlock::Resolved { items } => items,
+            TextBlock::Unresolved { .. } => return None,
+        };
+        let mut items_new = vec![];
+        let mut is_changed = false;
+        for item in items.iter() {
+            let text = match item {
+                TextItem::Text { text } => text,
+                TextItem::Link { .. } => {
+                    items_new.push(item.clone());
+                    continue;
+                }
+            };
+            let chars = text.chars().collect::<Vec<_>>();
+            let mut start_remaining = 0;
+            for (start, end, topic_key) in self.matcher.find_matches(text).into_iter() {
+                if self.excluded_topic_keys.contains(&topic_key) {
+                    continue;
+                }
+                let matched_text = chars[start..end].iter().collect::<String>();
+                let is_first = !self.mentions.contains_key(&topic_key);
+                let mention = self.mentions.entry(topic_key.clone()).or_insert(UnlinkedMention {
+                    topic_key: self.topic_key.clone(),
+                    mentioned_topic_key: topic_key.clone(),
+                    first_text: matched_text.clone(),
+                    count: 0,
+                });
+                mention.count += 1;
+                if self.rewrite && is_first {
+                    // The pieces of text around the new link may begin or end with a linefeed
+                    // from the middle of the original paragraph, so they're built directly
+                    // rather than through TextItem::new_text().
+                    if start > start_remaining {
+                        items_new.push(TextItem::Text { text: chars[start_remaining..start].iter().collect() });
+                    }
+                    items_new.push(TextItem::new_link(Link::new_topic_from_key(Some(&matched_text), &topic_key)));
+                    start_remaining = end;
+                    is_changed = true;
+                }
+            }
+            if start_remaining == 0 {
+                items_new.push(item.clone());
+            } else if start_remaining < chars.len() {
+                items_new.push(TextItem::Text { text: chars[start_remaining..].iter().collect() });
+            }
+        }
+        if is_changed { Some(TextBlock::new_resolved(items_new)) } else { None }
+    }
+}
+
+fn protected_ranges(chars: &Vec<char>) -> Vec<(usize, usize)> {
+    // Character ranges that shouldn't get links: inline code like ''cargo build'', unformatted
+    // text between "%%" pairs, and URLs.
+    let mut ranges = vec![];
+    for delim in [DELIM_MONOSPACE, DELIM_NO_FORMAT].iter() {
+        let delim = delim.chars().collect::<Vec<_>>();
+        let mut start: Option<usize> = None;
+        let mut index = 0;
+        while index + delim.len() <= chars.len() {
+            if chars[index..index + delim.len()].eq(&delim[..]) {
+                match start {
+                    Some(start_index) => {
+                        ranges.push((start_index, index + delim.len()));
+                        start = None;
+                    },
+                    None => {
+                        start = Some(index);
+                    }
+                }
+                index += delim.len();
+            } else {
+                index += 1;
+            }
+        }
+    }
+    let mut index = 0;
+    while index < chars.len() {
+        if chars[index..].starts_with(&[':', '/', '/']) {
+            let start = (0..index).rev().take_while(|i| !chars[*i].is_whitespace()).last().unwrap_or(index);
+            let end = (index..chars.len()).find(|i| chars[*i].is_whitespace()).unwrap_or(chars.len());
+            ranges.push((start, end));
+            index = end;
+        } else {
+            index += 1;
+        }
+    }
+    ranges
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn lowercase_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_name_wins_on_word_boundaries_outside_code() {
+        let mut model = Model::new("Tools", "tools", None, None);
+        for name in ["Rust", "Rust Analyzer", "Analyzer", "Go", "Café"].iter() {
+            model.add_topic(Topic::new("tools", name));
+        }
+        let matcher = TopicNameMatcher::new(&model);
+        let text = "İ use RUST analyzer and rust, trust, CAFÉ, ''rust'' http://rust.org go";
+        let chars = text.chars().collect::<Vec<_>>();
+        let matches = matcher.find_matches(text).into_iter()
+            .map(|(start, end, topic_key)| (chars[start..end].iter().collect::<String>(), topic_key.get_topic_name().to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(matches, vec![
+            ("RUST analyzer".to_string(), "Rust Analyzer".to_string()),
+            ("rust".to_string(), "Rust".to_string()),
+            ("CAFÉ".to_string(), "Café".to_string()),
+        ]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use crate::model::{Model, TopicKey, Topic, TopicTree};
 
 // use super::*;
@@ -5,8 +7,7 @@ use crate::model::{Model, TopicKey, Topic, TopicTree};
 pub(crate) fn add_missing_category_topics(model: &mut Model) {
     // First make sure we have a category entry for each category referenced in a topic.
     let mut category_names = model.get_topics().values()
-        .filter_map(|topic| topic.get_category())
-        .map(|category_name| category_name.to_string())
+        .flat_map(|topic| topic.get_categories().clone())
         .collect::<Vec<_>>();
     category_names.sort();
     category_names.dedup();
@@ -67,6 +68,8 @@ pub(crate) fn move_topics_to_namespace_by_category(model: &mut Model, category_n
 */
 
 pub(crate) fn make_category_tree(model: &mut Model) -> TopicTree {
+    // The tree holds each topic only under its primary category. Secondary categories are
+    // handled separately below.
     assert!(!model.get_topics().is_empty());
     let mut parent_child_pairs = vec![];
     for topic in model.get_topics().values() {
@@ -79,9 +82,50 @@ pub(crate) fn make_category_tree(model: &mut Model) -> TopicTree {
     assert!(!parent_child_pairs.is_empty());
     let mut tree = util::tree::Tree::create(parent_child_pairs, true);
     Topic::sort_topic_tree(&mut tree);
+    let mut secondary_topics = secondary_topics_in_categories(model);
     // Have each category topic point to its node in the category tree.
     for topic in model.get_topics_mut().values_mut() {
-        topic.set_category_tree_node(tree.get_node(&topic.get_topic_key()));
+        let topic_key = topic.get_topic_key();
+        topic.set_category_tree_node(tree.get_node(&topic_key));
+        let (topic_keys, topic_keys_indirect) = secondary_topics.remove(&topic_key).unwrap_or_default();
+        topic.set_secondary_topics_in_category(topic_keys, topic_keys_indirect);
     }
     tree
 }
+
+fn secondary_topics_in_categories(model: &Model) -> BTreeMap<TopicKey, (Vec<TopicKey>, Vec<TopicKey>)> {
+    // For each category topic, the topics that have it as a secondary category, and the same
+    // along with those from all of its subcategories. A topic with "Serialization" as a secondary
+    // category shows up directly in Serialization and indirectly in each category above it.
+    let namespace_main = model.get_main_namespace().to_string();
+    let mut map: BTreeMap<TopicKey, (Vec<TopicKey>, Vec<TopicKey>)> = BTreeMap::new();
+    for topic in model.get_topics().values() {
+        let topic_key = topic.get_topic_key();
+        for category_name in topic.get_categories().iter().skip(1) {
+            let category_topic_key = TopicKey::new(&namespace_main, category_name);
+            map.entry(category_topic_key.clone()).or_default().0.push(topic_key.clone());
+            for ancestor_topic_key in category_and_ancestors(model, &category_topic_key).into_iter() {
+                let entry = map.entry(ancestor_topic_key).or_default();
+                if !entry.1.contains(&topic_key) {
+                    entry.1.push(topic_key.clone());
+                }
+            }
+        }
+    }
+    map
+}
+
+fn category_and_ancestors(model: &Model, category_topic_key: &TopicKey) -> Vec<TopicKey> {
+    // Follow the primary categories upward. A category that's somehow its own ancestor stops the
+    // walk rather than looping.
+    let mut topic_keys = vec![category_topic_key.clone()];
+    let mut topic_key = category_topic_key.clone();
+    while let Some(category_name) = model.get_topics().get(&topic_key).and_then(|topic| topic.get_category()) {
+        topic_key = TopicKey::new(model.get_main_namespace(), &category_name);
+        if topic_keys.contains(&topic_key) {
+            break;
+        }
+        topic_keys.push(topic_key.clone());
+    }
+    topic_keys
+}
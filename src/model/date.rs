@@ -1,37 +1,148 @@
-use crate::model::{ATTRIBUTE_NAME_ADDED, ATTRIBUTE_NAME_EDITED};
-use crate::model::Model;
-
-pub(crate) fn update_date_attributes_from_file_monitor(model: &mut Model) {
-    // We're still working with the temp attributes.
-    println!("\nupdate_date_attributes_from_file_monitor()");
-    if let Some(project) = model.get_file_monitor_project() {
-        let summary = file_monitor::summary::Summary::read_or_create(project);
-        for topic in model.get_topics_mut().values_mut() {
-            //et debug = topic.get_name().eq("Profisee");
-            //f debug { dbg!(topic.get_name()); }
-            if !topic.has_temp_attribute(ATTRIBUTE_NAME_ADDED) {
-                if let Some(file) = topic.get_file_monitor_file(&summary) {
-                    if !topic.has_temp_attribute(ATTRIBUTE_NAME_ADDED) {
-                        if let Some(time_added) = file.time_added {
-                            //bg!(&topic.get_name(), "Added", &time_added);
-                            topic.set_temp_attribute_date(ATTRIBUTE_NAME_ADDED, &time_added.date());
-                        }
-                    }
+use std::collections::BTreeMap;
+use chrono::NaiveDate;
+
+use crate::model::{ATTRIBUTE_NAME_ADDED, ATTRIBUTE_NAME_ADDED_ESTIMATED, ATTRIBUTE_NAME_EDITED};
+use crate::model::{Model, Topic, TopicKey, AttributeType, AttributeValueType};
+
+// Where the Added and Edited dates of the topic pages come from. The file monitor knows about
+// changes since it started watching the pages folder, while the git history of the pages folder
+// may go back further. Either way the dates are looked up by the page file's path relative to the
+// pages folder, like "tools/rust_analyzer.txt".
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DateSource {
+    FileMonitor,
+    Git,
+}
+
+pub(crate) trait DateProvider {
+    fn get_file_dates(&self, file_key: &str) -> Option<FileDates>;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct FileDates {
+    pub(crate) added: Option<NaiveDate>,
+    pub(crate) edited: Option<NaiveDate>,
+}
+
+pub(crate) struct FileMonitorDateProvider {
+    summary: file_monitor::summary::Summary,
+}
+
+pub(crate) struct GitDateProvider {
+    files: BTreeMap<String, FileDates>,
+}
+
+// Marks the start of each commit in the output of "git log" so that it can't be mistaken for a
+// file name.
+const GIT_LOG_COMMIT_PREFIX: &str = "commit\t";
+
+impl FileMonitorDateProvider {
+    pub(crate) fn new(project: &file_monitor::model::Project) -> Self {
+        Self {
+            summary: file_monitor::summary::Summary::read_or_create(project),
+        }
+    }
+}
+
+impl DateProvider for FileMonitorDateProvider {
+    fn get_file_dates(&self, file_key: &str) -> Option<FileDates> {
+        self.summary.files.get(file_key).map(|file| FileDates {
+            added: file.time_added.map(|time_added| time_added.date()),
+            edited: file.time_latest_edit.map(|time_edited| time_edited.date()),
+        })
+    }
+}
+
+impl GitDateProvider {
+    pub(crate) fn read(path_repo: &str, ignore_authors: &[&str]) -> Result<Self, String> {
+        // Read the whole log once rather than once per page. A file's first commit gives the
+        // Added date and its latest commit gives the Edited date. Commits by any of the
+        // ignore_authors, matched by name or email, are skipped. These are meant to be the
+        // commits made after a round trip, which rewrite many pages without anyone editing them.
+        let output = std::process::Command::new("git")
+            .args(&["-C", path_repo, "-c", "core.quotepath=off", "log", "--relative", "--name-only", "--date=short"])
+            .arg(format!("--format={}%an%x09%ae%x09%ad", GIT_LOG_COMMIT_PREFIX))
+            .output()
+            .map_err(|e| format!("Unable to run git in \"{}\": {}", path_repo, e))?;
+        if !output.status.success() {
+            return Err(format!("git log failed in \"{}\": {}", path_repo, String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        let log = String::from_utf8(output.stdout).map_err(|e| e.to_string())?;
+        Self::parse_log(&log, ignore_authors)
+    }
+
+    fn parse_log(log: &str, ignore_authors: &[&str]) -> Result<Self, String> {
+        // The log lists the newest commit first, so the first date seen for a file is its Edited
+        // date and the last one is its Added date.
+        let mut files: BTreeMap<String, FileDates> = BTreeMap::new();
+        let mut commit_date = None;
+        for line in log.lines().map(|line| line.trim_end()).filter(|line| !line.is_empty()) {
+            if let Some(header) = line.strip_prefix(GIT_LOG_COMMIT_PREFIX) {
+                let parts = header.splitn(3, '\t').collect::<Vec<_>>();
+                if parts.len() != 3 {
+                    return Err(format!("Unexpected commit line in git log: \"{}\"", line));
                 }
-            }
-            if let Some(file) = topic.get_file_monitor_file(&summary) {
-                //f debug { dbg!(&file); }
-                if let Some(time_edited) = file.time_latest_edit {
-                    //f debug { dbg!(&time_edited); }
-                    let date_edited = time_edited.date();
-                    // If the topic has an Added attribute with this date, don't create an Edited
-                    // attribute.
-                    if topic.get_temp_attribute_date_opt(ATTRIBUTE_NAME_ADDED).map_or(true, |date_added| date_added != date_edited) {
-                        topic.set_temp_attribute_date(ATTRIBUTE_NAME_EDITED, &date_edited);
-                    }
+                let (author_name, author_email, date) = (parts[0], parts[1], parts[2]);
+                let is_ignored = ignore_authors.iter().any(|author| author.eq_ignore_ascii_case(author_name) || author.eq_ignore_ascii_case(author_email));
+                commit_date = if is_ignored {
+                    None
+                } else {
+                    Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("Bad commit date \"{}\": {}", date, e))?)
+                };
+            } else if let Some(date) = commit_date {
+                let file_dates = files.entry(line.to_string()).or_insert_with(FileDates::default);
+                file_dates.added = Some(date);
+                if file_dates.edited.is_none() {
+                    file_dates.edited = Some(date);
                 }
             }
-            //f debug { panic!(); }
+        }
+        Ok(Self { files })
+    }
+}
+
+impl DateProvider for GitDateProvider {
+    fn get_file_dates(&self, file_key: &str) -> Option<FileDates> {
+        self.files.get(file_key).copied()
+    }
+}
+
+pub(crate) fn update_date_attributes(model: &mut Model, provider: &dyn DateProvider) {
+    // We're still working with the temp attributes. A topic keeps an Added date it already has
+    // unless that date was estimated and the provider has an earlier one.
+    println!("\nupdate_date_attributes()");
+    for topic in model.get_topics_mut().values_mut() {
+        if let Some(file_dates) = provider.get_file_dates(&topic.get_file_key()) {
+            set_date_attributes(topic, &file_dates);
+        }
+    }
+}
+
+pub(crate) fn carry_over_date_attributes(model: &mut Model, provider: &dyn DateProvider, topic_key_old: &TopicKey, topic_key_new: &TopicKey) {
+    // After a rename the page file has a new path, which the provider knows nothing about yet, so
+    // give the renamed topic the dates recorded for the old path. Once they're in the topic's
+    // attribute table they stay with it.
+    if let Some(file_dates) = provider.get_file_dates(&topic_key_old.get_file_key()) {
+        let topic = model.get_topics_mut().get_mut(topic_key_new).unwrap();
+        set_date_attributes(topic, &file_dates);
+    }
+}
+
+fn set_date_attributes(topic: &mut Topic, file_dates: &FileDates) {
+    if let Some(date_added) = file_dates.added {
+        if !topic.has_temp_attribute(ATTRIBUTE_NAME_ADDED) {
+            topic.set_temp_attribute_date(ATTRIBUTE_NAME_ADDED, &date_added);
+        } else if topic.has_temp_attribute(ATTRIBUTE_NAME_ADDED_ESTIMATED) && topic.get_temp_attribute_date_opt(ATTRIBUTE_NAME_ADDED).map_or(false, |date_estimated| date_added < date_estimated) {
+            topic.set_temp_attribute_date(ATTRIBUTE_NAME_ADDED, &date_added);
+            topic.remove_temp_attribute(ATTRIBUTE_NAME_ADDED_ESTIMATED);
+        }
+    }
+    if let Some(date_edited) = file_dates.edited {
+        // If the topic has an Added attribute with this date, don't create an Edited
+        // attribute.
+        if topic.get_temp_attribute_date_opt(ATTRIBUTE_NAME_ADDED).map_or(true, |date_added| date_added != date_edited) {
+            topic.set_temp_attribute_date(ATTRIBUTE_NAME_EDITED, &date_edited);
         }
     }
 }
@@ -54,80 +165,226 @@ pub(crate) fn remove_edited_same_as_added(model: &mut Model) {
     }
 }
 
-/*
-pub(crate) fn interpolate_added_date(model: &mut Model) {
-    let attr_added_sequence = model.get_attribute_orders().get(ATTRIBUTE_NAME_ADDED).unwrap();
-    let mut changes = BTreeMap::new();
+pub(crate) enum AddedDateSource {
+    // The earliest of the topic's other date attributes, like Acquired or Started.
+    OtherDate { attr_type_name: String },
+    // The Added date of the earliest topic that links to this one.
+    InboundLink { topic_key: TopicKey },
+}
+
+pub(crate) struct AddedDateEstimate {
+    topic_key: TopicKey,
+    date: NaiveDate,
+    source: AddedDateSource,
+    // Zero for a date from within the topic, otherwise the round of following inbound links in
+    // which the date was found.
+    round: usize,
+}
 
-    // See if any dates can be worked out within a topic.
-    for topic in model.get_topics().values().filter(|topic| !topic.get_attributes().contains_key(ATTRIBUTE_NAME_ADDED)) {
+pub(crate) fn estimate_added_dates(model: &Model) -> Vec<AddedDateEstimate> {
+    // Work out an Added date for each topic that doesn't have one. This runs on a built model, so
+    // the attributes have been cataloged, but the inbound links may not have been so they're
+    // gathered here. Nothing is changed; see apply_added_date_estimates().
+    let mut estimates: Vec<AddedDateEstimate> = vec![];
+    let mut dates = model.get_topics().iter()
+        .filter_map(|(topic_key, topic)| topic.get_attribute_date(ATTRIBUTE_NAME_ADDED).map(|date| (topic_key.clone(), date)))
+        .collect::<BTreeMap<_, _>>();
+
+    // See if any dates can be worked out within a topic. Edited is left out since it only says
+    // when the page last changed, and it would crowd out the better guesses from inbound links.
+    for topic in model.get_topics().values().filter(|topic| !dates.contains_key(&topic.get_topic_key())) {
         let min_other_date = topic.get_attributes().values()
-            .filter_map(|attr_instance| {
-                let attr_type = model.get_attribute_type(attr_instance.get_attribute_type_name()).unwrap();
-                if attr_type.get_value_type().eq(&AttributeValueType::Date) {
-                    Some(attr_instance.get_values().iter().map(|value| AttributeType::value_to_date(value)).min().unwrap())
-                } else {
-                    None
-                }
-            }).min();
-        if let Some(min_other_date) = min_other_date {
-            println!("Based on other dates in topic: \"{}\": {}", topic.get_name(), min_other_date);
-            changes.insert(topic.get_key(), min_other_date);
+            .filter(|attr_instance| {
+                let attr_type_name = attr_instance.get_attribute_type_name();
+                attr_type_name.ne(ATTRIBUTE_NAME_EDITED)
+                    && model.get_attribute_type(attr_type_name).map_or(false, |attr_type| attr_type.get_value_type().eq(&AttributeValueType::Date))
+            })
+            .flat_map(|attr_instance| attr_instance.get_values().iter()
+                .map(move |value| (AttributeType::value_to_date(value), attr_instance.get_attribute_type_name().to_string())))
+            .min();
+        if let Some((date, attr_type_name)) = min_other_date {
+            estimates.push(AddedDateEstimate::new(topic.get_topic_key(), date, AddedDateSource::OtherDate { attr_type_name }, 0));
         }
     }
+    for estimate in estimates.iter() {
+        dates.insert(estimate.topic_key.clone(), estimate.date);
+    }
 
-    // If a given topic has no added date, set its date to the earliest date of any topics that
-    // link to it. Do this in multiple rounds, since a topic may get an added date in one round
-    // that is used to set the added date of another topic in a subsequent round.
+    // If a given topic still has no Added date, set its date to the earliest date of any topics
+    // that link to it. Do this in multiple rounds, since a topic may get a date in one round that
+    // is used to set the date of another topic in a later round. Within a round only the dates
+    // known at the start are used, so the result doesn't depend on the order of the topics. Links
+    // from Main don't count since it links to almost everything.
+    let inbound_map = inbound_topic_keys(model);
+    let mut round = 0;
     loop {
-        let mut changed_count = 0;
-        // We want only topics where there is no Added date and where it doesn't yet appear in the
-        // changes list as having been given a new Added date.
-        for topic in model.get_topics().values().filter(|topic| get_topic_added_date(topic, &changes).is_none()) {
-            let other_dates = topic.get_inbound_topic_keys().iter()
+        round += 1;
+        let mut round_estimates = vec![];
+        for (topic_key, inbound_topic_keys) in inbound_map.iter().filter(|(topic_key, _)| !dates.contains_key(*topic_key)) {
+            let min_inbound_date = inbound_topic_keys.iter()
                 .filter(|inbound_topic_key| !inbound_topic_key.get_topic_name().eq_ignore_ascii_case("Main"))
-                .filter_map(|inbound_topic_key| {
-                    get_topic_added_date(&model.get_topics().get(inbound_topic_key).unwrap(), &changes)
-                        .map(|date| Some((inbound_topic_key.clone(), date)))
-                })
-                .map(|x| x.unwrap())
-                .collect::<Vec<_>>();
-            //bg!(topic.get_name(), &other_dates);
-            if !other_dates.is_empty() {
-                /*
-                if let Some(min_other_date) = min_other_date {
-                    println!("Based on other dates in topic: \"{}\": {}", topic.get_name(), min_other_date);
-                    changes.insert(topic.get_key(), min_other_date);
-                }
-                */
-
-
-                changed_count += 1;
+                .filter_map(|inbound_topic_key| dates.get(inbound_topic_key).map(|date| (*date, inbound_topic_key.clone())))
+                .min();
+            if let Some((date, inbound_topic_key)) = min_inbound_date {
+                round_estimates.push(AddedDateEstimate::new(topic_key.clone(), date, AddedDateSource::InboundLink { topic_key: inbound_topic_key }, round));
             }
         }
-        if changed_count == 0 {
+        if round_estimates.is_empty() {
             break;
         }
+        for estimate in round_estimates.drain(..) {
+            dates.insert(estimate.topic_key.clone(), estimate.date);
+            estimates.push(estimate);
+        }
     }
+    estimates.sort_by(|a, b| a.topic_key.cmp(&b.topic_key));
+    estimates
+}
 
-    for (topic_key, date) in changes.iter() {
-        let topic = model.get_topics_mut().get_mut(topic_key).unwrap();
-        topic.set_attribute_date(ATTRIBUTE_NAME_ADDED, *attr_added_sequence, date);
+fn inbound_topic_keys(model: &Model) -> BTreeMap<TopicKey, Vec<TopicKey>> {
+    // The same links that Model::catalog_links() follows, not counting generated ones.
+    let mut map = BTreeMap::new();
+    for topic in model.get_topics().values() {
+        for dest_topic_key in topic.get_links(false, false).iter()
+                .filter_map(|link_rc| b!(link_rc).get_topic_key())
+                .filter(|dest_topic_key| model.has_topic(dest_topic_key)) {
+            let entry = map.entry(dest_topic_key).or_insert(vec![]);
+            if !entry.contains(&topic.get_topic_key()) {
+                entry.push(topic.get_topic_key());
+            }
+        }
     }
+    map
 }
-*/
 
-/*
-fn get_topic_added_date(topic: &Topic, changes: &BTreeMap<TopicKey, NaiveDate>) -> Option<NaiveDate> {
-    match changes.get(&topic.get_key()) {
-        Some(date) => Some(*date),
-        None => {
-            topic.get_attributes().get(ATTRIBUTE_NAME_ADDED)
-                .map(|attr_instance| {
-                    debug_assert!(attr_instance.get_values().len() == 1);
-                    AttributeType::value_to_date(&attr_instance.get_values()[0])
-                })
-        },
+pub(crate) fn apply_added_date_estimates(model: &mut Model, estimates: &[AddedDateEstimate]) {
+    // Give each topic its estimated Added date along with a flag saying that it's an estimate, so
+    // that a date found later by hand or from the file history can replace it. These go in as
+    // temp attributes to be picked up the next time the attributes are cataloged.
+    for estimate in estimates.iter() {
+        let topic = model.get_topics_mut().get_mut(&estimate.topic_key).unwrap();
+        topic.set_temp_attribute_date(ATTRIBUTE_NAME_ADDED, &estimate.date);
+        topic.add_temp_attribute_values(ATTRIBUTE_NAME_ADDED_ESTIMATED.to_string(), vec![util::bool::bool_to_yes_no(true)]);
+    }
+}
+
+impl AddedDateEstimate {
+    fn new(topic_key: TopicKey, date: NaiveDate, source: AddedDateSource, round: usize) -> Self {
+        Self {
+            topic_key,
+            date,
+            source,
+            round,
+        }
+    }
+
+    pub(crate) fn get_topic_key(&self) -> &TopicKey {
+        &self.topic_key
+    }
+
+    pub(crate) fn get_date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub(crate) fn get_source(&self) -> &AddedDateSource {
+        &self.source
+    }
+
+    pub(crate) fn get_round(&self) -> usize {
+        self.round
+    }
+
+    pub(crate) fn describe_source(&self) -> String {
+        match &self.source {
+            AddedDateSource::OtherDate { attr_type_name } => format!("from {}", attr_type_name),
+            AddedDateSource::InboundLink { topic_key } => format!("linked from {} (round {})", topic_key, self.round),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Paragraph, TextBlock, TextItem};
+
+    #[test]
+    fn git_log_gives_first_and_last_commit_dates() {
+        let log = "commit\tAnn\tann@example.com\t2021-06-01\n\ntools/rust.txt\n\n\
+            commit\tWiki Gen\twiki-gen@localhost\t2021-05-01\n\ntools/rust.txt\ntools/cargo.txt\n\n\
+            commit\tAnn\tann@example.com\t2020-01-15\n\ntools/rust.txt\n";
+        let provider = GitDateProvider::parse_log(log, &["wiki gen"]).unwrap();
+        let rust = provider.get_file_dates("tools/rust.txt").unwrap();
+        assert_eq!(rust.added, Some(NaiveDate::from_ymd(2020, 1, 15)));
+        assert_eq!(rust.edited, Some(NaiveDate::from_ymd(2021, 6, 1)));
+        // A file touched only by the generator has no dates.
+        assert!(provider.get_file_dates("tools/cargo.txt").is_none());
+    }
+
+    #[test]
+    fn git_keys_match_topic_file_keys() {
+        // The pages folder is below the root of the repo, so the keys only line up with
+        // Topic::get_file_key() if "git log --relative" makes them relative to that folder.
+        let path_repo = std::env::temp_dir().join(format!("wiki_git_dates_{}", std::process::id()));
+        let path_pages = path_repo.join("data").join("pages");
+        std::fs::create_dir_all(path_pages.join("tools").join("nav")).unwrap();
+        std::fs::write(path_pages.join("tools").join("nav").join("x.txt"), "Page text.").unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C").arg(&path_repo)
+                .args(&["-c", "user.name=Ann", "-c", "user.email=ann@example.com"])
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "Add x."]);
+        let provider = GitDateProvider::read(path_pages.to_str().unwrap(), &[]);
+        std::fs::remove_dir_all(&path_repo).unwrap();
+
+        let file_key = Topic::new("tools:nav", "X").get_file_key();
+        assert_eq!(file_key, "tools/nav/x.txt");
+        let file_dates = provider.unwrap().get_file_dates(&file_key).unwrap();
+        assert!(file_dates.added.is_some());
+        assert_eq!(file_dates.added, file_dates.edited);
+    }
+
+    #[test]
+    fn added_dates_follow_inbound_links_over_rounds() {
+        // Ann links to Bob and Bob links to Cat, so Bob gets Ann's date in the first round and
+        // Cat gets it from Bob in the second. Main's earlier date doesn't count.
+        let mut model = Model::new("Tools", "tools", None, None);
+        let mut add_topic = |name: &str, date: Option<NaiveDate>, link_names: &[&str]| {
+            let mut topic = Topic::new("tools", name);
+            if let Some(date) = date {
+                topic.set_attribute_date(ATTRIBUTE_NAME_ADDED, 0, &date);
+            }
+            let items = link_names.iter()
+                .map(|link_name| TextItem::new_topic_link(&TopicKey::new("tools", link_name)))
+                .collect::<Vec<_>>();
+            topic.add_paragraph(Paragraph::new_text(TextBlock::new_resolved(items)));
+            model.add_topic(topic);
+        };
+        add_topic("Main", Some(NaiveDate::from_ymd(2019, 1, 1)), &["Cat", "Dan"]);
+        add_topic("Ann", Some(NaiveDate::from_ymd(2020, 3, 1)), &["Bob"]);
+        add_topic("Eve", Some(NaiveDate::from_ymd(2021, 7, 1)), &["Bob"]);
+        add_topic("Bob", None, &["Cat"]);
+        add_topic("Cat", None, &[]);
+        add_topic("Dan", None, &[]);
+
+        let estimates = estimate_added_dates(&model).iter()
+            .map(|estimate| {
+                let source_name = match estimate.get_source() {
+                    AddedDateSource::InboundLink { topic_key } => topic_key.get_topic_name().to_string(),
+                    AddedDateSource::OtherDate { attr_type_name } => attr_type_name.clone(),
+                };
+                (estimate.get_topic_key().get_topic_name().to_string(), estimate.get_date(), estimate.get_round(), source_name)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(estimates, vec![
+            ("Bob".to_string(), NaiveDate::from_ymd(2020, 3, 1), 1, "Ann".to_string()),
+            ("Cat".to_string(), NaiveDate::from_ymd(2020, 3, 1), 2, "Bob".to_string()),
+        ]);
     }
 }
-*/
\ No newline at end of file
@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use crate::model::{TopicKey, make_topic_ref, ATTRIBUTE_VALUE_PUBLIC, ATTRIBUTE_VALUE_PRIVATE, ATTRIBUTE_VALUE_UNKNOWN};
+
+// Deciding which topics go into a filtered build. This happens before the topics are parsed,
+// since the redacted phrases have to be known before any of the page text is redacted, so each
+// topic is described by what can be read from its raw page: the audiences in its Visibility
+// attribute and who its parents are according to the breadcrumbs.
+//
+// An audience is any Visibility value other than Private and Unknown, such as "Team" or
+// "Client Acme". A topic is visible to each audience it lists. Public is itself an audience, but
+// a public topic is also visible to every other audience.
+
+#[derive(Clone, Debug)]
+pub(crate) struct TopicFilterCandidate {
+    topic_key: TopicKey,
+    audiences: Vec<String>,
+    parent_topic_keys: Vec<TopicKey>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct TopicFilterResult {
+    pub(crate) is_included: bool,
+    pub(crate) is_redacted: bool,
+}
+
+impl TopicFilterCandidate {
+    pub(crate) fn new(topic_key: TopicKey, audiences: Vec<String>, parent_topic_keys: Vec<TopicKey>) -> Self {
+        Self {
+            topic_key,
+            audiences,
+            parent_topic_keys,
+        }
+    }
+
+    fn is_public(&self) -> bool {
+        is_visible_to_audience(&self.audiences, ATTRIBUTE_VALUE_PUBLIC)
+    }
+}
+
+pub(crate) fn is_visible_to_audience(visibility_values: &[String], audience: &str) -> bool {
+    // Audience names ignore case, so "Client Acme" and "client acme" are the same audience.
+    if audience.eq_ignore_ascii_case(ATTRIBUTE_VALUE_PRIVATE) || audience.eq_ignore_ascii_case(ATTRIBUTE_VALUE_UNKNOWN) {
+        return false;
+    }
+    visibility_values.iter().any(|value| value.eq_ignore_ascii_case(ATTRIBUTE_VALUE_PUBLIC) || value.eq_ignore_ascii_case(audience))
+}
+
+pub(crate) fn decide_topic_filter(candidates: &Vec<TopicFilterCandidate>, filter_audience: &Option<String>, filter_root_topic_ref: &Option<String>) -> BTreeMap<TopicKey, TopicFilterResult> {
+    // In a build for one audience, a topic is included only if it's visible to that audience, and
+    // every other topic is redacted so that its name doesn't show up anywhere. In a build limited
+    // to one root topic, a topic is included if it's the root or descends from it. A topic that's
+    // left out but is public is not redacted, since its name is not a secret.
+    assert!(filter_audience.is_some() != filter_root_topic_ref.is_some());
+    let parent_map = candidates.iter()
+        .map(|candidate| (candidate.topic_key.clone(), candidate.parent_topic_keys.clone()))
+        .collect::<BTreeMap<_, _>>();
+    candidates.iter()
+        .map(|candidate| {
+            let is_included = match filter_root_topic_ref {
+                Some(root_topic_ref) => is_in_subtree(&parent_map, &candidate.topic_key, root_topic_ref),
+                None => is_visible_to_audience(&candidate.audiences, filter_audience.as_ref().unwrap()),
+            };
+            let is_redacted = !is_included && !candidate.is_public();
+            (candidate.topic_key.clone(), TopicFilterResult { is_included, is_redacted })
+        })
+        .collect()
+}
+
+fn is_in_subtree(parent_map: &BTreeMap<TopicKey, Vec<TopicKey>>, topic_key: &TopicKey, root_topic_ref: &str) -> bool {
+    // Walk up through all of the parents, including both parents of a combination topic. A topic
+    // that's seen twice is skipped so that a loop in the breadcrumbs can't hang the build.
+    let mut visited = vec![];
+    let mut pending = vec![topic_key.clone()];
+    while let Some(topic_key) = pending.pop() {
+        if topic_refs_match(&make_topic_ref(topic_key.get_namespace(), topic_key.get_topic_name()), root_topic_ref) {
+            return true;
+        }
+        if visited.contains(&topic_key) {
+            continue;
+        }
+        if let Some(parent_topic_keys) = parent_map.get(&topic_key) {
+            pending.extend(parent_topic_keys.iter().cloned());
+        }
+        visited.push(topic_key);
+    }
+    false
+}
+
+fn topic_refs_match(topic_ref: &str, root_topic_ref: &str) -> bool {
+    // The root may be given with or without a leading namespace separator, as in ":tools:rust" or
+    // "tools:rust".
+    topic_ref.trim_start_matches(':').eq_ignore_ascii_case(root_topic_ref.trim_start_matches(':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(topic_name: &str, is_public: bool, parent_topic_names: &[&str]) -> TopicFilterCandidate {
+        let audience = if is_public { ATTRIBUTE_VALUE_PUBLIC } else { ATTRIBUTE_VALUE_PRIVATE };
+        candidate_for_audiences(topic_name, &[audience], parent_topic_names)
+    }
+
+    fn candidate_for_audiences(topic_name: &str, audiences: &[&str], parent_topic_names: &[&str]) -> TopicFilterCandidate {
+        let audiences = audiences.iter().map(|audience| audience.to_string()).collect();
+        let parent_topic_keys = parent_topic_names.iter().map(|parent_topic_name| TopicKey::new("tools", parent_topic_name)).collect();
+        TopicFilterCandidate::new(TopicKey::new("tools", topic_name), audiences, parent_topic_keys)
+    }
+
+    fn public() -> Option<String> {
+        Some(ATTRIBUTE_VALUE_PUBLIC.to_string())
+    }
+
+    fn result(results: &BTreeMap<TopicKey, TopicFilterResult>, topic_name: &str) -> TopicFilterResult {
+        *results.get(&TopicKey::new("tools", topic_name)).unwrap()
+    }
+
+    fn candidates() -> Vec<TopicFilterCandidate> {
+        vec![
+            candidate("Rust", true, &[]),
+            candidate("Cargo", true, &["Rust"]),
+            candidate("Work Notes", false, &["Rust"]),
+            candidate("Client Project", false, &[]),
+            candidate("Client Project Tasks", true, &["Client Project"]),
+            candidate("Rust and Client Project", true, &["Rust", "Client Project"]),
+        ]
+    }
+
+    #[test]
+    fn public_build_excludes_and_redacts_private_topics() {
+        let results = decide_topic_filter(&candidates(), &public(), &None);
+        assert_eq!(result(&results, "Cargo"), TopicFilterResult { is_included: true, is_redacted: false });
+        assert_eq!(result(&results, "Work Notes"), TopicFilterResult { is_included: false, is_redacted: true });
+        assert_eq!(result(&results, "Client Project"), TopicFilterResult { is_included: false, is_redacted: true });
+        // A public subtopic of a private topic is still included. Its breadcrumbs are dropped
+        // later because they contain the redacted parent name.
+        assert_eq!(result(&results, "Client Project Tasks"), TopicFilterResult { is_included: true, is_redacted: false });
+    }
+
+    #[test]
+    fn root_build_includes_only_the_subtree() {
+        let results = decide_topic_filter(&candidates(), &None, &Some("tools:client_project".to_string()));
+        assert_eq!(result(&results, "Client Project"), TopicFilterResult { is_included: true, is_redacted: false });
+        assert_eq!(result(&results, "Client Project Tasks"), TopicFilterResult { is_included: true, is_redacted: false });
+        assert_eq!(result(&results, "Rust and Client Project"), TopicFilterResult { is_included: true, is_redacted: false });
+        assert_eq!(result(&results, "Cargo"), TopicFilterResult { is_included: false, is_redacted: false });
+        assert_eq!(result(&results, "Work Notes"), TopicFilterResult { is_included: false, is_redacted: true });
+    }
+
+    #[test]
+    fn root_build_survives_a_breadcrumb_loop() {
+        let candidates = vec![
+            candidate("A", true, &["B"]),
+            candidate("B", true, &["A"]),
+        ];
+        let results = decide_topic_filter(&candidates, &None, &Some(":tools:c".to_string()));
+        assert!(!result(&results, "A").is_included);
+        assert!(!result(&results, "B").is_included);
+    }
+
+    #[test]
+    fn audience_build_includes_public_and_audience_topics() {
+        let candidates = vec![
+            candidate("Rust", true, &[]),
+            candidate_for_audiences("Standup", &["Team"], &[]),
+            candidate_for_audiences("Acme Handoff", &["Team", "Client Acme"], &[]),
+            candidate_for_audiences("Globex Handoff", &["Client Globex"], &[]),
+            candidate("Work Notes", false, &[]),
+        ];
+        let results = decide_topic_filter(&candidates, &Some("client acme".to_string()), &None);
+        assert_eq!(result(&results, "Rust"), TopicFilterResult { is_included: true, is_redacted: false });
+        assert_eq!(result(&results, "Acme Handoff"), TopicFilterResult { is_included: true, is_redacted: false });
+        assert_eq!(result(&results, "Standup"), TopicFilterResult { is_included: false, is_redacted: true });
+        assert_eq!(result(&results, "Globex Handoff"), TopicFilterResult { is_included: false, is_redacted: true });
+        assert_eq!(result(&results, "Work Notes"), TopicFilterResult { is_included: false, is_redacted: true });
+        // A public build leaves out everything that's only for a named audience.
+        let results = decide_topic_filter(&candidates, &public(), &None);
+        assert!(!result(&results, "Acme Handoff").is_included);
+    }
+}
@@ -0,0 +1,67 @@
+// Writing the JSON reports and exports by hand, since they're all flat arrays of objects and
+// don't need serde. Each object goes on its own line so that the files diff well.
+
+pub(crate) fn json_quote(text: &str) -> String {
+    // Page text can hold a carriage return or other control characters, all of which have to be
+    // escaped for the result to be valid JSON.
+    let mut quoted = "\"".to_string();
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+pub(crate) fn json_field(name: &str, value: &str) -> String {
+    // The value is already in its JSON form, whether that's a quoted string, a number, or an
+    // array.
+    format!("{}: {}", json_quote(name), value)
+}
+
+pub(crate) fn json_object(fields: &[String]) -> String {
+    format!("{{{}}}", fields.join(", "))
+}
+
+pub(crate) fn json_array(values: &[String]) -> String {
+    format!("[{}]", values.join(", "))
+}
+
+pub(crate) fn json_array_lines(items: &[String]) -> String {
+    // An array with one item per line, like:
+    //   [
+    //     {"Topic": "Rust"},
+    //     {"Topic": "Cargo"}
+    //   ]
+    let mut json = "[\n".to_string();
+    for (index, item) in items.iter().enumerate() {
+        let comma = if index < items.len() - 1 { "," } else { "" };
+        json.push_str(&format!("  {}{}\n", item, comma));
+    }
+    json.push_str("]\n");
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_characters_are_escaped() {
+        assert_eq!(json_quote("a \"b\"\r\n\tc\\d\u{1}"), "\"a \\\"b\\\"\\r\\n\\tc\\\\d\\u0001\"");
+    }
+
+    #[test]
+    fn array_lines_have_commas_between_items() {
+        let items = vec![json_object(&[json_field("Topic", &json_quote("Rust"))]), json_object(&[json_field("Line", "12")])];
+        assert_eq!(json_array_lines(&items), "[\n  {\"Topic\": \"Rust\"},\n  {\"Line\": 12}\n]\n");
+        assert_eq!(json_array_lines(&[]), "[\n]\n");
+    }
+}
@@ -1,6 +1,7 @@
 // https://www.dokuwiki.org/images
 
 use super::*;
+use crate::dokuwiki::internal_link_name;
 
 pub(crate) type LinkRc = Rc<RefCell<Link>>;
 
@@ -194,6 +195,58 @@ impl Link {
         }
     }
 
+    pub(crate) fn update_topic_key(&mut self, topic_key_old: &TopicKey, topic_key_new: &TopicKey) -> bool {
+        // Point a topic or section link at a renamed topic. A label that was simply the old topic
+        // name is changed to the new name, while any other label is left alone. Returns true if
+        // the link was changed.
+        let is_changed = match &mut self.type_ {
+            LinkType::Topic { topic_key } => {
+                if topic_key.eq(topic_key_old) {
+                    *topic_key = topic_key_new.clone();
+                    true
+                } else {
+                    false
+                }
+            },
+            LinkType::Section { section_key } => {
+                if section_key.get_topic_key().eq(topic_key_old) {
+                    *section_key = SectionKey::new(topic_key_new.get_namespace(), topic_key_new.get_topic_name(), section_key.get_section_name());
+                    true
+                } else {
+                    false
+                }
+            },
+            _ => false,
+        };
+        if is_changed && self.label.as_ref().map_or(false, |label| label.eq(topic_key_old.get_topic_name())) {
+            self.label = Some(topic_key_new.get_topic_name().to_string());
+        }
+        is_changed
+    }
+
+    pub(crate) fn update_split_section(&mut self, topic_key_old: &TopicKey, section_name: &str, subsection_names: &Vec<String>, topic_key_new: &TopicKey) -> bool {
+        // For a section that has been split off into its own topic. A link to the section itself
+        // becomes a link to the new topic, while a link to a section within it points to that
+        // section in the new topic. Returns true if the link was changed.
+        let section_key = match &self.type_ {
+            LinkType::Section { section_key } if section_key.get_topic_key().eq(topic_key_old) => section_key.clone(),
+            _ => return false,
+        };
+        // Section links may use either the section's name or its anchor form.
+        let link_section_name = section_key.get_section_name().to_lowercase();
+        let is_match = |name: &str| name.to_lowercase().eq(&link_section_name) || internal_link_name(name).eq(&link_section_name);
+        if is_match(section_name) {
+            self.type_ = LinkType::Topic { topic_key: topic_key_new.clone() };
+            true
+        } else if subsection_names.iter().any(|subsection_name| is_match(subsection_name)) {
+            let section_key = SectionKey::new(topic_key_new.get_namespace(), topic_key_new.get_topic_name(), section_key.get_section_name());
+            self.type_ = LinkType::Section { section_key };
+            true
+        } else {
+            false
+        }
+    }
+
     pub(crate) fn get_display_text(&self) -> String {
         // This may not exactly match the display text if there's no label, since the actual link
         // text will be used, and this will vary by Wiki engine. In nearly all cases, though, there
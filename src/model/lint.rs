@@ -0,0 +1,410 @@
+use std::fmt::{Display, Formatter};
+
+use crate::*;
+use crate::model::json::{json_quote, json_field, json_object, json_array_lines};
+use crate::model::{Model, Topic, TopicKey, Paragraph, List, FILE_NAME_LINT_CONFIG, ATTRIBUTE_NAME_ADDED, ATTRIBUTE_NAME_VISIBILITY, ATTRIBUTE_VALUE_UNKNOWN, LIST_TYPE_GENERAL, list_type_to_header};
+
+pub(crate) const LINT_NAME_NO_CATEGORY: &str = "no_category";
+pub(crate) const LINT_NAME_NO_ADDED_DATE: &str = "no_added_date";
+pub(crate) const LINT_NAME_VISIBILITY_UNKNOWN: &str = "visibility_unknown";
+pub(crate) const LINT_NAME_EMPTY_SECTION: &str = "empty_section";
+pub(crate) const LINT_NAME_DUPLICATE_LIST_ITEM: &str = "duplicate_list_item";
+pub(crate) const LINT_NAME_LIST_HEADER_CASE: &str = "list_header_case";
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) enum LintSeverity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+pub(crate) trait TopicLint {
+    fn get_name(&self) -> &'static str;
+    fn get_default_severity(&self) -> LintSeverity;
+    // One message for each problem found in the topic.
+    fn check(&self, model: &Model, topic: &Topic) -> Vec<String>;
+}
+
+pub(crate) struct LintConfig {
+    severities: BTreeMap<String, LintSeverity>,
+    suppressions: BTreeMap<TopicKey, Vec<String>>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct LintResult {
+    topic_key: TopicKey,
+    lint_name: String,
+    severity: LintSeverity,
+    message: String,
+}
+
+struct NoCategoryLint {}
+struct NoAddedDateLint {}
+struct VisibilityUnknownLint {}
+struct EmptySectionLint {}
+struct DuplicateListItemLint {}
+struct ListHeaderCaseLint {}
+
+pub(crate) fn lint_rules() -> Vec<Box<dyn TopicLint>> {
+    vec![
+        Box::new(NoCategoryLint {}),
+        Box::new(NoAddedDateLint {}),
+        Box::new(VisibilityUnknownLint {}),
+        Box::new(EmptySectionLint {}),
+        Box::new(DuplicateListItemLint {}),
+        Box::new(ListHeaderCaseLint {}),
+    ]
+}
+
+pub(crate) fn lint_model(model: &Model, config: &LintConfig) -> Vec<LintResult> {
+    // Run every rule that isn't turned off against every topic, skipping the rules suppressed for
+    // a given topic. The results are ordered by topic and then by rule.
+    let mut results = vec![];
+    for lint in lint_rules().iter() {
+        let severity = config.get_severity(lint.as_ref());
+        if severity == LintSeverity::Off {
+            continue;
+        }
+        for topic in model.get_topics().values() {
+            let topic_key = topic.get_topic_key();
+            if config.is_suppressed(&topic_key, lint.get_name()) {
+                continue;
+            }
+            for message in lint.check(model, topic).into_iter() {
+                results.push(LintResult {
+                    topic_key: topic_key.clone(),
+                    lint_name: lint.get_name().to_string(),
+                    severity,
+                    message,
+                });
+            }
+        }
+    }
+    results.sort_by(|a, b| a.topic_key.cmp(&b.topic_key).then(a.lint_name.cmp(&b.lint_name)));
+    results
+}
+
+pub(crate) fn lint_results_to_json(results: &Vec<LintResult>) -> String {
+    // An array with one object per problem, like:
+    //   [
+    //     {"Topic": "Rust Analyzer", "Namespace": "tools", "Lint": "no_category", "Severity": "warning", "Message": "No category."}
+    //   ]
+    let items = results.iter()
+        .map(|result| json_object(&[
+            json_field("Topic", &json_quote(result.topic_key.get_topic_name())),
+            json_field("Namespace", &json_quote(result.topic_key.get_namespace())),
+            json_field("Lint", &json_quote(&result.lint_name)),
+            json_field("Severity", &json_quote(&result.severity.to_string())),
+            json_field("Message", &json_quote(&result.message)),
+        ]))
+        .collect::<Vec<_>>();
+    json_array_lines(&items)
+}
+
+impl LintSeverity {
+    pub(crate) fn from_name(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "info" => Ok(Self::Info),
+            "warning" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            _ => Err(format!("Unknown lint severity \"{}\". Expected off, info, warning, or error.", name)),
+        }
+    }
+}
+
+impl Display for LintSeverity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let s = match self {
+            Self::Off => "off",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl LintConfig {
+    pub(crate) fn new() -> Self {
+        // Every rule at its default severity with nothing suppressed.
+        Self {
+            severities: BTreeMap::new(),
+            suppressions: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn read(model: &Model) -> Result<Self, String> {
+        // Read the optional settings file, which has lines like:
+        //   empty_section = off
+        //   list_header_case = warning
+        //   Rust Analyzer: no_category, empty_section
+        // The first kind changes a rule's severity everywhere and the second suppresses one or
+        // more rules for a single topic. Blank lines and lines starting with "#" are skipped.
+        let mut config = Self::new();
+        if !util::file::path_exists(FILE_NAME_LINT_CONFIG) {
+            return Ok(config);
+        }
+        let lint_names = lint_rules().iter().map(|lint| lint.get_name()).collect::<Vec<_>>();
+        let check_lint_name = |lint_name: &str| -> Result<String, String> {
+            let lint_name = lint_name.trim();
+            if lint_names.contains(&lint_name) {
+                Ok(lint_name.to_string())
+            } else {
+                Err(format!("LintConfig::read(): Unknown lint \"{}\" in \"{}\".", lint_name, FILE_NAME_LINT_CONFIG))
+            }
+        };
+        for line in util::file::read_file_as_lines_r(FILE_NAME_LINT_CONFIG).map_err(|err| format!("LintConfig::read(): Unable to read \"{}\": {:?}", FILE_NAME_LINT_CONFIG, err))?.iter()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            if line.contains('=') {
+                let (lint_name, severity) = util::parse::split_2(line, "=");
+                let lint_name = check_lint_name(lint_name)?;
+                config.set_severity(&lint_name, LintSeverity::from_name(severity)?);
+            } else if line.contains(':') {
+                let (topic_name, lint_names) = util::parse::split_2(line, ":");
+                let topic_key = Model::get_corrected_topic_key(model.get_topic_refs(), model.get_main_namespace(), topic_name.trim())?;
+                for lint_name in lint_names.split(',') {
+                    config.suppress(&topic_key, &check_lint_name(lint_name)?);
+                }
+            } else {
+                return Err(format!("LintConfig::read(): Unexpected line \"{}\" in \"{}\".", line, FILE_NAME_LINT_CONFIG));
+            }
+        }
+        Ok(config)
+    }
+
+    pub(crate) fn set_severity(&mut self, lint_name: &str, severity: LintSeverity) {
+        self.severities.insert(lint_name.to_string(), severity);
+    }
+
+    pub(crate) fn suppress(&mut self, topic_key: &TopicKey, lint_name: &str) {
+        let entry = self.suppressions.entry(topic_key.clone()).or_insert(vec![]);
+        if !entry.iter().any(|name| name.eq(lint_name)) {
+            entry.push(lint_name.to_string());
+        }
+    }
+
+    pub(crate) fn get_severity(&self, lint: &dyn TopicLint) -> LintSeverity {
+        self.severities.get(lint.get_name()).cloned().unwrap_or(lint.get_default_severity())
+    }
+
+    pub(crate) fn is_suppressed(&self, topic_key: &TopicKey, lint_name: &str) -> bool {
+        self.suppressions.get(topic_key).map_or(false, |lint_names| lint_names.iter().any(|name| name.eq(lint_name)))
+    }
+}
+
+impl LintResult {
+    pub(crate) fn get_topic_key(&self) -> &TopicKey {
+        &self.topic_key
+    }
+
+    pub(crate) fn get_lint_name(&self) -> &str {
+        &self.lint_name
+    }
+
+    pub(crate) fn get_severity(&self) -> LintSeverity {
+        self.severity
+    }
+
+    pub(crate) fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl TopicLint for NoCategoryLint {
+    fn get_name(&self) -> &'static str {
+        LINT_NAME_NO_CATEGORY
+    }
+
+    fn get_default_severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn check(&self, _model: &Model, topic: &Topic) -> Vec<String> {
+        // Subtopics are found through their parents and the top-level categories have nothing
+        // above them, so neither needs a category.
+        if topic.get_category().is_none() && topic.get_parent_count() == 0 && !topic.is_category() {
+            vec!["No category.".to_string()]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl TopicLint for NoAddedDateLint {
+    fn get_name(&self) -> &'static str {
+        LINT_NAME_NO_ADDED_DATE
+    }
+
+    fn get_default_severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn check(&self, _model: &Model, topic: &Topic) -> Vec<String> {
+        if topic.has_temp_attribute(ATTRIBUTE_NAME_ADDED) || topic.get_attributes().contains_key(ATTRIBUTE_NAME_ADDED) {
+            vec![]
+        } else {
+            vec![format!("No {} date.", ATTRIBUTE_NAME_ADDED)]
+        }
+    }
+}
+
+impl TopicLint for VisibilityUnknownLint {
+    fn get_name(&self) -> &'static str {
+        LINT_NAME_VISIBILITY_UNKNOWN
+    }
+
+    fn get_default_severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn check(&self, _model: &Model, topic: &Topic) -> Vec<String> {
+        if topic.has_attribute_value_temp_or_permanent(ATTRIBUTE_NAME_VISIBILITY, ATTRIBUTE_VALUE_UNKNOWN) {
+            vec![format!("{} is {}.", ATTRIBUTE_NAME_VISIBILITY, ATTRIBUTE_VALUE_UNKNOWN)]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl TopicLint for EmptySectionLint {
+    fn get_name(&self) -> &'static str {
+        LINT_NAME_EMPTY_SECTION
+    }
+
+    fn get_default_severity(&self) -> LintSeverity {
+        LintSeverity::Info
+    }
+
+    fn check(&self, _model: &Model, topic: &Topic) -> Vec<String> {
+        // A section is empty if nothing comes between its header and the next header at the same
+        // or a shallower depth, or the generated part of the page. A section holding only deeper
+        // sections isn't empty.
+        let mut messages = vec![];
+        let mut empty_section: Option<(String, usize)> = None;
+        for paragraph in authored_paragraphs(topic).into_iter() {
+            match paragraph {
+                Paragraph::SectionHeader { name, depth, link_name: _ } => {
+                    if let Some((empty_name, empty_depth)) = &empty_section {
+                        if depth <= empty_depth {
+                            messages.push(format!("Section \"{}\" is empty.", empty_name));
+                        }
+                    }
+                    empty_section = Some((name.clone(), *depth));
+                },
+                Paragraph::GenStart => {
+                    if let Some((empty_name, _)) = empty_section.take() {
+                        messages.push(format!("Section \"{}\" is empty.", empty_name));
+                    }
+                },
+                _ => {
+                    empty_section = None;
+                },
+            }
+        }
+        if let Some((empty_name, _)) = empty_section {
+            messages.push(format!("Section \"{}\" is empty.", empty_name));
+        }
+        messages
+    }
+}
+
+impl TopicLint for DuplicateListItemLint {
+    fn get_name(&self) -> &'static str {
+        LINT_NAME_DUPLICATE_LIST_ITEM
+    }
+
+    fn get_default_severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn check(&self, _model: &Model, topic: &Topic) -> Vec<String> {
+        // Items count as duplicates if they're at the same depth in the same list and have the
+        // same text ignoring case. Each duplicated item is reported once.
+        let mut messages = vec![];
+        for list in authored_lists(topic).into_iter() {
+            let mut counts: BTreeMap<(usize, String), usize> = BTreeMap::new();
+            for item in list.get_items().iter() {
+                let text = item.get_display_text().trim().to_string();
+                if text.is_empty() {
+                    continue;
+                }
+                let count = counts.entry((item.get_depth(), text.to_lowercase())).or_insert(0);
+                *count += 1;
+                if *count == 2 {
+                    messages.push(format!("List item \"{}\" appears more than once{}.", text, list_description(list)));
+                }
+            }
+        }
+        messages
+    }
+}
+
+impl TopicLint for ListHeaderCaseLint {
+    fn get_name(&self) -> &'static str {
+        LINT_NAME_LIST_HEADER_CASE
+    }
+
+    fn get_default_severity(&self) -> LintSeverity {
+        LintSeverity::Info
+    }
+
+    fn check(&self, _model: &Model, topic: &Topic) -> Vec<String> {
+        // A header that List::header_to_type() recognizes, like "See Also:", should be written
+        // the same way as the list type, as in "See also:".
+        let mut messages = vec![];
+        for list in authored_lists(topic).into_iter()
+                .filter(|list| list.get_type() != LIST_TYPE_GENERAL) {
+            if let Some(header) = list.get_header() {
+                let header_text = header.get_display_text().trim().to_string();
+                let header_expected = list_type_to_header(list.get_type());
+                if header_text.ne(&header_expected) && header_text.to_lowercase().eq(&header_expected.to_lowercase()) {
+                    messages.push(format!("List header \"{}\" should be \"{}\".", header_text, header_expected));
+                }
+            }
+        }
+        messages
+    }
+}
+
+fn authored_paragraphs(topic: &Topic) -> Vec<&Paragraph> {
+    // The paragraphs outside of the generated part of the page. The GenStart paragraph itself is
+    // included so that rules can tell where the authored part ends.
+    let mut paragraphs = vec![];
+    let mut is_generated = false;
+    for paragraph in topic.get_paragraphs().iter() {
+        match paragraph {
+            Paragraph::GenStart => {
+                is_generated = true;
+                paragraphs.push(paragraph);
+            },
+            Paragraph::GenEnd => {
+                is_generated = false;
+            },
+            _ => {
+                if !is_generated {
+                    paragraphs.push(paragraph);
+                }
+            },
+        }
+    }
+    paragraphs
+}
+
+fn authored_lists(topic: &Topic) -> Vec<&List> {
+    authored_paragraphs(topic).into_iter()
+        .filter_map(|paragraph| match paragraph {
+            Paragraph::List { list } if !list.is_generated() => Some(list),
+            _ => None,
+        })
+        .collect()
+}
+
+fn list_description(list: &List) -> String {
+    match list.get_header() {
+        Some(header) => format!(" in list \"{}\"", header.get_display_text().trim()),
+        None => "".to_string(),
+    }
+}
@@ -0,0 +1,254 @@
+use crate::*;
+use crate::model::{Model, Topic, TopicKey, Paragraph, TopicErrorList, AttributeType, AttributeValueType, ATTRIBUTE_NAME_ADDED, ATTRIBUTE_NAME_EDITED, ATTRIBUTE_NAME_VISIBILITY};
+use super::rename::{attributes_to_temp, update_links, replace_category};
+
+const PREFIX_SECTION_MERGED: &str = "Merged from ";
+
+pub(crate) fn merge_topics(model: &mut Model, topic_key_keep: &TopicKey, topic_key_merge: &TopicKey) -> Result<TopicErrorList, String> {
+    // Fold one topic into another, as when we find both "Rust Analyzer" and "rust-analyzer". The
+    // surviving topic gets the other topic's paragraphs under a separate section, the union of
+    // the attributes, category, and parents, and every link that pointed to the other topic.
+    // Anything that can't be combined, like two different categories, is returned as an error
+    // for the surviving topic so that it can be sorted out by hand.
+    let context = "merge_topics()";
+    if topic_key_keep.eq(topic_key_merge) {
+        return Err(format!("{}: Can't merge {} into itself.", context, topic_key_keep));
+    }
+    for topic_key in [topic_key_keep, topic_key_merge].iter() {
+        if !model.get_topics().contains_key(*topic_key) {
+            return Err(format!("{}: Topic {} not found.", context, topic_key));
+        }
+    }
+    let mut conflicts = TopicErrorList::new();
+
+    // Find the topics that refer to the one going away while it's still in the model. Subtopics
+    // refer to it through their parents rather than through links.
+    model.catalog_links();
+    let mut topic_keys_to_update = model.get_topics().get(topic_key_merge).unwrap().get_inbound_topic_keys().clone();
+    for topic in model.get_topics().values() {
+        if (0..topic.get_parent_count()).any(|index| b!(&topic.get_parent(index)).get_topic_key().map_or(false, |topic_key| topic_key.eq(topic_key_merge))) {
+            topic_keys_to_update.push(topic.get_topic_key());
+        }
+    }
+    topic_keys_to_update.push(topic_key_keep.clone());
+    topic_keys_to_update.retain(|topic_key| topic_key.ne(topic_key_merge));
+    topic_keys_to_update.sort();
+    topic_keys_to_update.dedup();
+    let is_category_merge = model.get_categories().iter().any(|category| category.eq(topic_key_merge.get_topic_name()));
+
+    let mut topic_merge = model.remove_topic(topic_key_merge).unwrap();
+    let mut topic_keep = model.remove_topic(topic_key_keep).unwrap();
+    attributes_to_temp(model, &mut topic_merge);
+    attributes_to_temp(model, &mut topic_keep);
+    merge_attributes(model, &mut topic_keep, &topic_merge, &mut conflicts);
+    merge_category(&mut topic_keep, &topic_merge, &mut conflicts);
+    merge_parents(&mut topic_keep, &topic_merge, &mut conflicts);
+    merge_paragraphs(&mut topic_keep, &mut topic_merge);
+    model.add_topic(topic_keep);
+    model.add_topic_aliases(topic_key_keep).print_without_panic(Some(context));
+
+    let link_count = update_links(model, Some(&topic_keys_to_update), topic_key_merge, topic_key_keep);
+    model.retarget_redirects(topic_key_merge, topic_key_keep);
+    // A topic that was a subtopic of both topics now has the same parent twice.
+    for topic_key in topic_keys_to_update.iter() {
+        remove_duplicate_parents(model.find_topic_mut(topic_key, context));
+    }
+    if is_category_merge {
+        replace_category(model, topic_key_merge.get_topic_name(), topic_key_keep.get_topic_name());
+    }
+
+    println!("Merged {} into {}, updating {} links in {} topics.", topic_key_merge, topic_key_keep, link_count, topic_keys_to_update.len());
+    Ok(conflicts)
+}
+
+fn merge_attributes(model: &Model, topic_keep: &mut Topic, topic_merge: &Topic, conflicts: &mut TopicErrorList) {
+    // Both topics are working with temp attributes at this point. Attributes that can have
+    // several values, like Domain, get the union of the values. Those with a single value keep
+    // the surviving topic's value, except that the Added date is the earlier of the two and the
+    // Edited date is the later.
+    let topic_key_keep = topic_keep.get_topic_key();
+    for (attr_type_name, values_merge) in topic_merge.get_temp_attributes().iter() {
+        let values_keep = match topic_keep.get_temp_attributes().get(attr_type_name) {
+            Some(values_keep) => values_keep.clone(),
+            None => {
+                topic_keep.add_temp_attribute_values(attr_type_name.clone(), values_merge.clone());
+                continue;
+            }
+        };
+        let values_new = values_merge.iter()
+            .filter(|value| !values_keep.contains(value))
+            .cloned()
+            .collect::<Vec<_>>();
+        if values_new.is_empty() {
+            continue;
+        }
+        let value_type = model.get_attribute_type(attr_type_name).map_or(AttributeValueType::String, |attr_type| attr_type.get_value_type().clone());
+        match value_type {
+            AttributeValueType::String if attr_type_name.ne(ATTRIBUTE_NAME_VISIBILITY) => {
+                topic_keep.add_temp_attribute_values(attr_type_name.clone(), values_new);
+            },
+            AttributeValueType::Date if attr_type_name.eq(ATTRIBUTE_NAME_ADDED) || attr_type_name.eq(ATTRIBUTE_NAME_EDITED) => {
+                let dates = values_keep.iter().chain(values_merge.iter())
+                    .filter_map(|value| AttributeType::value_to_date_multiple_formats_r(value).ok().map(|date| (date, value.clone())))
+                    .collect::<Vec<_>>();
+                let date = if attr_type_name.eq(ATTRIBUTE_NAME_ADDED) { dates.iter().min() } else { dates.iter().max() };
+                if let Some((_date, value)) = date {
+                    topic_keep.remove_temp_attribute(attr_type_name);
+                    topic_keep.add_temp_attribute_values(attr_type_name.clone(), vec![value.clone()]);
+                }
+            },
+            _ => {
+                conflicts.add(&topic_key_keep, &format!("Attribute \"{}\": kept {} and dropped {} from {}.",
+                    attr_type_name, values_keep.join(", "), values_new.join(", "), topic_merge.get_topic_key()));
+            },
+        }
+    }
+}
+
+fn merge_category(topic_keep: &mut Topic, topic_merge: &Topic, conflicts: &mut TopicErrorList) {
+    // The kept topic ends up in every category of both topics. If both had a primary category and
+    // they differ, the kept topic's stays primary and the other becomes secondary.
+    if let (Some(category_keep), Some(category_merge)) = (topic_keep.get_category(), topic_merge.get_category()) {
+        if category_keep.ne(&category_merge) {
+            conflicts.add(&topic_keep.get_topic_key(), &format!("Category: kept \"{}\" as the primary category, with \"{}\" from {} as a secondary category.", category_keep, category_merge, topic_merge.get_topic_key()));
+        }
+    }
+    for category_merge in topic_merge.get_categories().iter() {
+        topic_keep.add_category(category_merge, false);
+    }
+}
+
+fn merge_parents(topic_keep: &mut Topic, topic_merge: &Topic, conflicts: &mut TopicErrorList) {
+    // A topic can have at most two parents, and then only if it's a combination topic.
+    let topic_key_keep = topic_keep.get_topic_key();
+    let mut parents = topic_keep.take_parents();
+    for index in 0..topic_merge.get_parent_count() {
+        let link = topic_merge.get_parent(index);
+        let parent_topic_key = b!(&link).get_topic_key().unwrap();
+        if parent_topic_key.eq(&topic_key_keep) || parents.iter().any(|parent| b!(parent).get_topic_key().unwrap().eq(&parent_topic_key)) {
+            continue;
+        }
+        if parents.len() < 2 {
+            parents.push(link);
+        } else {
+            conflicts.add(&topic_key_keep, &format!("Parent {} from {} was dropped since the topic already has two parents.", parent_topic_key, topic_merge.get_topic_key()));
+        }
+    }
+    if !parents.is_empty() {
+        topic_keep.set_parents(parents);
+    }
+}
+
+fn merge_paragraphs(topic_keep: &mut Topic, topic_merge: &mut Topic) {
+    // Move the paragraphs that were written by hand. The attributes, breadcrumbs, category, and
+    // anything between the generated markers are made again when the page is generated.
+    let mut is_generated = false;
+    let paragraphs = topic_merge.take_paragraphs().into_iter()
+        .filter(|paragraph| match paragraph {
+            Paragraph::GenStart => {
+                is_generated = true;
+                false
+            },
+            Paragraph::GenEnd => {
+                is_generated = false;
+                false
+            },
+            Paragraph::Attributes | Paragraph::Breadcrumbs | Paragraph::Category | Paragraph::Placeholder => false,
+            _ => !is_generated,
+        })
+        .collect::<Vec<_>>();
+    if paragraphs.is_empty() {
+        return;
+    }
+    topic_keep.add_paragraph(Paragraph::new_section_header(&format!("{}{}", PREFIX_SECTION_MERGED, topic_merge.get_name()), 1));
+    for paragraph in paragraphs.into_iter() {
+        topic_keep.add_paragraph(paragraph);
+    }
+}
+
+fn remove_duplicate_parents(topic: &mut Topic) {
+    let topic_key = topic.get_topic_key();
+    let mut parent_topic_keys = vec![];
+    let mut parents = topic.take_parents();
+    parents.retain(|parent| {
+        let parent_topic_key = b!(parent).get_topic_key().unwrap();
+        if parent_topic_key.eq(&topic_key) || parent_topic_keys.contains(&parent_topic_key) {
+            false
+        } else {
+            parent_topic_keys.push(parent_topic_key);
+            true
+        }
+    });
+    if !parents.is_empty() {
+        topic.set_parents(parents);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{TextBlock, TextItem};
+
+    fn model_with_topics(visibility_merge: &str) -> Model {
+        let mut model = Model::new("Tools", "tools", None, None);
+        let mut keep = Topic::new("tools", "Rust Analyzer");
+        keep.add_temp_attribute_values(ATTRIBUTE_NAME_VISIBILITY.to_string(), vec!["Public".to_string()]);
+        keep.add_temp_attribute_values("Domain".to_string(), vec!["IDE".to_string()]);
+        model.add_topic(keep);
+        let mut merge = Topic::new("tools", "Analyzer");
+        merge.add_temp_attribute_values(ATTRIBUTE_NAME_VISIBILITY.to_string(), vec![visibility_merge.to_string()]);
+        merge.add_temp_attribute_values("Domain".to_string(), vec!["IDE".to_string(), "Language Server".to_string()]);
+        merge.add_temp_attribute_values("Language".to_string(), vec!["Rust".to_string()]);
+        merge.add_paragraph(Paragraph::new_text(TextBlock::new_resolved_text("Runs in the editor.")));
+        model.add_topic(merge);
+        model
+    }
+
+    #[test]
+    fn merge_attributes_union_and_conflicts() {
+        let topic_key_keep = TopicKey::new("tools", "Rust Analyzer");
+        let topic_key_merge = TopicKey::new("tools", "Analyzer");
+
+        let mut model = model_with_topics("Public");
+        let conflicts = merge_topics(&mut model, &topic_key_keep, &topic_key_merge).unwrap();
+        assert!(conflicts.is_empty());
+        let keep = model.get_topics().get(&topic_key_keep).unwrap();
+        assert_eq!(keep.get_temp_attributes().get("Domain").unwrap(), &vec!["IDE".to_string(), "Language Server".to_string()]);
+        assert_eq!(keep.get_temp_attributes().get("Language").unwrap(), &vec!["Rust".to_string()]);
+        assert_eq!(keep.get_temp_attributes().get(ATTRIBUTE_NAME_VISIBILITY).unwrap(), &vec!["Public".to_string()]);
+
+        // A single-valued attribute keeps the surviving topic's value and reports the other.
+        let mut model = model_with_topics("Private");
+        let conflicts = merge_topics(&mut model, &topic_key_keep, &topic_key_merge).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        let keep = model.get_topics().get(&topic_key_keep).unwrap();
+        assert_eq!(keep.get_temp_attributes().get(ATTRIBUTE_NAME_VISIBILITY).unwrap(), &vec!["Public".to_string()]);
+    }
+
+    #[test]
+    fn merge_moves_paragraphs_and_inbound_links() {
+        let topic_key_keep = TopicKey::new("tools", "Rust Analyzer");
+        let topic_key_merge = TopicKey::new("tools", "Analyzer");
+        let mut model = model_with_topics("Public");
+        let mut editors = Topic::new("tools", "Editors");
+        editors.add_paragraph(Paragraph::new_text(TextBlock::new_resolved(vec![
+            TextItem::new_text("See "),
+            TextItem::new_topic_link(&topic_key_merge),
+        ])));
+        model.add_topic(editors);
+
+        merge_topics(&mut model, &topic_key_keep, &topic_key_merge).unwrap();
+        assert!(!model.has_topic(&topic_key_merge));
+        let editors = model.get_topics().get(&TopicKey::new("tools", "Editors")).unwrap();
+        assert_eq!(editors.get_referenced_topic_keys(false, false), vec![topic_key_keep.clone()]);
+        let keep = model.get_topics().get(&topic_key_keep).unwrap();
+        let section_names = keep.get_paragraphs().iter()
+            .filter_map(|paragraph| match paragraph {
+                Paragraph::SectionHeader { name, .. } => Some(name.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(section_names, vec!["Merged from Analyzer".to_string()]);
+        assert_eq!(keep.get_paragraphs().len(), 2);
+    }
+}
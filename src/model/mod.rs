@@ -3,6 +3,8 @@ use crate::*;
 pub(crate) mod attribute;
 pub(crate) use attribute::*;
 
+pub(crate) mod auto_link;
+
 // pub(crate) mod breadcrumbs;
 // pub(crate) use breadcrumbs::*;
 
@@ -10,18 +12,27 @@ pub(crate) mod category;
 pub(crate) use category::*;
 
 pub(crate) mod date;
+pub use date::DateSource;
+
+pub(crate) mod filter;
 
 pub(crate) mod domain;
 pub(crate) use domain::*;
 
 pub(crate) mod glossary;
 
+pub(crate) mod json;
+
 pub(crate) mod link;
 pub(crate) use link::*;
 
+pub(crate) mod lint;
+
 pub(crate) mod list;
 pub(crate) use list::*;
 
+pub(crate) mod merge;
+
 // pub(crate) mod namespace;
 // pub(crate) use namespace::*;
 
@@ -35,8 +46,12 @@ pub(crate) mod parse;
 
 pub(crate) mod redaction;
 
+pub(crate) mod rename;
+
 pub(crate) mod report;
 
+pub(crate) mod split;
+
 pub(crate) mod table;
 pub(crate) use table::*;
 
@@ -58,6 +73,7 @@ pub(crate) type TopicTreeNode = util::tree::TreeNode<TopicKey>;
 pub(crate) const PANIC_ON_MODEL_ERROR: bool = true;
 
 pub(crate) const FILE_NAME_REDACT: &str = "C:/Projects/Rust/utility/wiki/redact.txt";
+pub(crate) const FILE_NAME_LINT_CONFIG: &str = "C:/Projects/Rust/utility/wiki/lint.txt";
 
 pub const NAMESPACE_ROOT: &str = "";
 // pub(crate) const NAMESPACE_UNDECIDED: &str = "{undecided}";
@@ -83,22 +99,24 @@ pub(crate) const FOLDER_WIKI_GEN_BACKUP: &str = "C:/Wiki Gen Backup";
 pub(crate) const FOLDER_PREFIX_WIKI_GEN_BACKUP: &str = "Wiki Gen";
 pub(crate) const FOLDER_WIKI_COMPARE_OLD: &str = "C:/Wiki Gen Backup/Old";
 pub(crate) const FOLDER_WIKI_COMPARE_NEW: &str = r"C:/Wiki Gen Backup/New";
+pub(crate) const FILE_NAME_LINT_RESULTS: &str = "C:/Wiki Gen Backup/Lint.json";
+pub(crate) const FILE_NAME_REDACTION_RESULTS: &str = "C:/Wiki Gen Backup/Redactions.json";
 
 pub(crate) const PREFIX_HTTP: &str = "http://";
 pub(crate) const PREFIX_HTTPS: &str = "https://";
 pub(crate) const PREFIX_SFTP: &str = "sftp://";
 
-#[allow(dead_code)]
 pub(crate) const ATTRIBUTE_VALUE_PRIVATE: &str = "Private";
-#[allow(dead_code)]
-pub(crate) const ATTRIBUTE_VALUE_PUBLIC: &str = "Public";
+pub const ATTRIBUTE_VALUE_PUBLIC: &str = "Public";
 #[allow(dead_code)]
 pub(crate) const ATTRIBUTE_VALUE_UNKNOWN: &str = "Unknown";
 
 pub(crate) const ATTRIBUTE_NAME_ABANDONED: &str = "Abandoned";
 pub(crate) const ATTRIBUTE_NAME_ACQUIRED: &str = "Acquired";
 pub(crate) const ATTRIBUTE_NAME_ADDED: &str = "Added";
+pub(crate) const ATTRIBUTE_NAME_ADDED_ESTIMATED: &str = "Added Estimated";
 pub(crate) const ATTRIBUTE_NAME_ADDRESS: &str = "Address";
+pub(crate) const ATTRIBUTE_NAME_ALIASES: &str = "Aliases";
 pub(crate) const ATTRIBUTE_NAME_AUTHOR: &str = "Author";
 pub(crate) const ATTRIBUTE_NAME_BOOK: &str = "Book";
 pub(crate) const ATTRIBUTE_NAME_CELL_PHONE: &str = "Cell Phone";
@@ -156,7 +174,8 @@ pub(crate) const ATTRIBUTE_NAME_VISIBILITY: &str = "Visibility";
 pub(crate) const ATTRIBUTE_NAME_WORK_PHONE: &str = "Work Phone";
 pub(crate) const ATTRIBUTE_NAME_YEAR: &str = "Year";
 
-pub(crate) const ATTRIBUTE_ORDER: [&str; 60] = [
+pub(crate) const ATTRIBUTE_ORDER: [&str; 62] = [
+    ATTRIBUTE_NAME_ALIASES,
     ATTRIBUTE_NAME_SCHOOL,
     ATTRIBUTE_NAME_TITLE,
     ATTRIBUTE_NAME_SERIES,
@@ -215,11 +234,13 @@ pub(crate) const ATTRIBUTE_ORDER: [&str; 60] = [
     ATTRIBUTE_NAME_PRESENTED,
     ATTRIBUTE_NAME_CREATED,
     ATTRIBUTE_NAME_ADDED,
+    ATTRIBUTE_NAME_ADDED_ESTIMATED,
     ATTRIBUTE_NAME_EDITED,
     ATTRIBUTE_NAME_VISIBILITY,
 ];
 
-pub(crate) const PUBLIC_ATTRIBUTES: [&str; 38] = [
+pub(crate) const PUBLIC_ATTRIBUTES: [&str; 40] = [
+    ATTRIBUTE_NAME_ALIASES,
     ATTRIBUTE_NAME_SCHOOL,
     ATTRIBUTE_NAME_TITLE,
     ATTRIBUTE_NAME_SERIES,
@@ -257,6 +278,7 @@ pub(crate) const PUBLIC_ATTRIBUTES: [&str; 38] = [
     ATTRIBUTE_NAME_PRESENTED,
     ATTRIBUTE_NAME_CREATED,
     ATTRIBUTE_NAME_ADDED,
+    ATTRIBUTE_NAME_ADDED_ESTIMATED,
     ATTRIBUTE_NAME_EDITED,
 ];
 
@@ -1,12 +1,12 @@
 use super::*;
 use manage_projects::model::Model as ProjectModel;
 use std::collections::BTreeMap;
-use crate::model::date::{update_date_attributes_from_file_monitor, remove_edited_same_as_added};
+use crate::model::date::{update_date_attributes, carry_over_date_attributes, FileMonitorDateProvider, GitDateProvider, remove_edited_same_as_added, estimate_added_dates, apply_added_date_estimates, AddedDateEstimate};
 use crate::model::glossary::Glossary;
+use crate::model::filter::{TopicFilterCandidate, TopicFilterResult};
+use crate::model::redaction::{Redactor, RedactionHit};
 // use crate::connectedtext::NAMESPACE_TOOLS;
 
-const CHECK_SECTION_LINKS: bool = false;
-
 pub(crate) type TopicRefs = BTreeMap<String, TopicKey>;
 pub(crate) type NameTopicMap = BTreeMap<String, TopicKey>;
 pub(crate) type GlossaryMap = BTreeMap<String, Glossary>;
@@ -15,11 +15,13 @@ pub(crate) struct Model {
     _name: String,
     main_namespace: String,
     is_filtered: bool,
-    filter_is_public: bool,
+    filter_audience: Option<String>,
     filter_root_topic_ref: Option<String>,
     namespaces: BTreeMap<String, String>,
     topics: BTreeMap<TopicKey, Topic>,
     topic_refs: TopicRefs,
+    aliases: BTreeMap<TopicKey, TopicKey>,
+    redirects: BTreeMap<TopicKey, TopicKey>,
     categories: Vec<String>,
     category_tree: Option<TopicTree>,
     subtopic_tree: Option<TopicTree>,
@@ -29,23 +31,28 @@ pub(crate) struct Model {
     projects_name_map: Option<NameTopicMap>,
     file_monitor_project: Option<file_monitor::model::Project>,
     glossaries: GlossaryMap,
-    redacted_phrases: Vec<String>,
+    redacted_phrases: Vec<(String, TopicKey)>,
+    redactor: Option<Redactor>,
+    redaction_hits: Vec<RedactionHit>,
+    topic_filter_results: BTreeMap<TopicKey, TopicFilterResult>,
     warnings: Vec<String>,
 }
 
 impl Model {
-    pub(crate) fn new(name: &str, main_namespace: &str, filter_is_public: bool, filter_root_topic_ref: Option<String>) -> Self {
+    pub(crate) fn new(name: &str, main_namespace: &str, filter_audience: Option<String>, filter_root_topic_ref: Option<String>) -> Self {
         TopicKey::assert_legal_namespace(main_namespace);
-        assert!(!filter_is_public || filter_root_topic_ref.is_none());
+        assert!(filter_audience.is_none() || filter_root_topic_ref.is_none());
         let mut wiki = Self {
             _name: name.to_string(),
             main_namespace: main_namespace.to_string(),
-            is_filtered: filter_is_public || filter_root_topic_ref.is_some(),
-            filter_is_public,
+            is_filtered: filter_audience.is_some() || filter_root_topic_ref.is_some(),
+            filter_audience,
             filter_root_topic_ref,
             namespaces: Default::default(),
             topics: Default::default(),
             topic_refs: Default::default(),
+            aliases: Default::default(),
+            redirects: Default::default(),
             categories: Default::default(),
             category_tree: None,
             subtopic_tree: None,
@@ -56,6 +63,9 @@ impl Model {
             file_monitor_project: None,
             glossaries: Default::default(),
             redacted_phrases: vec![],
+            redactor: None,
+            redaction_hits: vec![],
+            topic_filter_results: Default::default(),
             warnings: vec![],
         };
         wiki.add_namespace(main_namespace);
@@ -76,6 +86,10 @@ impl Model {
         self.namespaces.insert(name.to_string(), name.to_string());
     }
 
+    pub(crate) fn has_namespace(&self, name: &str) -> bool {
+        self.namespaces.contains_key(name)
+    }
+
     #[inline]
     pub(crate) fn qualify_namespace(&self, name: &str) -> String {
         if name.starts_with(":") {
@@ -131,11 +145,18 @@ impl Model {
     }
     */
 
-    pub(crate) fn add_topic(&mut self, topic: Topic) {
+    pub(crate) fn add_topic(&mut self, mut topic: Topic) {
         assert!(self.namespaces.contains_key(topic.get_namespace()));
         let topic_key = topic.get_topic_key();
 
         let topic_ref = make_topic_ref(topic_key.get_namespace(), topic_key.get_topic_name());
+        // A real topic takes the place of a redirect with the same name, as when a topic is
+        // renamed and then renamed back.
+        let redirect_count = self.redirects.len();
+        self.redirects.retain(|redirect_topic_key, _| make_topic_ref(redirect_topic_key.get_namespace(), redirect_topic_key.get_topic_name()).ne(&topic_ref));
+        if self.redirects.len() < redirect_count {
+            self.topic_refs.remove(&topic_ref);
+        }
         if self.topic_refs.contains_key(&topic_ref) {
             panic!("We already have this topic ref: \"{}\".", topic_ref)
         }
@@ -144,14 +165,117 @@ impl Model {
         if self.topics.contains_key(&topic_key) {
             panic!("We already have this topic key: {:?}.", topic_key)
         }
+        if let Some(result) = self.topic_filter_results.get(&topic_key) {
+            debug_assert!(result.is_included, "Topic {} was left out by the filter but is being added.", topic_key);
+            topic.set_filter_flags(result.is_included, result.is_redacted);
+        }
         self.topics.insert(topic_key, topic);
     }
 
-    /*
     pub(crate) fn remove_topic(&mut self, topic_key: &TopicKey) -> Option<Topic> {
+        // This removes the refs for the topic's aliases along with the one for its name.
+        self.topic_refs.retain(|_topic_ref, ref_topic_key| ref_topic_key.ne(topic_key));
+        self.aliases.retain(|_alias_topic_key, ref_topic_key| ref_topic_key.ne(topic_key));
+        self.attribute_list.remove_topic(topic_key);
         self.topics.remove(topic_key)
     }
-    */
+
+    pub(crate) fn add_topic_alias(&mut self, topic_key: &TopicKey, alias: &str) -> Result<(), String> {
+        // Register another name for a topic so that links written against it, like
+        // "[[tools:k8s|K8s]]", resolve to the topic. An alias lives in the topic's namespace.
+        let alias = alias.trim();
+        if !TopicKey::is_legal_topic_name(alias) {
+            return Err(format!("Model::add_topic_alias(): \"{}\" is not a legal alias for {}.", alias, topic_key));
+        }
+        let topic_ref = make_topic_ref(topic_key.get_namespace(), alias);
+        match self.topic_refs.get(&topic_ref) {
+            Some(ref_topic_key) if ref_topic_key.eq(topic_key) => Ok(()),
+            Some(ref_topic_key) => Err(format!("Model::add_topic_alias(): Alias \"{}\" for {} is already used by {}.", alias, topic_key, ref_topic_key)),
+            None => {
+                self.topic_refs.insert(topic_ref, topic_key.clone());
+                self.aliases.insert(TopicKey::new(topic_key.get_namespace(), alias), topic_key.clone());
+                Ok(())
+            },
+        }
+    }
+
+    pub(crate) fn add_topic_aliases(&mut self, topic_key: &TopicKey) -> TopicErrorList {
+        // Register the aliases from the topic's Aliases attribute, as after the topic has been
+        // removed and added back under a new key.
+        let mut errors = TopicErrorList::new();
+        let aliases = self.topics.get(topic_key).map_or(vec![], |topic| topic.get_aliases());
+        for alias in aliases.iter() {
+            if let Err(msg) = self.add_topic_alias(topic_key, alias) {
+                errors.add(topic_key, &msg);
+            }
+        }
+        errors
+    }
+
+    pub(crate) fn get_aliases(&self) -> &BTreeMap<TopicKey, TopicKey> {
+        // The key is the alias in the form of a topic key and the value is the real topic.
+        &self.aliases
+    }
+
+    pub(crate) fn add_redirect(&mut self, topic_key_from: &TopicKey, topic_key_to: &TopicKey) -> Result<(), String> {
+        // A page that's no longer a topic but sends the reader on to another one, usually left
+        // behind by a rename or merge. Links to the old name resolve to the target topic.
+        if self.topics.contains_key(topic_key_from) {
+            return Err(format!("Model::add_redirect(): Can't redirect {} since it's a topic.", topic_key_from));
+        }
+        if !self.topics.contains_key(topic_key_to) {
+            return Err(format!("Model::add_redirect(): Redirect target {} from {} not found.", topic_key_to, topic_key_from));
+        }
+        let topic_ref = make_topic_ref(topic_key_from.get_namespace(), topic_key_from.get_topic_name());
+        if let Some(ref_topic_key) = self.topic_refs.get(&topic_ref) {
+            if ref_topic_key.ne(topic_key_to) {
+                return Err(format!("Model::add_redirect(): Can't redirect {} to {} since the name is already used by {}.", topic_key_from, topic_key_to, ref_topic_key));
+            }
+        }
+        self.topic_refs.insert(topic_ref, topic_key_to.clone());
+        self.redirects.insert(topic_key_from.clone(), topic_key_to.clone());
+        Ok(())
+    }
+
+    pub(crate) fn retarget_redirects(&mut self, topic_key_old: &TopicKey, topic_key_new: &TopicKey) {
+        // Keep redirects pointing at a topic that has been renamed or merged away. Removing the
+        // old topic took away the refs for its redirects, so they're added back.
+        for (topic_key_from, topic_key_to) in self.redirects.iter_mut()
+                .filter(|(_, topic_key_to)| topic_key_to.eq(&topic_key_old)) {
+            *topic_key_to = topic_key_new.clone();
+            let topic_ref = make_topic_ref(topic_key_from.get_namespace(), topic_key_from.get_topic_name());
+            self.topic_refs.insert(topic_ref, topic_key_new.clone());
+        }
+    }
+
+    pub(crate) fn get_redirects(&self) -> &BTreeMap<TopicKey, TopicKey> {
+        // The key is the page that redirects and the value is the topic it redirects to.
+        &self.redirects
+    }
+
+    pub(crate) fn rename_topic(&mut self, topic_key_old: &TopicKey, topic_name_new: &str) -> Result<TopicKey, String> {
+        rename::rename_topic(self, topic_key_old, topic_name_new)
+    }
+
+    pub(crate) fn merge_topics(&mut self, topic_key_keep: &TopicKey, topic_key_merge: &TopicKey) -> Result<TopicErrorList, String> {
+        merge::merge_topics(self, topic_key_keep, topic_key_merge)
+    }
+
+    pub(crate) fn split_topic(&mut self, topic_key: &TopicKey, section_names: &Vec<&str>) -> Result<Vec<TopicKey>, String> {
+        split::split_topic(self, topic_key, section_names)
+    }
+
+    pub(crate) fn find_unlinked_mentions(&self) -> Vec<auto_link::UnlinkedMention> {
+        auto_link::find_unlinked_mentions(self)
+    }
+
+    pub(crate) fn link_unlinked_mentions(&mut self) -> Vec<auto_link::UnlinkedMention> {
+        auto_link::link_unlinked_mentions(self)
+    }
+
+    pub(crate) fn lint(&self, config: &lint::LintConfig) -> Vec<lint::LintResult> {
+        lint::lint_model(self, config)
+    }
 
     pub(crate) fn get_topic_name(&self, topic_key: &TopicKey) -> &str {
         assert!(self.topics.contains_key(topic_key), "Topic key {} not found.", topic_key);
@@ -327,12 +451,7 @@ impl Model {
                     LinkType::Topic { topic_key } => {
                         self.check_topic_link(&mut errors, "links", &this_topic_key, topic_key);
                     },
-                    LinkType::Section { section_key } => {
-                        if CHECK_SECTION_LINKS && !self.has_section(section_key) {
-                            self.print_sections(section_key.get_topic_key());
-                            errors.add(&topic.get_topic_key(), &format!("Model::check_links(): Section link {} not found.", section_key));
-                        }
-                    },
+                    // Section links are checked in check_section_links().
                     _ => {},
                 }
             }
@@ -340,6 +459,48 @@ impl Model {
         errors
     }
 
+    pub(crate) fn check_section_links(&self) -> TopicErrorList {
+        let mut errors = TopicErrorList::new();
+        for (topic_key, section_key, suggestion) in self.find_broken_section_links().iter() {
+            let msg = match suggestion {
+                Some(section_name) => format!("Model::check_section_links(): Section link {} not found. Did you mean \"{}\"?", section_key, section_name),
+                None => format!("Model::check_section_links(): Section link {} not found.", section_key),
+            };
+            errors.add(topic_key, &msg);
+        }
+        errors
+    }
+
+    pub(crate) fn find_broken_section_links(&self) -> Vec<(TopicKey, SectionKey, Option<String>)> {
+        // Section links whose section isn't one of the target topic's section headers, along with
+        // the closest section name in that topic if there is one. If the topic itself doesn't
+        // exist there's nothing to suggest.
+        let mut broken_links: Vec<(TopicKey, SectionKey, Option<String>)> = vec![];
+        for topic in self.topics.values() {
+            let this_topic_key = topic.get_topic_key();
+            for link_rc in topic.get_links(true, false).iter() {
+                let link = b!(link_rc);
+                if let LinkType::Section { section_key } = &link.get_type() {
+                    if self.has_section(section_key) {
+                        continue;
+                    }
+                    let is_duplicate = broken_links.iter().any(|(topic_key, broken_section_key, _)| {
+                        this_topic_key.eq(topic_key)
+                            && broken_section_key.get_topic_key().eq(section_key.get_topic_key())
+                            && broken_section_key.get_section_name().eq(section_key.get_section_name())
+                    });
+                    if is_duplicate {
+                        continue;
+                    }
+                    let suggestion = self.topics.get(section_key.get_topic_key())
+                        .and_then(|topic| topic.get_closest_section_name(section_key.get_section_name()));
+                    broken_links.push((this_topic_key.clone(), section_key.clone(), suggestion));
+                }
+            }
+        }
+        broken_links
+    }
+
     pub(crate) fn check_topic_link(&self, errors: &mut TopicErrorList, list_name: &str, this_topic_key: &TopicKey, ref_topic_key: &TopicKey) {
         if !self.has_topic(ref_topic_key) {
             errors.add(this_topic_key, &format!("wiki::check_topic_link(): Topic link {} from {} list not found.", ref_topic_key, list_name));
@@ -416,16 +577,41 @@ impl Model {
         DomainList::catalog_domains(self)
     }
 
-    /*
-    pub(crate) fn interpolate_added_date(&mut self) {
-        super::date::interpolate_added_date(self);
+    pub(crate) fn estimate_added_dates(&self) -> Vec<AddedDateEstimate> {
+        estimate_added_dates(self)
+    }
+
+    pub(crate) fn apply_added_date_estimates(&mut self, estimates: &[AddedDateEstimate]) {
+        // The new temp attributes are cataloged by update_attributes() in complete_model().
+        apply_added_date_estimates(self, estimates);
     }
-    */
 
     pub(crate) fn update_attributes_from_file_monitor(&mut self) {
         // We're still working with the temp attributes.
         AttributeType::fill_attribute_orders(self);
-        update_date_attributes_from_file_monitor(self);
+        if let Some(project) = self.get_file_monitor_project() {
+            let provider = FileMonitorDateProvider::new(project);
+            update_date_attributes(self, &provider);
+        }
+    }
+
+    pub(crate) fn carry_over_attributes_from_file_monitor(&mut self, topic_key_old: &TopicKey, topic_key_new: &TopicKey) {
+        // We're still working with the temp attributes of the renamed topic.
+        AttributeType::fill_attribute_orders(self);
+        if let Some(project) = self.get_file_monitor_project() {
+            let provider = FileMonitorDateProvider::new(project);
+            carry_over_date_attributes(self, &provider, topic_key_old, topic_key_new);
+        }
+    }
+
+    pub(crate) fn update_attributes_from_git(&mut self, path_repo: &str, ignore_authors: &[&str]) {
+        // We're still working with the temp attributes. As with a missing file monitor project,
+        // if the git history can't be read the pages simply keep the dates they already have.
+        AttributeType::fill_attribute_orders(self);
+        match GitDateProvider::read(path_repo, ignore_authors) {
+            Ok(provider) => update_date_attributes(self, &provider),
+            Err(msg) => self.warnings.push(format!("Page dates not updated from git: {}", msg)),
+        }
     }
 
     #[allow(dead_code)]
@@ -466,6 +652,18 @@ impl Model {
         &self.categories
     }
 
+    pub(crate) fn replace_category(&mut self, name_old: &str, name_new: &str) {
+        // If the new category is already in the list, as when two categories are merged, simply
+        // drop the old one.
+        if self.categories.iter().any(|category| category.eq(name_new)) {
+            self.categories.retain(|category| category.ne(name_old));
+        } else {
+            for category in self.categories.iter_mut().filter(|category| category.eq(&name_old)) {
+                *category = name_new.to_string();
+            }
+        }
+    }
+
     /*
     pub(crate) fn get_attribute_order(&self, attr_type_name: &str) -> Result<usize, String> {
         match self.attribute_list.get_attribute_orders().get(attr_type_name) {
@@ -502,6 +700,7 @@ impl Model {
         self.topics[&section_key.get_topic_key()].has_section(&section_key.get_section_name())
     }
 
+    #[allow(dead_code)]
     pub(crate) fn print_sections(&self, topic_key: &TopicKey) {
         if !self.has_topic(topic_key) {
             panic!("Can't find topic {} to print its sections.", topic_key);
@@ -556,8 +755,12 @@ impl Model {
     }
 
     pub(crate) fn get_topics_first_letter_map(&self) -> BTreeMap<String, Vec<TopicKey>> {
+        // Aliases are mixed in with the topics. The caller can tell them apart with get_aliases().
         let mut map = BTreeMap::new();
-        for topic_key in self.topic_keys_alphabetical_by_topic_name() {
+        let mut topic_keys = self.topic_keys_alphabetical_by_topic_name();
+        topic_keys.extend(self.aliases.keys().cloned());
+        TopicKey::sort_topic_keys_by_name(&mut topic_keys);
+        for topic_key in topic_keys {
             let first_char = topic_key.get_topic_name().to_uppercase().chars().next().unwrap();
             let map_key = if first_char.is_numeric() {
                 '#'.to_string()
@@ -610,8 +813,15 @@ impl Model {
         self.is_filtered
     }
 
-    pub(crate) fn filter_is_public(&self) -> bool {
-        self.filter_is_public
+    pub(crate) fn filter_audience(&self) -> Option<&str> {
+        // The audience a filtered build is for, such as "Public" or "Team".
+        self.filter_audience.as_deref()
+    }
+
+    pub(crate) fn topic_is_in_audience(&self, topic: &Topic) -> bool {
+        // True if the topic belongs in this build. Only a build for an audience leaves out topics
+        // this way, so any topic passes when there's no audience.
+        self.filter_audience.as_ref().map_or(true, |audience| topic.is_visible_to(audience))
     }
 
     pub fn filter_root_topic_ref(&self) -> &Option<String> {
@@ -623,64 +833,61 @@ impl Model {
         self.topics.get(topic_key).unwrap().is_public()
     }
 
-    pub fn add_redacted_phrase(&mut self, phrase: String) {
-        self.redacted_phrases.push(phrase);
+    pub(crate) fn add_redacted_phrase(&mut self, phrase: String, topic_key: &TopicKey) {
+        // A phrase to redact because it names a topic that's been left out of the build.
+        self.redacted_phrases.push((phrase, topic_key.clone()));
     }
 
-    /*
-    pub(crate) fn add_redacted_phrases(&mut self, phrases: &mut Vec<String>) {
-        self.redacted_phrases.append(phrases);
+    pub(crate) fn get_redactor(&self) -> &Redactor {
+        self.redactor.as_ref().expect("Model::get_redactor(): finalize_redacted_phrases() hasn't been called.")
     }
-    */
 
-    pub(crate) fn get_redacted_phrases(&self) -> &Vec<String> {
-        &self.redacted_phrases
+    pub(crate) fn add_redaction_hits(&mut self, mut hits: Vec<RedactionHit>) {
+        self.redaction_hits.append(&mut hits);
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn print_redacted_phrases(&self) {
-        println!("\nModel::print_redacted_phrases():");
-        for phrase in self.redacted_phrases.iter() {
-            println!("\t\"{}\"", phrase);
-        }
-        println!();
+    pub(crate) fn get_redaction_hits(&self) -> &Vec<RedactionHit> {
+        &self.redaction_hits
     }
 
-    pub(crate) fn filter_set_topics_include_and_redacted(&mut self) {
-        // FILTER UNCOMMENT
-        /*
-        let mut topic_is_included = true;
-        let mut topic_is_redacted = false;
-        for topic in self.topics.values_mut() {
-            let topic_is_public = topic.is_public();
-            if let Some(filter_root_topic_ref) = &self.filter_root_topic_ref {
-                let root_topic_ref = topic.get_root_topic_ref();
-                topic_is_included = root_topic_ref == filter_root_topic_ref;
-                topic_is_redacted = !topic_is_included && !topic_is_public;
-            } else {
-                assert!(self.filter_is_public);
-                topic_is_included = topic_is_public;
-                topic_is_redacted = !topic_is_included;
-            }
-            topic.is_included = topic_is_included;
-            topic.is_redacted = topic_is_redacted;
-            if !topic_is_included {
-                let topic_key = topic.get_topic_key();
-                assert!(!self.topic_files_to_delete.contains(&topic_key));
-                self.topic_files_to_delete.push(topic_key);
+    #[allow(dead_code)]
+    pub(crate) fn print_redacted_phrases(&self) {
+        self.get_redactor().print();
+    }
+
+    pub(crate) fn filter_set_topics_include_and_redacted(&mut self, candidates: &Vec<TopicFilterCandidate>) -> Vec<TopicKey> {
+        // Decide which topics go into a filtered build. This has to happen before the topics are
+        // parsed, since each page is redacted as it's parsed. A redacted topic's name, file name
+        // and topic reference become redacted phrases, so any mention of the topic in the pages
+        // that are kept is hidden. Returns the keys of the topics to leave out, whose files the
+        // build process should drop.
+        assert!(self.is_filtered);
+        self.topic_filter_results = filter::decide_topic_filter(candidates, &self.filter_audience, &self.filter_root_topic_ref);
+        let mut excluded_topic_keys = vec![];
+        let mut phrases = vec![];
+        for (topic_key, result) in self.topic_filter_results.iter() {
+            if !result.is_included {
+                excluded_topic_keys.push(topic_key.clone());
             }
-            if topic_is_redacted {
-                self.add_redacted_phrase(topic.get_name().to_string());
-                self.add_redacted_phrase(topic.get_topic_ref());
+            if result.is_redacted {
+                let topic_name = topic_key.get_topic_name();
+                phrases.push((topic_name.to_string(), topic_key.clone()));
+                phrases.push((crate::dokuwiki::legal_file_name(topic_name), topic_key.clone()));
+                phrases.push((make_topic_ref(topic_key.get_namespace(), topic_name), topic_key.clone()));
             }
         }
-         */
+        for (phrase, topic_key) in phrases.into_iter() {
+            self.add_redacted_phrase(phrase, &topic_key);
+        }
+        excluded_topic_keys
     }
 
     pub(crate) fn finalize_redacted_phrases(&mut self) {
-        // Take the value from self.redacted_phrases so that we avoid cloning it.
+        // Combine the phrases from the private topics with the rules file into the redactor
+        // that's used on every page. Take the value from self.redacted_phrases so that we avoid
+        // cloning it.
         let phrases = std::mem::replace(&mut self.redacted_phrases, vec![]);
-        self.redacted_phrases = redaction::finalize_redacted_phrases(phrases);
+        self.redactor = Some(Redactor::read(phrases, self.filter_audience.as_deref()).unwrap());
     }
 
     pub(crate) fn get_glossaries(&self) -> &GlossaryMap {
@@ -715,12 +922,6 @@ impl Model {
         }
     }
 
-    /*
-    pub(crate) fn redact(&mut self, compare_only: bool, redaction_preview_only: bool) {
-        self.redaction_record = Some(redaction::RedactionRecord::redact(self, compare_only, redaction_preview_only));
-    }
-    */
-
     /*
     pub(crate) fn remove_non_public_parent_topic_refs(&mut self) {
         assert!(self.is_public);
@@ -19,86 +19,617 @@ While refining paragraphs for a public build, ignore the Added and Visibility at
 don't call the later code that might add them (from file-monitor in the case of Added).
 */
 
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use regex::{Regex, RegexBuilder};
+
 use crate::dokuwiki::MARKER_REDACTION;
 use crate::*;
-use crate::model::FILE_NAME_REDACT;
-
-pub(crate) fn finalize_redacted_phrases(mut phrases: Vec<String>) -> Vec<String> {
-    // The model already has a list of redacted phrases consisting of the topic names and topic
-    // refs for redacted topics.
-    assert!(!phrases.is_empty());
-
-    let mut blacklist = util::file::read_file_as_lines_r(FILE_NAME_REDACT).unwrap();
-    phrases.append(&mut blacklist);
-
-    let mut whitelist = PHRASE_WHITELIST.iter().map(|x| x.to_string()).collect::<Vec<_>>();
-    // In the whitelist, add versions of the original whitelist where spaces are replaced with
-    // underscores. So if "social media" is on the list, "social_media" will be as well. This will
-    // cover more cases such as when a whitelisted phrase appears in a link.
-    whitelist.append(&mut PHRASE_WHITELIST.iter().map(|x| x.replace(" ", "_")).collect::<Vec<_>>());
-    whitelist.sort();
-    whitelist.dedup();
-
-    // Lowercase and get rid of any blank phrases and whitespace.
-    phrases = phrases.iter()
-        .map(|phrase| phrase.trim().to_lowercase())
-        .filter(|phrase| !phrase.is_empty() && !whitelist.contains(phrase))
-        .collect();
-    phrases.sort();
-    phrases.dedup();
-    //redact_phrases.sort_by_key(|x| (Reverse(x.len()), x));
-    phrases.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
-    //bg!(&self.phrases); panic!();
-    phrases
-}
-
-pub(crate) fn text_contains_phrase(text: &str, phrases: &Vec<String>) -> bool {
-    debug_assert!(!text.is_empty());
-    debug_assert!(!phrases.is_empty());
-    let text_lower = text.to_lowercase();
-    for phrase in phrases.iter() {
-        if text_lower.contains(phrase) {
-            println!("redaction::text_contains_phrase() for \"{}\": phrase found is \"{}\"", text, phrase);
-            return true;
-        }
-    }
-    false
-}
-
-pub fn redact_text(text: &str, phrases: &Vec<String>) -> Option<String> {
-    debug_assert!(!text.is_empty());
-    debug_assert!(!phrases.is_empty());
-    let mut working_text = text.to_string();
-    loop {
-        match find_match(&working_text, phrases) {
-            Some((start_index, end_index)) => {
-                working_text = format!("{}{}{}", &working_text[0..start_index], MARKER_REDACTION, &working_text[end_index..working_text.len()]);
-            },
-            None => {
-                break;
-            }
+use crate::model::{TopicKey, FILE_NAME_REDACT};
+use crate::model::json::{json_quote, json_field, json_object, json_array_lines};
+
+const PREFIX_RULE_PARTIAL: &str = "partial:";
+const PREFIX_RULE_REGEX: &str = "regex:";
+const PREFIX_RULE_ALLOW: &str = "allow:";
+const DELIM_RULE_SCOPE_START: &str = "[";
+const DELIM_RULE_SCOPE_END: &str = "]";
+const DELIM_RULE_AUDIENCE_START: &str = "{";
+const DELIM_RULE_AUDIENCE_END: &str = "}";
+// How much of the line on either side of a redaction to show in the report.
+const REDACTION_HIT_CONTEXT_CHARS: usize = 40;
+
+// Where a redaction rule came from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum RedactionSource {
+    // The name, file name or topic reference of a topic left out of the build.
+    PrivateTopic { topic_key: TopicKey },
+    // A literal phrase from the rules file.
+    Blacklist { line: String },
+    // A regular expression from the rules file.
+    Regex { line: String },
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum RedactionPattern {
+    Literal { phrase: String, whole_word: bool },
+    Regex { regex: Regex },
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct RedactionRule {
+    pattern: RedactionPattern,
+    source: RedactionSource,
+    // If empty the rule applies to pages in every namespace.
+    namespaces: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct RedactionMatch {
+    start: usize,
+    end: usize,
+    rule_index: usize,
+}
+
+// One place where text in a page was redacted.
+#[derive(Clone, Debug)]
+pub(crate) struct RedactionHit {
+    topic_key: TopicKey,
+    line_number: usize,
+    matched_text: String,
+    before: String,
+    after: String,
+    rule_description: String,
+    source: RedactionSource,
+}
+
+pub(crate) struct Redactor {
+    // The literal rules come first so that an Aho-Corasick pattern ID is also an index into the
+    // rules.
+    rules: Vec<RedactionRule>,
+    literal_rule_count: usize,
+    literal_matcher: Option<AhoCorasick>,
+    whitelist: Vec<String>,
+    whitelist_matcher: Option<AhoCorasick>,
+}
+
+impl RedactionSource {
+    pub(crate) fn description(&self) -> String {
+        match self {
+            RedactionSource::PrivateTopic { topic_key } => format!("private topic {}", topic_key),
+            RedactionSource::Blacklist { line } => format!("blacklist \"{}\"", line),
+            RedactionSource::Regex { line } => format!("regex \"{}\"", line),
+        }
+    }
+
+    pub(crate) fn get_kind_name(&self) -> &'static str {
+        match self {
+            RedactionSource::PrivateTopic { .. } => "private topic",
+            RedactionSource::Blacklist { .. } => "blacklist",
+            RedactionSource::Regex { .. } => "regex",
+        }
+    }
+}
+
+impl RedactionMatch {
+    pub(crate) fn get_start(&self) -> usize {
+        self.start
+    }
+
+    pub(crate) fn get_end(&self) -> usize {
+        self.end
+    }
+}
+
+impl RedactionHit {
+    pub(crate) fn get_topic_key(&self) -> &TopicKey {
+        &self.topic_key
+    }
+
+    pub(crate) fn get_line_number(&self) -> usize {
+        self.line_number
+    }
+
+    pub(crate) fn get_matched_text(&self) -> &str {
+        &self.matched_text
+    }
+
+    pub(crate) fn get_before(&self) -> &str {
+        &self.before
+    }
+
+    pub(crate) fn get_after(&self) -> &str {
+        &self.after
+    }
+
+    pub(crate) fn get_rule_description(&self) -> &str {
+        &self.rule_description
+    }
+
+    pub(crate) fn get_source(&self) -> &RedactionSource {
+        &self.source
+    }
+}
+
+pub(crate) fn redaction_hits_to_json(hits: &Vec<RedactionHit>) -> String {
+    // An array with one object per redaction, like:
+    //   [
+    //     {"Topic": "Rust", "Namespace": "tools", "Line": 12, "Match": "Acme Corp", "Before": "worked on this at ", "After": " in 2019.", "Rule": "\"Acme Corp\"", "Source": "blacklist", "SourceDetail": "blacklist \"Acme Corp\""}
+    //   ]
+    let items = hits.iter()
+        .map(|hit| json_object(&[
+            json_field("Topic", &json_quote(hit.topic_key.get_topic_name())),
+            json_field("Namespace", &json_quote(hit.topic_key.get_namespace())),
+            json_field("Line", &hit.line_number.to_string()),
+            json_field("Match", &json_quote(&hit.matched_text)),
+            json_field("Before", &json_quote(&hit.before)),
+            json_field("After", &json_quote(&hit.after)),
+            json_field("Rule", &json_quote(&hit.rule_description)),
+            json_field("Source", &json_quote(hit.source.get_kind_name())),
+            json_field("SourceDetail", &json_quote(&hit.source.description())),
+        ]))
+        .collect::<Vec<_>>();
+    json_array_lines(&items)
+}
+
+impl RedactionRule {
+    pub(crate) fn new_literal(phrase: &str, whole_word: bool, source: RedactionSource, namespaces: Vec<String>) -> Self {
+        Self {
+            pattern: RedactionPattern::Literal { phrase: phrase.trim().to_string(), whole_word },
+            source,
+            namespaces,
+        }
+    }
+
+    pub(crate) fn new_regex(pattern: &str, source: RedactionSource, namespaces: Vec<String>) -> Result<Self, String> {
+        let regex = RegexBuilder::new(pattern).case_insensitive(true).build()
+            .map_err(|err| format!("Bad redaction regex \"{}\": {}", pattern, err))?;
+        Ok(Self {
+            pattern: RedactionPattern::Regex { regex },
+            source,
+            namespaces,
+        })
+    }
+
+    fn applies_to_namespace(&self, namespace: &str) -> bool {
+        self.namespaces.is_empty() || self.namespaces.iter().any(|rule_namespace| rule_namespace.trim_start_matches(':').eq_ignore_ascii_case(namespace.trim_start_matches(':')))
+    }
+
+    fn description(&self) -> String {
+        match &self.pattern {
+            RedactionPattern::Literal { phrase, whole_word } => format!("\"{}\"{}", phrase, if *whole_word { "" } else { " (partial)" }),
+            RedactionPattern::Regex { regex } => format!("/{}/", regex.as_str()),
+        }
+    }
+}
+
+pub(crate) fn read_rules_file(file_name: &str, audience: Option<&str>) -> Result<(Vec<RedactionRule>, Vec<String>), String> {
+    // The rules file has one rule per line, like:
+    //   Acme Corp
+    //   partial: acme
+    //   regex: \bproj-\d+\b
+    //   allow: Acme Corporation Museum
+    //   [tools, book] Acme Corp
+    //   {Public, Team} Acme Corp
+    //   {Client Acme} allow: Acme Corp
+    // A plain line is a phrase that's redacted where it appears as whole words, so "Ann" doesn't
+    // redact "Annotation". A "partial:" phrase is redacted even inside a word. A "regex:" line is
+    // a regular expression, and like the phrases it ignores case. An "allow:" line is a whitelist
+    // entry, which is never redacted and protects any phrase inside it. Any rule may start with a
+    // list of namespaces in brackets, in which case it applies only to pages in those
+    // namespaces. Any line, including an "allow:" line, may start with a list of audiences in
+    // braces, in which case it's used only in a build for one of those audiences. Blank lines and
+    // lines starting with "#" are skipped.
+    let mut rules = vec![];
+    let mut whitelist = vec![];
+    if !util::file::path_exists(file_name) {
+        return Ok((rules, whitelist));
+    }
+    for line in util::file::read_file_as_lines_r(file_name).map_err(|err| format!("read_rules_file(): Unable to read \"{}\": {:?}", file_name, err))?.iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let context = format!("redaction::read_rules_file(): line \"{}\" in \"{}\"", line, file_name);
+        let (audiences, line) = split_rule_audiences(line).map_err(|msg| format!("{}: {}", context, msg))?;
+        if !audiences.is_empty() && !audience.map_or(false, |audience| audiences.iter().any(|rule_audience| rule_audience.eq_ignore_ascii_case(audience))) {
+            continue;
+        }
+        match parse_rule_line(line).map_err(|msg| format!("{}: {}", context, msg))? {
+            RuleLine::Rule(rule) => rules.push(rule),
+            RuleLine::Allow(phrase) => whitelist.push(phrase),
+        }
+    }
+    Ok((rules, whitelist))
+}
+
+fn split_rule_audiences(line: &str) -> Result<(Vec<String>, &str), String> {
+    // Separate the optional list of audiences at the start of a line from the rest of it.
+    let line = line.trim();
+    if !line.starts_with(DELIM_RULE_AUDIENCE_START) {
+        return Ok((vec![], line));
+    }
+    if !line.contains(DELIM_RULE_AUDIENCE_END) {
+        return Err(format!("Expected \"{}\" after the list of audiences.", DELIM_RULE_AUDIENCE_END));
+    }
+    let audiences = util::parse::between(line, DELIM_RULE_AUDIENCE_START, DELIM_RULE_AUDIENCE_END).split(',')
+        .map(|audience| audience.trim().to_string())
+        .filter(|audience| !audience.is_empty())
+        .collect::<Vec<_>>();
+    if audiences.is_empty() {
+        return Err("Empty list of audiences.".to_string());
+    }
+    Ok((audiences, util::parse::after(line, DELIM_RULE_AUDIENCE_END).trim()))
+}
+
+pub(crate) enum RuleLine {
+    Rule(RedactionRule),
+    Allow(String),
+}
+
+pub(crate) fn parse_rule_line(line: &str) -> Result<RuleLine, String> {
+    let mut line = line.trim();
+    let mut namespaces = vec![];
+    if line.starts_with(DELIM_RULE_SCOPE_START) {
+        if !line.contains(DELIM_RULE_SCOPE_END) {
+            return Err(format!("Expected \"{}\" after the list of namespaces.", DELIM_RULE_SCOPE_END));
         }
+        namespaces = util::parse::between(line, DELIM_RULE_SCOPE_START, DELIM_RULE_SCOPE_END).split(',')
+            .map(|namespace| namespace.trim().to_string())
+            .filter(|namespace| !namespace.is_empty())
+            .collect();
+        line = util::parse::after(line, DELIM_RULE_SCOPE_END).trim();
     }
-    if working_text.ne(text) {
-        Some(working_text)
+    let source = RedactionSource::Blacklist { line: line.to_string() };
+    let rule_line = if line.starts_with(PREFIX_RULE_ALLOW) {
+        if !namespaces.is_empty() {
+            return Err("A whitelist entry can't be limited to namespaces.".to_string());
+        }
+        RuleLine::Allow(util::parse::after(line, PREFIX_RULE_ALLOW).trim().to_string())
+    } else if line.starts_with(PREFIX_RULE_REGEX) {
+        let source = RedactionSource::Regex { line: line.to_string() };
+        RuleLine::Rule(RedactionRule::new_regex(util::parse::after(line, PREFIX_RULE_REGEX).trim(), source, namespaces)?)
+    } else if line.starts_with(PREFIX_RULE_PARTIAL) {
+        RuleLine::Rule(RedactionRule::new_literal(util::parse::after(line, PREFIX_RULE_PARTIAL), false, source, namespaces))
     } else {
-        None
+        RuleLine::Rule(RedactionRule::new_literal(line, true, source, namespaces))
+    };
+    match &rule_line {
+        RuleLine::Rule(RedactionRule { pattern: RedactionPattern::Literal { phrase, .. }, .. }) | RuleLine::Allow(phrase) if phrase.is_empty() => Err("Empty phrase.".to_string()),
+        _ => Ok(rule_line),
     }
 }
 
-fn find_match(text: &str, phrases: &Vec<String>) -> Option<(usize, usize)> {
-    debug_assert!(!text.is_empty());
-    debug_assert!(!phrases.is_empty());
-    let text_lower = text.to_lowercase();
-    for phrase in phrases.iter() {
-        if let Some(pos) = text_lower.find(phrase) {
-            return Some((pos, pos + phrase.len()))
+impl Redactor {
+    pub(crate) fn read(private_topic_phrases: Vec<(String, TopicKey)>, audience: Option<&str>) -> Result<Self, String> {
+        // The model already has a list of redacted phrases consisting of the topic names and
+        // topic refs for the topics redacted for this audience. Add the rules from the rules
+        // file that apply to the audience.
+        let mut rules = private_topic_phrases.into_iter()
+            .map(|(phrase, topic_key)| RedactionRule::new_literal(&phrase, true, RedactionSource::PrivateTopic { topic_key }, vec![]))
+            .collect::<Vec<_>>();
+        let (mut file_rules, mut whitelist) = read_rules_file(FILE_NAME_REDACT, audience)?;
+        rules.append(&mut file_rules);
+        whitelist.extend(PHRASE_WHITELIST.iter().map(|phrase| phrase.to_string()));
+        // In the whitelist, add versions of the original whitelist where spaces are replaced with
+        // underscores. So if "social media" is on the list, "social_media" will be as well. This
+        // will cover more cases such as when a whitelisted phrase appears in a link.
+        let underscored = whitelist.iter().map(|phrase| phrase.replace(" ", "_")).collect::<Vec<_>>();
+        whitelist.extend(underscored);
+        Ok(Self::new(rules, whitelist))
+    }
+
+    pub(crate) fn new(rules: Vec<RedactionRule>, whitelist: Vec<String>) -> Self {
+        // Get rid of blank or duplicate phrases and any that are themselves whitelisted. The
+        // first rule for a phrase wins, so a private topic's name is reported as such rather than
+        // as a blacklist entry.
+        let mut whitelist = whitelist.iter()
+            .map(|phrase| fold_case(phrase.trim()).text)
+            .filter(|phrase| !phrase.is_empty())
+            .collect::<Vec<_>>();
+        whitelist.sort();
+        whitelist.dedup();
+        let mut literal_rules: Vec<RedactionRule> = vec![];
+        let mut regex_rules = vec![];
+        for rule in rules.into_iter() {
+            match &rule.pattern {
+                RedactionPattern::Literal { phrase, whole_word } => {
+                    let phrase_lower = fold_case(phrase).text;
+                    let is_duplicate = literal_rules.iter().any(|other| match &other.pattern {
+                        RedactionPattern::Literal { phrase: other_phrase, whole_word: other_whole_word } => fold_case(other_phrase).text.eq(&phrase_lower) && other_whole_word == whole_word && other.namespaces == rule.namespaces,
+                        _ => false,
+                    });
+                    if !phrase.is_empty() && !is_duplicate && whitelist.binary_search(&phrase_lower).is_err() {
+                        literal_rules.push(rule);
+                    }
+                },
+                RedactionPattern::Regex { .. } => regex_rules.push(rule),
+            }
+        }
+        let literal_rule_count = literal_rules.len();
+        let literal_matcher = Self::make_matcher(literal_rules.iter().map(|rule| match &rule.pattern {
+            RedactionPattern::Literal { phrase, .. } => fold_case(phrase).text,
+            _ => unreachable!(),
+        }).collect());
+        let whitelist_matcher = Self::make_matcher(whitelist.clone());
+        let mut rules = literal_rules;
+        rules.append(&mut regex_rules);
+        Self {
+            rules,
+            literal_rule_count,
+            literal_matcher,
+            whitelist,
+            whitelist_matcher,
+        }
+    }
+
+    fn make_matcher(patterns: Vec<String>) -> Option<AhoCorasick> {
+        // The patterns are already case-folded, and the text will be too. The matcher reports
+        // every phrase found, including ones that overlap, since a phrase may be thrown out
+        // later for not being whole words or for being in the wrong namespace, and then a
+        // shorter phrase at the same place still has to be caught.
+        if patterns.is_empty() {
+            None
+        } else {
+            Some(AhoCorasickBuilder::new()
+                .match_kind(MatchKind::Standard)
+                .build(patterns))
+        }
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn get_whitelist(&self) -> &Vec<String> {
+        &self.whitelist
+    }
+
+    pub(crate) fn find_matches(&self, text: &str, namespace: &str) -> Vec<RedactionMatch> {
+        // Find every place in the text that should be redacted, in order and without overlaps,
+        // leaving out anything inside a whitelisted phrase. All of the literal phrases are found
+        // in one pass, followed by one pass for each regex.
+        // The literal phrases and the whitelist are matched against a case-folded copy of the
+        // text, and each match is mapped back to a range in the original.
+        let folded = fold_case(text);
+        let protected_ranges = self.whitelist_matcher.as_ref().map_or(vec![], |matcher| matcher.find_overlapping_iter(&folded.text).map(|found| folded.original_range(found.start(), found.end())).collect::<Vec<_>>());
+        let is_protected = |start: usize, end: usize| protected_ranges.iter().any(|(protected_start, protected_end)| start >= *protected_start && end <= *protected_end);
+        let mut matches = vec![];
+        if let Some(matcher) = &self.literal_matcher {
+            for found in matcher.find_overlapping_iter(&folded.text) {
+                let rule_index = found.pattern();
+                let rule = &self.rules[rule_index];
+                let whole_word = match &rule.pattern {
+                    RedactionPattern::Literal { whole_word, .. } => *whole_word,
+                    _ => false,
+                };
+                let (start, end) = folded.original_range(found.start(), found.end());
+                if rule.applies_to_namespace(namespace)
+                        && (!whole_word || is_whole_word(text, start, end))
+                        && !is_protected(start, end) {
+                    matches.push(RedactionMatch { start, end, rule_index });
+                }
+            }
+        }
+        for (rule_index, rule) in self.rules.iter().enumerate().skip(self.literal_rule_count)
+                .filter(|(_, rule)| rule.applies_to_namespace(namespace)) {
+            if let RedactionPattern::Regex { regex } = &rule.pattern {
+                for found in regex.find_iter(text).filter(|found| !found.as_str().is_empty()) {
+                    if !is_protected(found.start(), found.end()) {
+                        matches.push(RedactionMatch { start: found.start(), end: found.end(), rule_index });
+                    }
+                }
+            }
+        }
+        // Where matches overlap, keep the one that starts first, or if they start at the same
+        // place the longer one.
+        matches.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+        let mut kept: Vec<RedactionMatch> = vec![];
+        for found in matches.into_iter() {
+            if kept.last().map_or(true, |last| found.start >= last.end) {
+                kept.push(found);
+            }
+        }
+        kept
+    }
+
+    pub(crate) fn describe_match(&self, found: &RedactionMatch) -> String {
+        let rule = &self.rules[found.rule_index];
+        format!("{} from {}", rule.description(), rule.source.description())
+    }
+
+    pub(crate) fn text_contains_phrase(&self, text: &str, namespace: &str) -> bool {
+        match self.find_matches(text, namespace).first() {
+            Some(found) => {
+                println!("Redactor::text_contains_phrase() for \"{}\": phrase found is \"{}\" from rule {}", text, &text[found.start..found.end], self.rules[found.rule_index].description());
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub(crate) fn redact_text(&self, text: &str, namespace: &str) -> Option<String> {
+        Self::apply_matches(text, &self.find_matches(text, namespace))
+    }
+
+    pub(crate) fn apply_matches(text: &str, matches: &Vec<RedactionMatch>) -> Option<String> {
+        // Replace each match from find_matches() with the redaction marker.
+        if matches.is_empty() {
+            return None;
         }
+        let mut redacted_text = String::with_capacity(text.len());
+        let mut position = 0;
+        for found in matches.iter() {
+            redacted_text.push_str(&text[position..found.start]);
+            redacted_text.push_str(MARKER_REDACTION);
+            position = found.end;
+        }
+        redacted_text.push_str(&text[position..]);
+        Some(redacted_text)
+    }
+
+    pub(crate) fn make_hits(&self, topic_key: &TopicKey, text: &str, matches: &Vec<RedactionMatch>) -> Vec<RedactionHit> {
+        // Describe each match from find_matches() for the redaction report, with the line it's on
+        // and some of the text around it.
+        matches.iter()
+            .map(|found| {
+                let line_start = text[..found.start].rfind('\n').map_or(0, |index| index + 1);
+                let line_end = text[found.end..].find('\n').map_or(text.len(), |index| found.end + index);
+                let before = &text[line_start..found.start];
+                let after = &text[found.end..line_end];
+                let before_char_count = before.chars().count();
+                let before = before.chars().skip(before_char_count.saturating_sub(REDACTION_HIT_CONTEXT_CHARS)).collect::<String>();
+                let after = after.chars().take(REDACTION_HIT_CONTEXT_CHARS).collect::<String>();
+                let rule = &self.rules[found.rule_index];
+                RedactionHit {
+                    topic_key: topic_key.clone(),
+                    line_number: text[..found.start].matches('\n').count() + 1,
+                    matched_text: text[found.start..found.end].to_string(),
+                    before,
+                    after,
+                    rule_description: rule.description(),
+                    source: rule.source.clone(),
+                }
+            })
+            .collect()
     }
-    None
+
+    pub(crate) fn print(&self) {
+        println!("\nRedactor::print():");
+        for rule in self.rules.iter() {
+            let scope = if rule.namespaces.is_empty() { "".to_string() } else { format!(" in [{}]", rule.namespaces.join(", ")) };
+            println!("\t{}{} from {}", rule.description(), scope, rule.source.description());
+        }
+        println!();
+    }
+}
+
+// A lowercased copy of some text, along with where each of its bytes came from in the original.
+// Lowercasing a character can change how many bytes it takes, as with "İ", so the offsets of a
+// match in the copy can't be used directly on the original.
+struct FoldedText {
+    text: String,
+    // For each byte of the copy, the start and end in the original of the character it came from.
+    original_starts: Vec<usize>,
+    original_ends: Vec<usize>,
+}
+
+impl FoldedText {
+    fn original_range(&self, start: usize, end: usize) -> (usize, usize) {
+        debug_assert!(start < end);
+        (self.original_starts[start], self.original_ends[end - 1])
+    }
+}
+
+fn fold_case(text: &str) -> FoldedText {
+    // Lowercase one character at a time with the Unicode rules, so that "MÜLLER" and "Müller"
+    // come out the same. The rule phrases are folded the same way as the page text. This isn't
+    // str::to_lowercase(), which treats a final sigma differently depending on what follows it.
+    let mut folded = FoldedText { text: String::with_capacity(text.len()), original_starts: vec![], original_ends: vec![] };
+    for (original_start, c) in text.char_indices() {
+        let original_end = original_start + c.len_utf8();
+        for c_lower in c.to_lowercase() {
+            folded.text.push(c_lower);
+            for _ in 0..c_lower.len_utf8() {
+                folded.original_starts.push(original_start);
+                folded.original_ends.push(original_end);
+            }
+        }
+    }
+    folded
+}
+
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    // A phrase that begins or ends with a letter or digit has to be set off from whatever comes
+    // before or after it. A phrase like "tools:acme_" that ends with punctuation can run straight
+    // into the next word.
+    let is_word_char = |c: char| c.is_alphanumeric();
+    let phrase = &text[start..end];
+    let before_ok = !phrase.chars().next().map_or(false, is_word_char)
+        || !text[..start].chars().next_back().map_or(false, is_word_char);
+    let after_ok = !phrase.chars().next_back().map_or(false, is_word_char)
+        || !text[end..].chars().next().map_or(false, is_word_char);
+    before_ok && after_ok
 }
 
 const PHRASE_WHITELIST: [&str; 36] = ["behavioral economics", "bluehost", "bold", "domains", "grit", "health", "keto", "machines", "main", "meetings",
     "meetup", "music", "nlp", "nori", "oracle vm virtualbox", "organizations", "oracle vm virtualbox", "pcs", "philips hue", "pmwiki", "podcasts", "practices",
     "precalculus", "privacy", "queue", "rework", "sbt", "security project", "simplify", "skype", "social media platform", "to do", "twitter", "virtualbox",
     "winit", "wordpress"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(lines: &[&str]) -> Redactor {
+        let mut rules = vec![];
+        let mut whitelist = vec![];
+        for line in lines.iter() {
+            match parse_rule_line(line).unwrap() {
+                RuleLine::Rule(rule) => rules.push(rule),
+                RuleLine::Allow(phrase) => whitelist.push(phrase),
+            }
+        }
+        Redactor::new(rules, whitelist)
+    }
+
+    fn redact(redactor: &Redactor, text: &str, namespace: &str) -> String {
+        redactor.redact_text(text, namespace).unwrap_or(text.to_string())
+    }
+
+    #[test]
+    fn whole_word_phrases_leave_longer_words_alone() {
+        let redactor = redactor(&["Ann"]);
+        assert_eq!(redact(&redactor, "Ann wrote an Annotation.", "tools"), format!("{} wrote an Annotation.", MARKER_REDACTION));
+    }
+
+    #[test]
+    fn partial_phrases_match_inside_words() {
+        let redactor = redactor(&["partial: acme"]);
+        assert_eq!(redact(&redactor, "See Acmeworks.", "tools"), format!("See {}works.", MARKER_REDACTION));
+    }
+
+    #[test]
+    fn longest_phrase_wins_and_whitelist_protects() {
+        let redactor = redactor(&["Acme", "Acme Corp", "allow: Acme Corp Museum"]);
+        assert_eq!(redact(&redactor, "Acme Corp and Acme, but not the Acme Corp Museum.", "tools"),
+            format!("{} and {}, but not the Acme Corp Museum.", MARKER_REDACTION, MARKER_REDACTION));
+    }
+
+    #[test]
+    fn shorter_phrase_is_found_when_longer_one_is_not_a_whole_word() {
+        let redactor = redactor(&["Acme", "Acme Corp"]);
+        assert_eq!(redact(&redactor, "Acme Corporation", "tools"), format!("{} Corporation", MARKER_REDACTION));
+    }
+
+    #[test]
+    fn scoped_phrase_does_not_hide_shorter_global_phrase() {
+        let redactor = redactor(&["Acme", "[book] Acme Corp"]);
+        assert_eq!(redact(&redactor, "Acme Corp", "tools"), format!("{} Corp", MARKER_REDACTION));
+        assert_eq!(redact(&redactor, "Acme Corp", "book"), MARKER_REDACTION);
+    }
+
+    #[test]
+    fn case_is_folded_beyond_ascii() {
+        // "İ" takes more bytes once lowercased, which mustn't throw off where the match lands.
+        let redactor = redactor(&["Müller"]);
+        assert_eq!(redact(&redactor, "İlse asked MÜLLER.", "tools"), format!("İlse asked {}.", MARKER_REDACTION));
+    }
+
+    #[test]
+    fn regex_and_namespace_scope() {
+        let redactor = redactor(&["[book] regex: \\bproj-\\d+\\b"]);
+        assert_eq!(redact(&redactor, "Ticket PROJ-42.", "book"), format!("Ticket {}.", MARKER_REDACTION));
+        assert_eq!(redact(&redactor, "Ticket PROJ-42.", "tools"), "Ticket PROJ-42.");
+    }
+
+    #[test]
+    fn bad_lines_are_errors() {
+        assert!(parse_rule_line("regex: (unclosed").is_err());
+        assert!(parse_rule_line("[tools allow: x").is_err());
+        assert!(parse_rule_line("partial:").is_err());
+        assert!(split_rule_audiences("{Team Acme Corp").is_err());
+        assert!(split_rule_audiences("{} Acme Corp").is_err());
+    }
+
+    #[test]
+    fn audience_list_is_split_from_the_rule() {
+        let (audiences, line) = split_rule_audiences("{Public, Client Acme} [book] Acme Corp").unwrap();
+        assert_eq!(audiences, vec!["Public".to_string(), "Client Acme".to_string()]);
+        assert_eq!(line, "[book] Acme Corp");
+        assert_eq!(split_rule_audiences("Acme Corp").unwrap(), (vec![], "Acme Corp"));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::model::{Model, Topic, TopicKey, LinkRc};
+
+pub(crate) fn rename_topic(model: &mut Model, topic_key_old: &TopicKey, topic_name_new: &str) -> Result<TopicKey, String> {
+    // Rename a topic and point everything that refers to it at the new name: topic and section
+    // links in every paragraph, the parents behind the breadcrumbs, glossary links, and, if it's a
+    // category topic, the category of each topic in that category. The subtopic and category
+    // trees and the inbound links are rebuilt later from these when the model is completed.
+    let context = "rename_topic()";
+    let topic_name_new = topic_name_new.trim();
+    if !TopicKey::is_legal_topic_name(topic_name_new) {
+        return Err(format!("{}: \"{}\" is not a legal topic name.", context, topic_name_new));
+    }
+    if !model.get_topics().contains_key(topic_key_old) {
+        return Err(format!("{}: Topic {} not found.", context, topic_key_old));
+    }
+    let topic_key_new = TopicKey::new(topic_key_old.get_namespace(), topic_name_new);
+    if model.get_topics().contains_key(&topic_key_new) {
+        return Err(format!("{}: Can't rename {} because {} already exists.", context, topic_key_old, topic_key_new));
+    }
+
+    let is_category = model.get_categories().iter().any(|category| category.eq(topic_key_old.get_topic_name()));
+    let mut topic = model.remove_topic(topic_key_old).unwrap();
+    topic.set_name(topic_name_new);
+    attributes_to_temp(model, &mut topic);
+    model.add_topic(topic);
+    model.add_topic_aliases(&topic_key_new).print_without_panic(Some(context));
+
+    let link_count = update_links(model, None, topic_key_old, &topic_key_new);
+    model.retarget_redirects(topic_key_old, &topic_key_new);
+
+    if is_category {
+        replace_category(model, topic_key_old.get_topic_name(), topic_name_new);
+    }
+
+    println!("Renamed {} to {}, updating {} links.", topic_key_old, topic_key_new, link_count);
+    Ok(topic_key_new)
+}
+
+pub(crate) fn update_links(model: &mut Model, topic_keys: Option<&Vec<TopicKey>>, topic_key_old: &TopicKey, topic_key_new: &TopicKey) -> usize {
+    // Point the links in the paragraphs and parents of the given topics, or of all topics, at
+    // the new topic key, along with the glossary links. Returns the number of links changed.
+    let mut link_count = 0;
+    for topic in model.get_topics().values()
+            .filter(|topic| topic_keys.map_or(true, |topic_keys| topic_keys.contains(&topic.get_topic_key()))) {
+        let mut links = topic.get_links(true, true);
+        links.append(&mut (0..topic.get_parent_count()).map(|index| topic.get_parent(index)).collect());
+        link_count += update_link_list(&links, topic_key_old, topic_key_new);
+    }
+    for glossary in model.get_glossaries().values() {
+        link_count += update_link_list(&glossary.get_links(), topic_key_old, topic_key_new);
+    }
+    let mut glossaries = model.take_glossaries();
+    for glossary in glossaries.values_mut() {
+        if glossary.topic_key.as_ref().map_or(false, |topic_key| topic_key.eq(topic_key_old)) {
+            glossary.topic_key = Some(topic_key_new.clone());
+        }
+    }
+    model.set_glossaries(glossaries);
+    link_count
+}
+
+fn update_link_list(links: &Vec<LinkRc>, topic_key_old: &TopicKey, topic_key_new: &TopicKey) -> usize {
+    // The same link may show up more than once, such as a list item that's also in a generated
+    // list, but after the first update it no longer matches so it's only counted once.
+    links.iter()
+        .filter(|link_rc| link_rc.borrow_mut().update_topic_key(topic_key_old, topic_key_new))
+        .count()
+}
+
+pub(crate) fn replace_category(model: &mut Model, category_name_old: &str, category_name_new: &str) {
+    // Move the topics in a category whose topic is being renamed or merged away.
+    for topic in model.get_topics_mut().values_mut()
+            .filter(|topic| topic.has_category(category_name_old)) {
+        topic.replace_category(category_name_old, category_name_new);
+    }
+    model.replace_category(category_name_old, category_name_new);
+}
+
+pub(crate) fn attributes_to_temp(model: &Model, topic: &mut Topic) {
+    // The model's list of attribute values refers to topics by key, so a topic that's getting a
+    // new key goes back to having temp attributes. These are cataloged again under the new key
+    // when the model is completed. The display form is used because that's what the parser
+    // reads from the pages.
+    let temp_attributes = topic.get_attributes().iter()
+        .map(|(attr_type_name, attr_instance)| {
+            let values = attr_instance.get_values().iter()
+                .map(|value| match model.get_attribute_type(attr_type_name) {
+                    Some(attr_type) => attr_type.get_value_display_string(value),
+                    None => value.clone(),
+                })
+                .collect::<Vec<_>>();
+            (attr_type_name.clone(), values)
+        })
+        .collect::<Vec<_>>();
+    topic.clear_attributes();
+    for (attr_type_name, values) in temp_attributes.into_iter() {
+        topic.add_temp_attribute_values(attr_type_name, values);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::*;
+use crate::model::{Model, Topic, TopicKey, Paragraph, LinkRc, Link, date_now_to_doc_format, ATTRIBUTE_NAME_ADDED, ATTRIBUTE_NAME_VISIBILITY};
+
+struct SplitSection {
+    name: String,
+    subsection_names: Vec<String>,
+    paragraphs: Vec<Paragraph>,
+    topic_key: Option<TopicKey>,
+}
+
+pub(crate) fn split_topic(model: &mut Model, topic_key: &TopicKey, section_names: &Vec<&str>) -> Result<Vec<TopicKey>, String> {
+    // Turn the named sections of a long topic into subtopics of that topic. Each section, along
+    // with any deeper sections inside it, becomes the body of a new topic whose parent is the
+    // original topic, so the original page picks up a generated Subtopics list in place of the
+    // sections. Section links anywhere in the wiki that pointed to the moved sections are
+    // rewritten to point to the new topics. Returns the keys of the new topics.
+    let context = "split_topic()";
+    if !model.get_topics().contains_key(topic_key) {
+        return Err(format!("{}: Topic {} not found.", context, topic_key));
+    }
+    if section_names.is_empty() {
+        return Err(format!("{}: No sections given for {}.", context, topic_key));
+    }
+
+    let paragraphs = model.find_topic_mut(topic_key, context).take_paragraphs();
+    let paragraphs_backup = paragraphs.clone();
+    let (paragraphs_keep, mut sections) = take_sections(paragraphs, section_names);
+    let result = validate_sections(model, topic_key, section_names, &mut sections);
+    if let Err(msg) = result {
+        // Leave the topic the way it was.
+        let topic = model.find_topic_mut(topic_key, context);
+        for paragraph in paragraphs_backup.into_iter() {
+            topic.add_paragraph(paragraph);
+        }
+        return Err(format!("{}: {}", context, msg));
+    }
+    let topic = model.find_topic_mut(topic_key, context);
+    for paragraph in paragraphs_keep.into_iter() {
+        topic.add_paragraph(paragraph);
+    }
+    let visibility = topic.get_temp_attributes().get(ATTRIBUTE_NAME_VISIBILITY).cloned()
+        .or_else(|| topic.get_attributes().get(ATTRIBUTE_NAME_VISIBILITY).map(|attr_instance| attr_instance.get_values().clone()));
+
+    let mut topic_keys_new = vec![];
+    for section in sections.iter_mut() {
+        let topic_key_new = section.topic_key.clone().unwrap();
+        let mut topic_new = Topic::new(topic_key_new.get_namespace(), topic_key_new.get_topic_name());
+        topic_new.set_parents(vec![r!(Link::new_topic_from_key(None, topic_key))]);
+        if let Some(visibility) = &visibility {
+            topic_new.add_temp_attribute_values(ATTRIBUTE_NAME_VISIBILITY.to_string(), visibility.clone());
+        }
+        topic_new.add_temp_attribute_values(ATTRIBUTE_NAME_ADDED.to_string(), vec![date_now_to_doc_format()]);
+        for paragraph in std::mem::replace(&mut section.paragraphs, vec![]).into_iter() {
+            topic_new.add_paragraph(paragraph);
+        }
+        model.add_topic(topic_new);
+        topic_keys_new.push(topic_key_new);
+    }
+
+    let link_count = update_section_links(model, topic_key, &sections);
+    println!("Split {} sections from {} into new topics, updating {} links.", sections.len(), topic_key, link_count);
+    Ok(topic_keys_new)
+}
+
+fn take_sections(paragraphs: Vec<Paragraph>, section_names: &Vec<&str>) -> (Vec<Paragraph>, Vec<SplitSection>) {
+    // A section runs until the next section header at the same or a shallower depth, or until
+    // the generated part of the page. The sections inside it move with it and are raised so that
+    // the shallowest becomes a top-level section in the new topic.
+    let section_names_lower = section_names.iter().map(|name| name.trim().to_lowercase()).collect::<Vec<_>>();
+    let mut paragraphs_keep = vec![];
+    let mut sections: Vec<SplitSection> = vec![];
+    let mut split_depth: Option<usize> = None;
+    let mut is_generated = false;
+    for paragraph in paragraphs.into_iter() {
+        match &paragraph {
+            Paragraph::GenStart => {
+                is_generated = true;
+                split_depth = None;
+            },
+            Paragraph::GenEnd => {
+                is_generated = false;
+            },
+            Paragraph::SectionHeader { name, depth, link_name: _ } if !is_generated => {
+                if split_depth.map_or(false, |split_depth| *depth <= split_depth) {
+                    split_depth = None;
+                }
+                if split_depth.is_none() && section_names_lower.contains(&name.to_lowercase()) {
+                    split_depth = Some(*depth);
+                    sections.push(SplitSection {
+                        name: name.clone(),
+                        subsection_names: vec![],
+                        paragraphs: vec![],
+                        topic_key: None,
+                    });
+                    continue;
+                }
+                if let Some(split_depth) = split_depth {
+                    let section = sections.last_mut().unwrap();
+                    section.subsection_names.push(name.clone());
+                    section.paragraphs.push(Paragraph::new_section_header(name, depth - split_depth));
+                    continue;
+                }
+            },
+            _ => {},
+        }
+        if split_depth.is_some() {
+            sections.last_mut().unwrap().paragraphs.push(paragraph);
+        } else {
+            paragraphs_keep.push(paragraph);
+        }
+    }
+    (paragraphs_keep, sections)
+}
+
+fn validate_sections(model: &Model, topic_key: &TopicKey, section_names: &Vec<&str>, sections: &mut Vec<SplitSection>) -> Result<(), String> {
+    // Every requested section has to be found, and each needs a topic name that isn't taken. A
+    // section name that's already a topic gets the original topic's name added, as in
+    // "Installation (Rust)".
+    for section_name in section_names.iter() {
+        if !sections.iter().any(|section| section.name.to_lowercase().eq(&section_name.trim().to_lowercase())) {
+            return Err(format!("Section \"{}\" not found in {}.", section_name, topic_key));
+        }
+    }
+    let mut topic_keys_new: Vec<TopicKey> = vec![];
+    for section in sections.iter_mut() {
+        let mut topic_key_new = TopicKey::new(topic_key.get_namespace(), &section.name);
+        if model.has_topic(&topic_key_new) || topic_keys_new.contains(&topic_key_new) {
+            topic_key_new = TopicKey::new(topic_key.get_namespace(), &format!("{} ({})", section.name, topic_key.get_topic_name()));
+        }
+        if !TopicKey::is_legal_topic_name(topic_key_new.get_topic_name()) {
+            return Err(format!("\"{}\" is not a legal topic name.", topic_key_new.get_topic_name()));
+        }
+        if model.has_topic(&topic_key_new) || topic_keys_new.contains(&topic_key_new) {
+            return Err(format!("Can't split section \"{}\" because {} already exists.", section.name, topic_key_new));
+        }
+        topic_keys_new.push(topic_key_new.clone());
+        section.topic_key = Some(topic_key_new);
+    }
+    Ok(())
+}
+
+fn update_section_links(model: &mut Model, topic_key: &TopicKey, sections: &Vec<SplitSection>) -> usize {
+    // Links within the moved sections themselves are included since a section may link to one
+    // of its own subsections.
+    let mut links: Vec<LinkRc> = vec![];
+    for topic in model.get_topics().values() {
+        links.append(&mut topic.get_links(true, true));
+    }
+    for glossary in model.get_glossaries().values() {
+        links.append(&mut glossary.get_links());
+    }
+    links.iter()
+        .filter(|link_rc| {
+            let mut link = link_rc.borrow_mut();
+            sections.iter().any(|section| link.update_split_section(topic_key, &section.name, &section.subsection_names, section.topic_key.as_ref().unwrap()))
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{LinkType, TextBlock, TextItem};
+
+    fn rust_paragraphs() -> Vec<Paragraph> {
+        vec![
+            Paragraph::new_text(TextBlock::new_resolved_text("Intro.")),
+            Paragraph::new_section_header("Installation", 1),
+            Paragraph::new_text(TextBlock::new_resolved_text("Install it.")),
+            Paragraph::new_section_header("Linux", 2),
+            Paragraph::new_text(TextBlock::new_resolved_text("Use the package manager.")),
+            Paragraph::new_section_header("Usage", 1),
+            Paragraph::new_text(TextBlock::new_resolved_text("Run it.")),
+        ]
+    }
+
+    fn section_headers(paragraphs: &Vec<Paragraph>) -> Vec<(String, usize)> {
+        paragraphs.iter()
+            .filter_map(|paragraph| match paragraph {
+                Paragraph::SectionHeader { name, depth, .. } => Some((name.clone(), *depth)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn take_sections_raises_subsections() {
+        let (paragraphs_keep, sections) = take_sections(rust_paragraphs(), &vec!["installation"]);
+        assert_eq!(section_headers(&paragraphs_keep), vec![("Usage".to_string(), 1)]);
+        assert_eq!(paragraphs_keep.len(), 3);
+        assert_eq!(sections.len(), 1);
+        let section = &sections[0];
+        assert_eq!(section.name, "Installation");
+        assert_eq!(section.subsection_names, vec!["Linux".to_string()]);
+        assert_eq!(section_headers(&section.paragraphs), vec![("Linux".to_string(), 1)]);
+        assert_eq!(section.paragraphs.len(), 3);
+    }
+
+    #[test]
+    fn split_topic_rewrites_section_links() {
+        let mut model = Model::new("Tools", "tools", None, None);
+        let mut rust = Topic::new("tools", "Rust");
+        for paragraph in rust_paragraphs().into_iter() {
+            rust.add_paragraph(paragraph);
+        }
+        model.add_topic(rust);
+        let mut cargo = Topic::new("tools", "Cargo");
+        cargo.add_paragraph(Paragraph::new_text(TextBlock::new_resolved(vec![
+            TextItem::new_link(Link::new_section(None, "tools", "Rust", "Installation")),
+            TextItem::new_link(Link::new_section(None, "tools", "Rust", "Linux")),
+            TextItem::new_link(Link::new_section(None, "tools", "Rust", "Usage")),
+        ])));
+        model.add_topic(cargo);
+
+        let topic_key_rust = TopicKey::new("tools", "Rust");
+        let topic_keys_new = split_topic(&mut model, &topic_key_rust, &vec!["Installation"]).unwrap();
+        let topic_key_new = TopicKey::new("tools", "Installation");
+        assert_eq!(topic_keys_new, vec![topic_key_new.clone()]);
+        let topic_new = model.get_topics().get(&topic_key_new).unwrap();
+        assert_eq!(section_headers(topic_new.get_paragraphs()), vec![("Linux".to_string(), 1)]);
+
+        let links = model.get_topics().get(&TopicKey::new("tools", "Cargo")).unwrap().get_links(false, false);
+        let dests = links.iter()
+            .map(|link_rc| match b!(link_rc).get_type() {
+                LinkType::Topic { topic_key } => (topic_key.get_topic_name().to_string(), None),
+                LinkType::Section { section_key } => (section_key.get_topic_key().get_topic_name().to_string(), Some(section_key.get_section_name().to_string())),
+                _ => panic!("Unexpected link type."),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(dests, vec![
+            ("Installation".to_string(), None),
+            ("Installation".to_string(), Some("Linux".to_string())),
+            ("Rust".to_string(), Some("Usage".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn split_topic_leaves_topic_alone_when_section_missing() {
+        let mut model = Model::new("Tools", "tools", None, None);
+        let mut rust = Topic::new("tools", "Rust");
+        for paragraph in rust_paragraphs().into_iter() {
+            rust.add_paragraph(paragraph);
+        }
+        model.add_topic(rust);
+        let topic_key_rust = TopicKey::new("tools", "Rust");
+        assert!(split_topic(&mut model, &topic_key_rust, &vec!["Installation", "Windows"]).is_err());
+        assert_eq!(model.get_topics().get(&topic_key_rust).unwrap().get_paragraphs().len(), 7);
+        assert_eq!(model.get_topics().len(), 1);
+    }
+}
@@ -11,7 +11,9 @@ pub(crate) struct Topic {
     parents: Vec<LinkRc>,
     namespace: String,
     name: String,
-    category: Option<String>,
+    // The first category is the primary one, which decides where the topic sits in the category
+    // tree. The topic is also listed in any others.
+    categories: Vec<String>,
     temp_attributes: BTreeMap<String, Vec<String>>,
     attributes: BTreeMap<String, AttributeInstance>,
     paragraphs: Vec<Paragraph>,
@@ -19,6 +21,10 @@ pub(crate) struct Topic {
     // outbound_links: Vec<Link>,
     // generated_outbound_links: Vec<Link>,
     category_tree_node: Option<Rc<RefCell<TopicTreeNode>>>,
+    // For a category topic, the topics that have this category or one of its subcategories as a
+    // secondary category, since the category tree only holds each topic under its primary one.
+    secondary_topics_in_category: Vec<TopicKey>,
+    secondary_topics_in_category_indirect: Vec<TopicKey>,
     subtopics: Vec<LinkRc>,
     subtopic_tree_node: Option<Rc<RefCell<TopicTreeNode>>>,
     combo_subtopics: Vec<LinkRc>,
@@ -46,7 +52,7 @@ impl Topic {
             parents: vec![],
             namespace: namespace.to_string(),
             name: name.to_string(),
-            category: None,
+            categories: vec![],
             temp_attributes: Default::default(),
             attributes: Default::default(),
             paragraphs: vec![],
@@ -54,6 +60,8 @@ impl Topic {
             // outbound_links: vec![],
             // generated_outbound_links: vec![],
             category_tree_node: None,
+            secondary_topics_in_category: vec![],
+            secondary_topics_in_category_indirect: vec![],
             subtopics: vec![],
             subtopic_tree_node: None,
             combo_subtopics: vec![],
@@ -82,6 +90,14 @@ impl Topic {
         self.parents = parents;
     }
 
+    pub(crate) fn take_parents(&mut self) -> Vec<LinkRc> {
+        std::mem::replace(&mut self.parents, vec![])
+    }
+
+    pub(crate) fn take_paragraphs(&mut self) -> Vec<Paragraph> {
+        std::mem::replace(&mut self.paragraphs, vec![])
+    }
+
     pub(crate) fn get_parent_count(&self) -> usize {
         self.parents.len()
     }
@@ -118,21 +134,58 @@ impl Topic {
     }
 
     pub(crate) fn get_category(&self) -> Option<String> {
-        if let Some(category) = &self.category {
-            Some(category.clone())
+        // The primary category.
+        self.categories.first().cloned()
+    }
+
+    pub(crate) fn get_categories(&self) -> &Vec<String> {
+        &self.categories
+    }
+
+    pub(crate) fn has_category(&self, category: &str) -> bool {
+        self.categories.iter().any(|topic_category| topic_category.eq(category))
+    }
+
+    pub(crate) fn set_name(&mut self, name: &str) {
+        // Only for renaming a topic. The model has to be updated at the same time since it holds
+        // the topic under its key.
+        TopicKey::assert_legal_topic_name(name);
+        self.name = name.to_string();
+    }
+
+    pub(crate) fn replace_category(&mut self, category_old: &str, category_new: &str) {
+        // If the topic already has the new category, as when two categories are merged, the old
+        // one is simply dropped. Otherwise the new one takes the old one's place, so a primary
+        // category stays primary.
+        debug_assert!(self.has_category(category_old));
+        if self.has_category(category_new) {
+            self.categories.retain(|category| category.ne(category_old));
         } else {
-            None
+            for category in self.categories.iter_mut().filter(|category| category.eq(&category_old)) {
+                *category = category_new.to_string();
+            }
         }
     }
 
     pub(crate) fn set_category(&mut self, category: &str) {
-        debug_assert!(self.category.is_none());
-        self.category = Some(category.to_string());
+        debug_assert!(self.categories.is_empty());
+        self.categories.push(category.to_string());
+    }
+
+    pub(crate) fn add_category(&mut self, category: &str, is_primary: bool) {
+        // A category added as primary goes to the front, and if the topic already had it as a
+        // secondary category it's moved there.
+        if is_primary {
+            self.categories.retain(|topic_category| topic_category.ne(category));
+            self.categories.insert(0, category.to_string());
+        } else if !self.has_category(category) {
+            self.categories.push(category.to_string());
+        }
     }
 
     #[allow(dead_code)]
     pub(crate) fn clear_category(&mut self) {
-        self.category = None;
+        self.categories.clear();
     }
 
     #[allow(dead_code)]
@@ -305,6 +358,32 @@ impl Topic {
             || self.has_attribute_value(ATTRIBUTE_NAME_VISIBILITY, ATTRIBUTE_VALUE_PUBLIC)
     }
 
+    pub(crate) fn get_audiences(&self) -> Vec<String> {
+        // The values of the Visibility attribute, like "Public" or "Team, Client Acme".
+        self.temp_attributes.get(ATTRIBUTE_NAME_VISIBILITY).cloned()
+            .or_else(|| self.attributes.get(ATTRIBUTE_NAME_VISIBILITY).map(|attr_instance| attr_instance.get_values().clone()))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn is_visible_to(&self, audience: &str) -> bool {
+        filter::is_visible_to_audience(&self.get_audiences(), audience)
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn is_included(&self) -> bool {
+        self.is_included
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn is_redacted(&self) -> bool {
+        self.is_redacted
+    }
+
+    pub(crate) fn set_filter_flags(&mut self, is_included: bool, is_redacted: bool) {
+        self.is_included = is_included;
+        self.is_redacted = is_redacted;
+    }
+
     /*
     pub(crate) fn set_attribute_date(&mut self, attr_type_name: &str, sequence: usize, value: &NaiveDate) {
         AttributeType::assert_legal_attribute_type_name(attr_type_name);
@@ -315,11 +394,18 @@ impl Topic {
         self.add_attribute(attr_instance);
     }
     */
-    /*
+    pub(crate) fn get_aliases(&self) -> Vec<String> {
+        // Other names for the topic, like "K8s" for Kubernetes, whether or not the attributes
+        // have been cataloged yet.
+        match self.temp_attributes.get(ATTRIBUTE_NAME_ALIASES) {
+            Some(values) => values.clone(),
+            None => self.attributes.get(ATTRIBUTE_NAME_ALIASES).map_or(vec![], |attr_instance| attr_instance.get_values().clone()),
+        }
+    }
+
     pub(crate) fn clear_attributes(&mut self) {
         self.attributes.clear()
     }
-    */
 
     pub(crate) fn get_paragraph_count(&self) -> usize {
         self.paragraphs.len()
@@ -459,6 +545,11 @@ impl Topic {
         self.category_tree_node = node
     }
 
+    pub(crate) fn set_secondary_topics_in_category(&mut self, topic_keys: Vec<TopicKey>, topic_keys_indirect: Vec<TopicKey>) {
+        self.secondary_topics_in_category = topic_keys;
+        self.secondary_topics_in_category_indirect = topic_keys_indirect;
+    }
+
     /*
     pub(crate) fn add_subtopic(&mut self, topic_key: TopicKey) {
         self.subtopics.push(topic_key);
@@ -493,6 +584,7 @@ impl Topic {
 
     pub(crate) fn is_category(&self) -> bool {
         self.category_tree_node.as_ref().map_or(false, |node| b!(&node).height() > 1)
+            || !self.secondary_topics_in_category.is_empty()
     }
 
     /*
@@ -514,30 +606,42 @@ impl Topic {
     */
 
     pub(crate) fn direct_topics_in_category(&self) -> Vec<TopicKey> {
-        match &self.category_tree_node {
+        let mut topic_keys = match &self.category_tree_node {
             Some(node_rc) => {
                 let node = b!(node_rc);
                 // If the child topic is a category topic, it will have at least one child of its
                 // own in the category tree and thus will not be a leaf.
                 let filter_func = |found_node: Ref<TopicTreeNode>| found_node.is_leaf();
-                let mut topic_keys = node.get_direct_child_items(&filter_func);
-                TopicKey::sort_topic_keys_by_name(&mut topic_keys);
-                topic_keys
+                node.get_direct_child_items(&filter_func)
             },
             None => vec![],
-        }
+        };
+        Self::add_secondary_topic_keys(&mut topic_keys, &self.secondary_topics_in_category);
+        TopicKey::sort_topic_keys_by_name(&mut topic_keys);
+        topic_keys
     }
 
     pub(crate) fn indirect_topics_in_category(&self) -> Vec<TopicKey> {
-        match &self.category_tree_node {
+        let mut topic_keys = match &self.category_tree_node {
             Some(node_rc) => {
                 let node = b!(node_rc);
                 let filter_func = |found_node: Ref<TopicTreeNode>| found_node.is_leaf();
-                let mut topic_keys = node.get_indirect_child_items(&filter_func);
-                TopicKey::sort_topic_keys_by_name(&mut topic_keys);
-                topic_keys
+                node.get_indirect_child_items(&filter_func)
             },
             None => vec![],
+        };
+        Self::add_secondary_topic_keys(&mut topic_keys, &self.secondary_topics_in_category_indirect);
+        TopicKey::sort_topic_keys_by_name(&mut topic_keys);
+        topic_keys
+    }
+
+    fn add_secondary_topic_keys(topic_keys: &mut Vec<TopicKey>, secondary_topic_keys: &Vec<TopicKey>) {
+        // A topic may already be in the list through its primary category, as when it's in both
+        // a category and one of that category's subcategories.
+        for topic_key in secondary_topic_keys.iter() {
+            if !topic_keys.contains(topic_key) {
+                topic_keys.push(topic_key.clone());
+            }
         }
     }
 
@@ -564,6 +668,18 @@ impl Topic {
         false
     }
 
+    pub(crate) fn get_closest_section_name(&self, section_name: &str) -> Option<String> {
+        // For suggesting a fix for a broken section link.
+        let sections = self.paragraphs.iter()
+            .filter_map(|paragraph| match paragraph {
+                Paragraph::SectionHeader { name, depth: _, link_name } => Some((name.clone(), link_name.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        closest_section_name(section_name, &sections)
+    }
+
+    #[allow(dead_code)]
     pub(crate) fn print_sections(&self) {
         println!("\nSections in {}", self.get_topic_key());
         for paragraph in self.paragraphs.iter() {
@@ -900,12 +1016,8 @@ impl Topic {
         text_blocks
     }
 
-    pub(crate) fn get_file_monitor_file<'a>(&self, summary: &'a file_monitor::summary::Summary) -> Option<&'a file_monitor::summary::MonitoredFile> {
-        let path = self.namespace.replace(":", "/");
-        let file_name = format!("{}.txt", legal_file_name(self.get_name()));
-        let key = format!("{}/{}", path, file_name);
-        // if self.name.eq("Yew (crate)") { dbg!(&key, summary.files.contains_key(&key)); }
-        summary.files.get(&key)
+    pub(crate) fn get_file_key(&self) -> String {
+        self.get_topic_key().get_file_key()
     }
 }
 
@@ -960,6 +1072,13 @@ impl TopicKey {
         &self.topic_name
     }
 
+    pub(crate) fn get_file_key(&self) -> String {
+        // The path of the page file relative to the pages folder, as used by the date providers.
+        let path = self.namespace.replace(":", "/");
+        let file_name = format!("{}.txt", legal_file_name(&self.topic_name));
+        format!("{}/{}", path, file_name)
+    }
+
     pub(crate) fn sort_topic_keys_by_name(vec: &mut Vec<TopicKey>) {
         vec.sort_by_cached_key(|topic_key| topic_key.topic_name.to_lowercase());
     }
@@ -1084,6 +1203,37 @@ impl Display for SectionKey {
     }
 }
 
+fn closest_section_name(section_name: &str, sections: &[(String, String)]) -> Option<String> {
+    // The sections are pairs of the name as it appears in the header and the anchor form, and a
+    // link may use either one. A section that's too far off, more than a third of the length of
+    // the name in the link, is probably not what the link meant, so nothing is suggested.
+    let section_name = section_name.to_lowercase();
+    let max_distance = 2.max(section_name.chars().count() / 3);
+    sections.iter()
+        .map(|(name, link_name)| (edit_distance(&name.to_lowercase(), &section_name).min(edit_distance(link_name, &section_name)), name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name.clone())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    // Levenshtein distance counting characters rather than bytes.
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for i in 1..=a.len() {
+        let mut diagonal = row[0];
+        row[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let value = (row[j] + 1).min(row[j - 1] + 1).min(diagonal + cost);
+            diagonal = row[j];
+            row[j] = value;
+        }
+    }
+    row[b.len()]
+}
+
 pub fn make_topic_ref(namespace: &str, topic_name: &str) -> String {
     // For now use the DokuWiki conventions. If we later need to generate a different wiki format,
     // create a function like this for each wiki engine and pass it to the gen process.
@@ -1091,3 +1241,24 @@ pub fn make_topic_ref(namespace: &str, topic_name: &str) -> String {
     let topic_ref = format!("{}{}{}", namespace, super::dokuwiki::DELIM_NAMESPACE, canonical_topic_name);
     topic_ref
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_characters() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("naïve", "naive"), 1);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn closest_section_name_ignores_unrelated_sections() {
+        let sections = vec![("Installation".to_string(), "installation".to_string()), ("Error Handling".to_string(), "error_handling".to_string())];
+        assert_eq!(closest_section_name("Instalation", &sections), Some("Installation".to_string()));
+        assert_eq!(closest_section_name("error_handlng", &sections), Some("Error Handling".to_string()));
+        assert_eq!(closest_section_name("Licenses", &sections), None);
+    }
+}
@@ -54,6 +54,11 @@ impl TopicErrorList {
         }
     }
 
+    pub(crate) fn print_without_panic(&self, context: Option<&str>) {
+        // For lists of things to follow up on rather than errors in the model.
+        self.print_internal(context);
+    }
+
     pub(crate) fn list_missing_topics(&self) {
         self.list_missing_topics_internal();
         if PANIC_ON_MODEL_ERROR && !self.is_empty() {